@@ -92,4 +92,18 @@ mod tests {
             sum
         });
     }
+
+    #[bench]
+    pub fn perft_make_unmake(b: &mut Bencher) {
+        let mut states = load_benchmarking_positions();
+        let mut movelist = ReservedMoveList::default();
+        let mut attack_container = ReservedAttackContainer::default();
+        b.iter(|| {
+            let mut sum = 0;
+            for i in 0..BENCHMARKING_POSITIONS_AMOUNT {
+                sum += core_sdk::perft_mut(&mut states[i], 2, &mut movelist, &mut attack_container);
+            }
+            sum
+        });
+    }
 }
@@ -1,15 +1,18 @@
 use crate::bitboards::bitboards::constants::{KING_ATTACKS, KNIGHT_ATTACKS};
 use crate::board_representation::game_state_attack_container::GameStateAttackContainer;
 use crate::board_representation::zobrist_hashing::ZOBRIST_KEYS;
+use crate::evaluation::eval_params::DEFAULT_EVAL_PARAMS;
+#[cfg(feature = "nnue")]
+use crate::evaluation::nnue::Accumulator;
 use crate::evaluation::params::*;
 use crate::evaluation::phase::Phase;
 use crate::evaluation::EvaluationScore;
-use crate::move_generation::makemove::make_move;
 use crate::move_generation::movegen::{
-    b_pawn_east_targets, b_pawn_west_targets, bishop_attack, double_push_pawn_targets,
-    generate_moves, pawn_east_targets, pawn_west_targets, rook_attack, single_push_pawn_targets,
-    w_pawn_east_targets, w_pawn_west_targets, MoveList,
+    b_pawn_east_targets, b_pawn_west_targets, bishop_attack, castle_permitted,
+    double_push_pawn_targets, generate_moves, get_checkers, pawn_east_targets, pawn_west_targets,
+    rook_attack, single_push_pawn_targets, w_pawn_east_targets, w_pawn_west_targets, MoveList,
 };
+pub use square_enum::{Bitboard, File, Rank, Square};
 use std::fmt::{Debug, Display, Formatter, Result};
 
 pub const PAWN: usize = 0;
@@ -20,6 +23,9 @@ pub const QUEEN: usize = 4;
 pub const KING: usize = 5;
 pub const WHITE: usize = 0;
 pub const BLACK: usize = 1;
+pub const KINGSIDE: usize = 0;
+pub const QUEENSIDE: usize = 1;
+pub const STANDARD_CASTLE_ROOK_FILES: [[u8; 2]; 2] = [[7, 0], [7, 0]];
 
 #[derive(PartialEq, Debug)]
 pub enum GameResult {
@@ -118,10 +124,36 @@ impl PieceType {
     }
 }
 
+#[derive(PartialEq, Clone, Debug, Copy)]
+pub enum Color {
+    White,
+    Black,
+}
+impl Color {
+    #[inline(always)]
+    pub fn to_index(self) -> usize {
+        match &self {
+            Color::White => WHITE,
+            Color::Black => BLACK,
+        }
+    }
+
+    #[inline(always)]
+    pub fn from_index(side: usize) -> Color {
+        if side == WHITE {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+}
+
+pub type Mailbox = [Option<(PieceType, Color)>; 64];
+
 #[derive(Copy, PartialEq)]
 pub struct GameMove {
-    pub from: u8,
-    pub to: u8,
+    pub from: Square,
+    pub to: Square,
     pub move_type: GameMoveType,
     pub piece_type: PieceType,
 }
@@ -160,133 +192,41 @@ impl GameMove {
             _ => panic!("Captured piece type  called on a capture"),
         }
     }
-    pub fn string_to_move(desc: &str) -> (usize, usize, Option<PieceType>) {
-        let mut chars = desc.chars();
-        let from_file = match chars.next() {
-            Some(s) => char_to_file(s),
-            _ => {
-                panic!("Invalid move desc!");
-            }
-        };
-        let from_rank = match chars.next() {
-            Some(s) => char_to_rank(s),
-            _ => {
-                panic!("Invalid move desc!");
-            }
-        };
-        let to_file = match chars.next() {
-            Some(s) => char_to_file(s),
-            _ => {
-                panic!("Invalid move desc!");
-            }
-        };
-        let to_rank = match chars.next() {
-            Some(s) => char_to_rank(s),
-            _ => {
-                panic!("Invalid move desc!");
-            }
+    pub fn string_to_move(
+        desc: &str,
+    ) -> std::result::Result<(Square, Square, Option<PieceType>), MoveError> {
+        let invalid = || MoveError::InvalidSyntax(desc.to_string());
+        if desc.len() != 4 && desc.len() != 5 {
+            return Err(invalid());
+        }
+        let parse_square = |range: std::ops::Range<usize>| -> Option<Square> {
+            desc.get(range).and_then(|square| square.parse().ok())
         };
+        let from = parse_square(0..2).ok_or_else(invalid)?;
+        let to = parse_square(2..4).ok_or_else(invalid)?;
         if desc.len() == 5 {
-            return (
-                from_file + 8 * from_rank,
-                to_file + 8 * to_rank,
-                Some(char_to_promotion_piecetype(match chars.next() {
-                    Some(s) => s,
-                    _ => panic!("Invalid move desc!"),
-                })),
-            );
-        }
-        (from_file + 8 * from_rank, to_file + 8 * to_rank, None)
+            let promotion = desc
+                .chars()
+                .nth(4)
+                .and_then(char_to_promotion_piecetype)
+                .ok_or_else(invalid)?;
+            return Ok((from, to, Some(promotion)));
+        }
+        Ok((from, to, None))
     }
 
     pub fn to_san(self, game_state: &GameState) -> String {
-        let mut movelist = MoveList::default();
-        let mut agsi = GameStateAttackContainer::from_state(game_state);
-        generate_moves(game_state, false, &mut movelist, &agsi);
-        let mut res_str = String::new();
-        if let GameMoveType::Castle = self.move_type {
-            if self.to == 2 || self.to == 58 {
-                res_str.push_str("O-O-O");
-            } else {
-                res_str.push_str("O-O");
-            }
-        } else {
-            res_str.push_str(match self.piece_type {
-                PieceType::Pawn => "",
-                PieceType::Knight => "N",
-                PieceType::Bishop => "B",
-                PieceType::Rook => "R",
-                PieceType::Queen => "Q",
-                PieceType::King => "K",
-            });
-            //Check for disambiguities
-            let mut file_needed = false;
-            let mut rank_needed = false;
-            for gmv in movelist.move_list.iter() {
-                let other_mv = gmv.0;
-                if other_mv.piece_type == self.piece_type
-                    && other_mv.to == self.to
-                    && other_mv.from != self.from
-                {
-                    if other_mv.from % 8 != self.from % 8 {
-                        file_needed = true;
-                    } else if other_mv.from / 8 != self.from / 8 {
-                        rank_needed = true;
-                    } else {
-                        file_needed = true;
-                        rank_needed = true;
-                    }
-                }
-            }
-            if file_needed {
-                res_str.push_str(file_to_string((self.from % 8) as usize));
-            }
-            if rank_needed {
-                res_str.push_str(&format!("{}", self.from / 8 + 1))
-            };
-            if self.is_capture() {
-                if self.piece_type == PieceType::Pawn && !file_needed {
-                    res_str.push_str(file_to_string((self.from % 8) as usize));
-                }
-                res_str.push_str("x");
-            }
-            res_str.push_str(file_to_string((self.to % 8) as usize));
-            res_str.push_str(&format!("{}", self.to / 8 + 1));
-            if let GameMoveType::Promotion(promo_piece, _) = self.move_type {
-                res_str.push_str(&format!(
-                    "={}",
-                    match promo_piece {
-                        PieceType::Queen => "Q",
-                        PieceType::Rook => "R",
-                        PieceType::Bishop => "B",
-                        PieceType::Knight => "N",
-                        _ => panic!("Invalid promotion piece"),
-                    }
-                ));
-            }
-        }
-        let game_state = make_move(game_state, self);
-        agsi.write_state(&game_state);
-        let agsi = generate_moves(&game_state, false, &mut movelist, &agsi);
-        if agsi.stm_incheck && !agsi.stm_haslegalmove {
-            res_str.push_str("#");
-        } else if agsi.stm_incheck {
-            res_str.push_str("+");
-        }
-        res_str
+        game_state
+            .moves_to_san(&[self])
+            .pop()
+            .expect("One SAN per move")
     }
 }
 
 impl Debug for GameMove {
     fn fmt(&self, formatter: &mut Formatter) -> Result {
         let mut res_str: String = String::new();
-        res_str.push_str(&format!(
-            "{}{}{}{}",
-            file_to_string((self.from % 8) as usize),
-            self.from / 8 + 1,
-            file_to_string((self.to % 8) as usize),
-            self.to / 8 + 1
-        ));
+        res_str.push_str(&format!("{}{}", self.from, self.to));
         if let GameMoveType::Promotion(s, _) = &self.move_type {
             match s {
                 PieceType::Queen => res_str.push_str("q"),
@@ -300,13 +240,13 @@ impl Debug for GameMove {
     }
 }
 
-fn char_to_promotion_piecetype(c: char) -> PieceType {
+fn char_to_promotion_piecetype(c: char) -> Option<PieceType> {
     match c {
-        'q' | 'Q' => PieceType::Queen,
-        'r' | 'R' => PieceType::Rook,
-        'b' | 'B' => PieceType::Bishop,
-        'n' | 'N' => PieceType::Knight,
-        _ => panic!("Invalid promotion piece {}", c),
+        'q' | 'Q' => Some(PieceType::Queen),
+        'r' | 'R' => Some(PieceType::Rook),
+        'b' | 'B' => Some(PieceType::Bishop),
+        'n' | 'N' => Some(PieceType::Knight),
+        _ => None,
     }
 }
 
@@ -356,6 +296,162 @@ fn file_to_string(file: usize) -> &'static str {
     }
 }
 
+fn side_to_string(side: usize) -> &'static str {
+    if side == WHITE {
+        "white"
+    } else {
+        "black"
+    }
+}
+
+fn squares_to_string(squares: Bitboard) -> String {
+    let squares: Vec<String> = squares.map(|square| square.to_string()).collect();
+    squares.join(" ")
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum FenError {
+    //Number of whitespace separated fields found, at least 4 are required
+    MissingFields(usize),
+    //Number of ranks found in the piece placement field
+    BadRankCount(usize),
+    //Offending character and the rank (1-8) it was found on
+    BadPieceChar(char, usize),
+    //Rank (1-8) and the number of files it describes
+    BadRankLength(usize, usize),
+    BadSideToMove(String),
+    IllegalCastling(String),
+    BadEnPassant(String),
+    BadMoveCounter(String),
+    //Side without a king
+    MissingKing(usize),
+    //Side with more than one king
+    TooManyKings(usize),
+    //The position can not arise in a game
+    IllegalPosition(Vec<PositionError>),
+}
+impl Display for FenError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        match self {
+            FenError::MissingFields(n) => {
+                write!(formatter, "expected at least 4 fields, found {}", n)
+            }
+            FenError::BadRankCount(n) => write!(formatter, "expected 8 ranks, found {}", n),
+            FenError::BadPieceChar(c, rank) => {
+                write!(formatter, "invalid piece '{}' on rank {}", c, rank)
+            }
+            FenError::BadRankLength(rank, files) => write!(
+                formatter,
+                "rank {} describes {} files instead of 8",
+                rank, files
+            ),
+            FenError::BadSideToMove(s) => write!(formatter, "invalid side to move '{}'", s),
+            FenError::IllegalCastling(s) => write!(formatter, "illegal castling field '{}'", s),
+            FenError::BadEnPassant(s) => write!(formatter, "invalid en passant square '{}'", s),
+            FenError::BadMoveCounter(s) => write!(formatter, "invalid move counter '{}'", s),
+            FenError::MissingKing(side) => {
+                write!(formatter, "{} has no king", side_to_string(*side))
+            }
+            FenError::TooManyKings(side) => {
+                write!(
+                    formatter,
+                    "{} has more than one king",
+                    side_to_string(*side)
+                )
+            }
+            FenError::IllegalPosition(errors) => {
+                write!(formatter, "illegal position: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(formatter, ", ")?;
+                    }
+                    write!(formatter, "{}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+impl std::error::Error for FenError {}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum PositionError {
+    //Side and its number of kings
+    KingCount(usize, u32),
+    //Squares holding more than one piece
+    OverlappingPieces(Bitboard),
+    //Pawns on the first or eighth rank
+    PawnsOnBackRank(Bitboard),
+    //Side with more pawns and promoted pieces than it could have
+    TooManyPieces(usize),
+    //The side not to move is in check
+    OpponentInCheck,
+    //Number of pieces giving check to the side to move
+    TooManyCheckers(u32),
+    //Side and castle side (KINGSIDE/QUEENSIDE) whose right does not match king and rook
+    CastlingRights(usize, usize),
+    InvalidEnPassant(Bitboard),
+}
+impl Display for PositionError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        match self {
+            PositionError::KingCount(side, n) => {
+                write!(formatter, "{} has {} kings", side_to_string(*side), n)
+            }
+            PositionError::OverlappingPieces(squares) => write!(
+                formatter,
+                "more than one piece on {}",
+                squares_to_string(*squares)
+            ),
+            PositionError::PawnsOnBackRank(squares) => write!(
+                formatter,
+                "pawns on back rank {}",
+                squares_to_string(*squares)
+            ),
+            PositionError::TooManyPieces(side) => {
+                write!(formatter, "{} has too many pieces", side_to_string(*side))
+            }
+            PositionError::OpponentInCheck => write!(formatter, "side not to move is in check"),
+            PositionError::TooManyCheckers(n) => {
+                write!(formatter, "side to move is checked by {} pieces", n)
+            }
+            PositionError::CastlingRights(side, castle_side) => write!(
+                formatter,
+                "{} can not castle {}",
+                side_to_string(*side),
+                if *castle_side == KINGSIDE {
+                    "kingside"
+                } else {
+                    "queenside"
+                }
+            ),
+            PositionError::InvalidEnPassant(squares) => write!(
+                formatter,
+                "impossible en passant square {}",
+                squares_to_string(*squares)
+            ),
+        }
+    }
+}
+impl std::error::Error for PositionError {}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum MoveError {
+    //The string is not a move in long algebraic notation such as e2e4 or e7e8q
+    InvalidSyntax(String),
+    //No legal move in the position matches the string
+    IllegalMove(String),
+}
+impl Display for MoveError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        match self {
+            MoveError::InvalidSyntax(s) => write!(formatter, "invalid move '{}'", s),
+            MoveError::IllegalMove(s) => write!(formatter, "illegal move '{}'", s),
+        }
+    }
+}
+impl std::error::Error for MoveError {}
+
 pub struct GameState {
     // 0 = White
     // 1 = Black
@@ -373,220 +469,208 @@ pub struct GameState {
     // 0 -> White
     // 1 -> Black
     pub pieces: [[u64; 2]; 6],
+    //Piece on every square, kept in sync with the bitboards
+    pub mailbox: Mailbox,
 
     //Castle flags
     pub castle_white_kingside: bool,
     pub castle_white_queenside: bool,
     pub castle_black_kingside: bool,
     pub castle_black_queenside: bool,
+    //Files of the rooks the castle flags refer to
+    //Index 1: Side
+    //Index 2:
+    // 0 -> Kingside
+    // 1 -> Queenside
+    pub castle_rook_files: [[u8; 2]; 2],
+    //Chess960 games write castling moves as king takes rook
+    pub chess960: bool,
 
-    pub en_passant: u64,
+    pub en_passant: Bitboard,
     //50 move draw counter
     pub half_moves: usize,
     pub full_moves: usize,
     pub hash: u64,
+    //Zobrist key of the pawns alone, which indexes the pawn hash table
+    pub pawn_hash: u64,
     pub psqt: EvaluationScore,
     pub phase: Phase,
+    #[cfg(feature = "nnue")]
+    pub nnue: Accumulator,
 }
 
 impl GameState {
+    //Only checks the syntax of the FEN, so that positions which fail validate() can still be set up
     pub fn from_fen(fen: &str) -> GameState {
-        let vec: Vec<&str> = fen.trim().split(' ').collect();
+        match GameState::parse_fen(fen) {
+            Ok(state) => state,
+            Err(e) => panic!("Invalid FEN {}: {}", fen, e),
+        }
+    }
+
+    pub fn try_from_fen(fen: &str) -> std::result::Result<GameState, FenError> {
+        let state = GameState::parse_fen(fen)?;
+        state.validate().map_err(FenError::IllegalPosition)?;
+        Ok(state)
+    }
+
+    //Parses a move as sent over UCI. Castling is expected as king takes rook if chess960 is set
+    pub fn parse_uci_move(&self, desc: &str) -> std::result::Result<GameMove, MoveError> {
+        let (from, to, promotion) = GameMove::string_to_move(desc)?;
+        let mut movelist = MoveList::default();
+        let attack_container = GameStateAttackContainer::from_state(self);
+        generate_moves(self, false, &mut movelist, &attack_container);
+        movelist
+            .move_list
+            .iter()
+            .map(|gmv| gmv.0)
+            .find(|mv| {
+                mv.from == from
+                    && mv.to == to
+                    && promotion
+                        == match mv.move_type {
+                            GameMoveType::Promotion(piece, _) => Some(piece),
+                            _ => None,
+                        }
+            })
+            .ok_or_else(|| MoveError::IllegalMove(desc.to_string()))
+    }
+
+    pub fn is_legal(&self, mv: GameMove) -> bool {
+        let mut movelist = MoveList::default();
+        let attack_container = GameStateAttackContainer::from_state(self);
+        generate_moves(self, false, &mut movelist, &attack_container);
+        movelist.move_list.iter().any(|gmv| gmv.0 == mv)
+    }
+
+    fn parse_fen(fen: &str) -> std::result::Result<GameState, FenError> {
+        let vec: Vec<&str> = fen.split_whitespace().collect();
         if vec.len() < 4 {
-            panic!("Invalid FEN");
+            return Err(FenError::MissingFields(vec.len()));
         }
         //Parse through FEN
         //Pieces
         let pieces: Vec<&str> = vec[0].split('/').collect();
         if pieces.len() != 8 {
-            panic!("Invalid FEN");
+            return Err(FenError::BadRankCount(pieces.len()));
         }
         //Iterate over all 8 ranks
         let mut pieces_arr: [[u64; 2]; 6] = [[0u64; 2]; 6];
-        for (rank, rank_str) in pieces.iter().enumerate().take(8) {
+        for (rank_idx, rank_str) in pieces.iter().enumerate() {
+            let rank = 7 - rank_idx;
             let mut file: usize = 0;
-            let mut rank_str_idx: usize = 0;
-            while file < 8 {
-                let idx = (7 - rank) * 8 + file;
-                let next_char = rank_str.chars().nth(rank_str_idx);
-                rank_str_idx += 1;
-                match next_char {
-                    Some(x) => match x {
-                        'P' => {
-                            pieces_arr[PAWN][WHITE] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'p' => {
-                            pieces_arr[PAWN][BLACK] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'N' => {
-                            pieces_arr[KNIGHT][WHITE] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'n' => {
-                            pieces_arr[KNIGHT][BLACK] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'B' => {
-                            pieces_arr[BISHOP][WHITE] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'b' => {
-                            pieces_arr[BISHOP][BLACK] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'R' => {
-                            pieces_arr[ROOK][WHITE] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'r' => {
-                            pieces_arr[ROOK][BLACK] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'Q' => {
-                            pieces_arr[QUEEN][WHITE] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'q' => {
-                            pieces_arr[QUEEN][BLACK] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'K' => {
-                            pieces_arr[KING][WHITE] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        'k' => {
-                            pieces_arr[KING][BLACK] |= 1u64 << idx;
-                            file += 1;
-                        }
-                        '1' => {
-                            file += 1;
-                        }
-                        '2' => {
-                            file += 2;
-                        }
-                        '3' => {
-                            file += 3;
-                        }
-                        '4' => {
-                            file += 4;
-                        }
-                        '5' => {
-                            file += 5;
-                        }
-                        '6' => {
-                            file += 6;
-                        }
-                        '7' => {
-                            file += 7;
-                        }
-                        '8' => {
-                            file += 8;
-                        }
-                        _ => {
-                            panic!("Invalid FEN");
+            for c in rank_str.chars() {
+                match c {
+                    '1'..='8' => {
+                        file += c.to_digit(10).unwrap() as usize;
+                    }
+                    _ => {
+                        let piece = match c.to_ascii_lowercase() {
+                            'p' => PAWN,
+                            'n' => KNIGHT,
+                            'b' => BISHOP,
+                            'r' => ROOK,
+                            'q' => QUEEN,
+                            'k' => KING,
+                            _ => return Err(FenError::BadPieceChar(c, rank + 1)),
+                        };
+                        let side = if c.is_ascii_uppercase() { WHITE } else { BLACK };
+                        if file < 8 {
+                            pieces_arr[piece][side] |= 1u64 << (rank * 8 + file);
                         }
-                    },
-                    None => panic!("Invalid FEN"),
+                        file += 1;
+                    }
                 }
             }
+            if file != 8 {
+                return Err(FenError::BadRankLength(rank + 1, file));
+            }
+        }
+        for (side, kings) in pieces_arr[KING].iter().enumerate() {
+            match kings.count_ones() {
+                0 => return Err(FenError::MissingKing(side)),
+                1 => {}
+                _ => return Err(FenError::TooManyKings(side)),
+            }
         }
 
         //Side to move
         let color_to_move = match vec[1] {
-            "w" => 0,
-            "b" => 1,
-            _ => panic!("Invalid FEN!"),
+            "w" => WHITE,
+            "b" => BLACK,
+            _ => return Err(FenError::BadSideToMove(vec[1].to_owned())),
         };
 
         //Castling-Abilities
-        let castle_white_kingside = vec[2].contains('K');
-        let castle_white_queenside = vec[2].contains('Q');
-        let castle_black_kingside = vec[2].contains('k');
-        let castle_black_queenside = vec[2].contains('q');
-
-        //En passant target square
-        let en_passant: u64 = if vec[3] != "-" {
-            let mut idx: usize = 0usize;
-            let file = vec[3].chars().next();
-            let rank = vec[3].chars().nth(1);
-            match file {
-                Some(x) => match x {
-                    'a' | 'A' => {}
-                    'b' | 'B' => {
-                        idx += 1;
-                    }
-                    'c' | 'C' => {
-                        idx += 2;
-                    }
-                    'd' | 'D' => {
-                        idx += 3;
-                    }
-                    'e' | 'E' => {
-                        idx += 4;
-                    }
-                    'f' | 'F' => {
-                        idx += 5;
-                    }
-                    'g' | 'G' => {
-                        idx += 6;
-                    }
-                    'h' | 'H' => {
-                        idx += 7;
-                    }
-                    _ => {
-                        panic!("Invalid FEN!");
+        //Besides KQkq we accept X-FEN, where KQkq mean the outermost rook, and Shredder-FEN,
+        //which names the file of the castling rook
+        let mut castle_rights = [[false; 2]; 2];
+        let mut castle_rook_files = STANDARD_CASTLE_ROOK_FILES;
+        let mut chess960 = false;
+        if vec[2] != "-" {
+            for c in vec[2].chars() {
+                let side = if c.is_ascii_uppercase() { WHITE } else { BLACK };
+                let back_rank = if side == WHITE { 0 } else { 56 };
+                let king_file = pieces_arr[KING][side].trailing_zeros() as usize % 8;
+                let rooks = pieces_arr[ROOK][side] >> back_rank & 0xFF;
+                let rook_file = match c.to_ascii_lowercase() {
+                    'k' => (king_file + 1..8)
+                        .rev()
+                        .find(|file| rooks & (1u64 << file) != 0u64)
+                        .unwrap_or(7),
+                    'q' => (0..king_file)
+                        .find(|file| rooks & (1u64 << file) != 0u64)
+                        .unwrap_or(0),
+                    'a'..='h' => {
+                        let file = char_to_file(c.to_ascii_lowercase());
+                        if rooks & (1u64 << file) == 0u64 {
+                            return Err(FenError::IllegalCastling(vec[2].to_owned()));
+                        }
+                        chess960 = true;
+                        file
                     }
-                },
-                None => {
-                    panic!("Invalid FEN!");
+                    _ => return Err(FenError::IllegalCastling(vec[2].to_owned())),
+                };
+                let castle_side = if rook_file > king_file {
+                    KINGSIDE
+                } else {
+                    QUEENSIDE
+                };
+                if castle_rights[side][castle_side] {
+                    return Err(FenError::IllegalCastling(vec[2].to_owned()));
                 }
-            }
-            match rank {
-                Some(x) => match x {
-                    '1' => {}
-                    '2' => {
-                        idx += 8;
-                    }
-                    '3' => {
-                        idx += 16;
-                    }
-                    '4' => {
-                        idx += 24;
-                    }
-                    '5' => {
-                        idx += 32;
-                    }
-                    '6' => {
-                        idx += 40;
-                    }
-                    '7' => {
-                        idx += 48;
-                    }
-                    '8' => {
-                        idx += 56;
-                    }
-                    _ => {
-                        panic!("Invalid FEN!");
-                    }
-                },
-                None => {
-                    panic!("Invalid FEN!");
+                castle_rights[side][castle_side] = true;
+                castle_rook_files[side][castle_side] = rook_file as u8;
+                if king_file != 4 || rook_file != 0 && rook_file != 7 {
+                    chess960 = true;
                 }
             }
-            1u64 << idx
+        }
+        let castle_white_kingside = castle_rights[WHITE][KINGSIDE];
+        let castle_white_queenside = castle_rights[WHITE][QUEENSIDE];
+        let castle_black_kingside = castle_rights[BLACK][KINGSIDE];
+        let castle_black_queenside = castle_rights[BLACK][QUEENSIDE];
+
+        //En passant target square
+        //The target square has to lie behind a pawn of the side that is not to move
+        let en_passant = if vec[3] != "-" {
+            let chars: Vec<char> = vec[3].to_ascii_lowercase().chars().collect();
+            let expected_rank = if color_to_move == WHITE { '6' } else { '3' };
+            if chars.len() != 2 || !('a'..='h').contains(&chars[0]) || chars[1] != expected_rank {
+                return Err(FenError::BadEnPassant(vec[3].to_owned()));
+            }
+            Bitboard(1u64 << (char_to_rank(chars[1]) * 8 + char_to_file(chars[0])))
         } else {
-            0u64
+            Bitboard::EMPTY
         };
-        let (half_moves, full_moves) = if vec.len() > 4 {
-            (
-                vec[4].parse().expect("unable to parse half moves"),
-                vec[5].parse().expect("unable to parse full moves"),
-            )
-        } else {
-            (0, 1)
+        let parse_counter = |field: Option<&&str>, default: usize| match field {
+            Some(s) => s
+                .parse()
+                .map_err(|_| FenError::BadMoveCounter((*s).to_owned())),
+            None => Ok(default),
         };
+        let half_moves = parse_counter(vec.get(4), 0)?;
+        let full_moves = parse_counter(vec.get(5), 1)?;
         let hash = GameState::calculate_zobrist_hash(
             color_to_move,
             pieces_arr,
@@ -598,59 +682,101 @@ impl GameState {
         );
         let mut _eval = crate::evaluation::EvaluationResult {
             final_eval: 0,
-            #[cfg(feature = "texel-tuning")]
-            trace: crate::evaluation::trace::Trace::default(),
+            trace: None,
         };
-        let p_w = crate::evaluation::psqt_evaluation::psqt(true, &pieces_arr, &mut _eval);
-        let p_b = crate::evaluation::psqt_evaluation::psqt(false, &pieces_arr, &mut _eval);
+        let p_w = crate::evaluation::psqt_evaluation::psqt(
+            true,
+            &pieces_arr,
+            &DEFAULT_EVAL_PARAMS,
+            &mut _eval,
+        );
+        let p_b = crate::evaluation::psqt_evaluation::psqt(
+            false,
+            &pieces_arr,
+            &DEFAULT_EVAL_PARAMS,
+            &mut _eval,
+        );
         let phase = Phase::from_pieces(&pieces_arr);
-        GameState {
+        Ok(GameState {
             color_to_move,
             pieces: pieces_arr,
+            mailbox: GameState::mailbox_from_pieces(&pieces_arr),
             castle_white_kingside,
             castle_white_queenside,
             castle_black_kingside,
             castle_black_queenside,
+            castle_rook_files,
+            chess960,
             half_moves,
             full_moves,
             en_passant,
             hash,
+            pawn_hash: GameState::calculate_pawn_hash(&pieces_arr),
             psqt: p_w - p_b,
             phase,
+            #[cfg(feature = "nnue")]
+            nnue: Accumulator::default(),
+        })
+    }
+
+    #[inline(always)]
+    pub fn piece_on(&self, square: Square) -> Option<(PieceType, Color)> {
+        self.mailbox[square.index()]
+    }
+
+    pub fn get_piece_on(&self, square: Square) -> &str {
+        match self.piece_on(square) {
+            Some((piece, Color::White)) => match piece {
+                PieceType::Pawn => "P",
+                PieceType::Knight => "N",
+                PieceType::Bishop => "B",
+                PieceType::Rook => "R",
+                PieceType::Queen => "Q",
+                PieceType::King => "K",
+            },
+            Some((piece, Color::Black)) => match piece {
+                PieceType::Pawn => "p",
+                PieceType::Knight => "n",
+                PieceType::Bishop => "b",
+                PieceType::Rook => "r",
+                PieceType::Queen => "q",
+                PieceType::King => "k",
+            },
+            None => " ",
         }
     }
 
-    pub fn get_piece_on(&self, shift: i32) -> &str {
-        if (self.pieces[PAWN][WHITE] >> shift) & 1u64 != 0u64 {
-            "P"
-        } else if (self.pieces[KNIGHT][WHITE] >> shift) & 1u64 != 0u64 {
-            "N"
-        } else if (self.pieces[BISHOP][WHITE] >> shift) & 1u64 != 0u64 {
-            "B"
-        } else if (self.pieces[ROOK][WHITE] >> shift) & 1u64 != 0u64 {
-            "R"
-        } else if (self.pieces[QUEEN][WHITE] >> shift) & 1u64 != 0u64 {
-            "Q"
-        } else if (self.pieces[KING][WHITE] >> shift) & 1u64 != 0u64 {
-            "K"
-        } else if (self.pieces[PAWN][BLACK] >> shift) & 1u64 != 0u64 {
-            "p"
-        } else if (self.pieces[KNIGHT][BLACK] >> shift) & 1u64 != 0u64 {
-            "n"
-        } else if (self.pieces[BISHOP][BLACK] >> shift) & 1u64 != 0u64 {
-            "b"
-        } else if (self.pieces[ROOK][BLACK] >> shift) & 1u64 != 0u64 {
-            "r"
-        } else if (self.pieces[QUEEN][BLACK] >> shift) & 1u64 != 0u64 {
-            "q"
-        } else if (self.pieces[KING][BLACK] >> shift) & 1u64 != 0u64 {
-            "k"
-        } else {
-            " "
+    pub fn mailbox_from_pieces(pieces: &[[u64; 2]; 6]) -> Mailbox {
+        let mut mailbox = [None; 64];
+        for &piece in [
+            PieceType::Pawn,
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+            PieceType::King,
+        ]
+        .iter()
+        {
+            for (side, &board) in pieces[piece.to_index()].iter().enumerate() {
+                for square in Bitboard(board) {
+                    mailbox[square.index()] = Some((piece, Color::from_index(side)));
+                }
+            }
         }
+        mailbox
     }
 
     pub fn to_fen(&self) -> String {
+        self.fen_with_castling(false)
+    }
+
+    //Same as to_fen, but castling rights are always written as rook files
+    pub fn to_shredder_fen(&self) -> String {
+        self.fen_with_castling(true)
+    }
+
+    fn fen_with_castling(&self, shredder: bool) -> String {
         let mut res_str = String::new();
         for rank in 0..8 {
             let big_endian_rank = 7 - rank;
@@ -658,10 +784,10 @@ impl GameState {
             let mut file = 0;
             let mut files_skipped = 0;
             while file < 8 {
-                let shift = big_endian_rank * 8 + file;
+                let square = Square::ALL[big_endian_rank * 8 + file];
                 file += 1;
                 files_skipped += 1;
-                let piece_on = self.get_piece_on(shift);
+                let piece_on = self.get_piece_on(square);
 
                 if piece_on != " " {
                     if files_skipped != 1 {
@@ -692,28 +818,43 @@ impl GameState {
         {
             res_str.push_str("-");
         } else {
-            if self.castle_white_kingside {
-                res_str.push_str("K");
-            }
-            if self.castle_white_queenside {
-                res_str.push_str("Q");
-            }
-            if self.castle_black_kingside {
-                res_str.push_str("k");
-            }
-            if self.castle_black_queenside {
-                res_str.push_str("q");
+            let rights = [
+                (WHITE, KINGSIDE, self.castle_white_kingside),
+                (WHITE, QUEENSIDE, self.castle_white_queenside),
+                (BLACK, KINGSIDE, self.castle_black_kingside),
+                (BLACK, QUEENSIDE, self.castle_black_queenside),
+            ];
+            for &(side, castle_side, has_right) in rights.iter() {
+                if !has_right {
+                    continue;
+                }
+                let rook_file = self.castle_rook_files[side][castle_side] as usize;
+                //X-FEN only needs the file if another rook stands further outside
+                let rooks = self.pieces[ROOK][side] >> (side * 56) & 0xFF;
+                let outer_files = if castle_side == KINGSIDE {
+                    0xFFu64 << rook_file << 1 & 0xFF
+                } else {
+                    (1u64 << rook_file) - 1
+                };
+                let c = if shredder || self.chess960 && rooks & outer_files != 0u64 {
+                    file_to_string(rook_file)
+                } else if castle_side == KINGSIDE {
+                    "k"
+                } else {
+                    "q"
+                };
+                if side == WHITE {
+                    res_str.push_str(&c.to_uppercase());
+                } else {
+                    res_str.push_str(c);
+                }
             }
         }
         res_str.push_str(" ");
 
-        if self.en_passant == 0u64 {
-            res_str.push_str("-");
-        } else {
-            let idx = self.en_passant.trailing_zeros() as usize;
-            let rank = idx / 8;
-            let file = idx % 8;
-            res_str.push_str(&format!("{}{}", file_to_string(file), rank + 1));
+        match self.en_passant_square() {
+            Some(square) => res_str.push_str(&square.to_string()),
+            None => res_str.push_str("-"),
         }
         res_str.push_str(" ");
         res_str.push_str(&format!("{} ", self.half_moves));
@@ -733,20 +874,32 @@ impl GameState {
         ];
         let mut _eval = crate::evaluation::EvaluationResult {
             final_eval: 0,
-            #[cfg(feature = "texel-tuning")]
-            trace: crate::evaluation::trace::Trace::default(),
+            trace: None,
         };
-        let p_w = crate::evaluation::psqt_evaluation::psqt(true, &pieces, &mut _eval);
-        let p_b = crate::evaluation::psqt_evaluation::psqt(false, &pieces, &mut _eval);
+        let p_w = crate::evaluation::psqt_evaluation::psqt(
+            true,
+            &pieces,
+            &DEFAULT_EVAL_PARAMS,
+            &mut _eval,
+        );
+        let p_b = crate::evaluation::psqt_evaluation::psqt(
+            false,
+            &pieces,
+            &DEFAULT_EVAL_PARAMS,
+            &mut _eval,
+        );
         let phase = Phase::from_pieces(&pieces);
         GameState {
             color_to_move,
             pieces,
+            mailbox: GameState::mailbox_from_pieces(&pieces),
             castle_white_kingside: true,
             castle_white_queenside: true,
             castle_black_kingside: true,
             castle_black_queenside: true,
-            en_passant: 0u64,
+            castle_rook_files: STANDARD_CASTLE_ROOK_FILES,
+            chess960: false,
+            en_passant: Bitboard::EMPTY,
             half_moves: 0usize,
             full_moves: 1usize,
             hash: GameState::calculate_zobrist_hash(
@@ -756,10 +909,13 @@ impl GameState {
                 true,
                 true,
                 true,
-                0u64,
+                Bitboard::EMPTY,
             ),
+            pawn_hash: GameState::calculate_pawn_hash(&pieces),
             psqt: p_w - p_b,
             phase,
+            #[cfg(feature = "nnue")]
+            nnue: Accumulator::default(),
         }
     }
 
@@ -770,7 +926,7 @@ impl GameState {
         cwq: bool,
         cbk: bool,
         cbq: bool,
-        ep: u64,
+        ep: Bitboard,
     ) -> u64 {
         let mut hash = 0u64;
         if color_to_move == 1 {
@@ -788,9 +944,8 @@ impl GameState {
         if cbq {
             hash ^= ZOBRIST_KEYS.castle_b_queenside;
         }
-        if ep != 0u64 {
-            let file = ep.trailing_zeros() as usize % 8;
-            hash ^= ZOBRIST_KEYS.en_passant[file];
+        if let Some(square) = ep.lsb() {
+            hash ^= ZOBRIST_KEYS.en_passant[square.file().index()];
         }
         //W Pawns
         let mut w_pawns = pieces[PAWN][WHITE];
@@ -868,6 +1023,23 @@ impl GameState {
         hash
     }
 
+    pub fn calculate_pawn_hash(pieces: &[[u64; 2]; 6]) -> u64 {
+        let mut hash = 0u64;
+        let mut w_pawns = pieces[PAWN][WHITE];
+        while w_pawns != 0u64 {
+            let idx = w_pawns.trailing_zeros() as usize;
+            hash ^= ZOBRIST_KEYS.w_pawns[idx];
+            w_pawns ^= 1u64 << idx;
+        }
+        let mut b_pawns = pieces[PAWN][BLACK];
+        while b_pawns != 0u64 {
+            let idx = b_pawns.trailing_zeros() as usize;
+            hash ^= ZOBRIST_KEYS.b_pawns[idx];
+            b_pawns ^= 1u64 << idx;
+        }
+        hash
+    }
+
     #[inline(always)]
     pub fn get_pieces_from_side(&self, side: usize) -> u64 {
         self.get_pieces_from_side_without_king(side) | self.pieces[KING][side]
@@ -888,8 +1060,35 @@ impl GameState {
     }
 
     #[inline(always)]
-    pub fn king_square(&self, side: usize) -> usize {
-        self.pieces[KING][side].trailing_zeros() as usize
+    pub fn piece_bitboard(&self, piece: PieceType, side: usize) -> Bitboard {
+        Bitboard(self.pieces[piece.to_index()][side])
+    }
+
+    #[inline(always)]
+    pub fn en_passant_square(&self) -> Option<Square> {
+        self.en_passant.lsb()
+    }
+
+    #[inline(always)]
+    pub fn king_square(&self, side: usize) -> Square {
+        Square::ALL[self.pieces[KING][side].trailing_zeros() as usize]
+    }
+
+    #[inline(always)]
+    pub fn castle_rook_square(&self, side: usize, kingside: bool) -> Square {
+        let castle_side = if kingside { KINGSIDE } else { QUEENSIDE };
+        Square::ALL[side * 56 + self.castle_rook_files[side][castle_side] as usize]
+    }
+
+    //The to-square of a castling move: the king's target square in standard chess,
+    //the square of the castling rook in Chess960
+    #[inline(always)]
+    pub fn castle_target_square(&self, side: usize, kingside: bool) -> Square {
+        if self.chess960 {
+            self.castle_rook_square(side, kingside)
+        } else {
+            Square::ALL[side * 56 + if kingside { 6 } else { 2 }]
+        }
     }
 
     #[inline(always)]
@@ -900,15 +1099,126 @@ impl GameState {
             || self.pieces[QUEEN][side] != 0u64
     }
 
+    //Checks whether the position could have arisen in a game and reports every problem found
+    pub fn validate(&self) -> std::result::Result<(), Vec<PositionError>> {
+        let mut errors = Vec::new();
+        let mut occupied = 0u64;
+        let mut overlapping = 0u64;
+        for piece in self.pieces.iter() {
+            for bb in piece.iter() {
+                overlapping |= occupied & *bb;
+                occupied |= *bb;
+            }
+        }
+        if overlapping != 0u64 {
+            errors.push(PositionError::OverlappingPieces(Bitboard(overlapping)));
+        }
+        let back_ranks = 0xFFu64 | 0xFFu64 << 56;
+        let pawns_on_back_rank = (self.pieces[PAWN][WHITE] | self.pieces[PAWN][BLACK]) & back_ranks;
+        if pawns_on_back_rank != 0u64 {
+            errors.push(PositionError::PawnsOnBackRank(Bitboard(pawns_on_back_rank)));
+        }
+        for side in 0..2 {
+            let kings = self.pieces[KING][side].count_ones();
+            if kings != 1 {
+                errors.push(PositionError::KingCount(side, kings));
+            }
+            //Every piece beyond the initial set has to be a promoted pawn
+            let count = |piece: usize| self.pieces[piece][side].count_ones() as usize;
+            let promoted = count(KNIGHT).saturating_sub(2)
+                + count(BISHOP).saturating_sub(2)
+                + count(ROOK).saturating_sub(2)
+                + count(QUEEN).saturating_sub(1);
+            if count(PAWN) + promoted > 8 {
+                errors.push(PositionError::TooManyPieces(side));
+            }
+        }
+        //The remaining checks need exactly one king per side
+        if self.pieces[KING][WHITE].count_ones() != 1 || self.pieces[KING][BLACK].count_ones() != 1
+        {
+            return Err(errors);
+        }
+
+        let us = self.color_to_move;
+        let them = 1 - us;
+        let enemy_king = self.king_square(them).index();
+        let all_pieces = self.get_all_pieces();
+        let attackers = KNIGHT_ATTACKS[enemy_king] & self.pieces[KNIGHT][us]
+            | KING_ATTACKS[enemy_king] & self.pieces[KING][us]
+            | (pawn_west_targets(them, 1u64 << enemy_king)
+                | pawn_east_targets(them, 1u64 << enemy_king))
+                & self.pieces[PAWN][us]
+            | bishop_attack(enemy_king, all_pieces)
+                & (self.pieces[BISHOP][us] | self.pieces[QUEEN][us])
+            | rook_attack(enemy_king, all_pieces)
+                & (self.pieces[ROOK][us] | self.pieces[QUEEN][us]);
+        if attackers != 0u64 {
+            errors.push(PositionError::OpponentInCheck);
+        }
+        let checkers = get_checkers(self, false).count_ones();
+        if checkers > 2 {
+            errors.push(PositionError::TooManyCheckers(checkers));
+        }
+
+        let castle_rights = [
+            [self.castle_white_kingside, self.castle_white_queenside],
+            [self.castle_black_kingside, self.castle_black_queenside],
+        ];
+        for (side, rights) in castle_rights.iter().enumerate() {
+            for &castle_side in [KINGSIDE, QUEENSIDE].iter() {
+                if !rights[castle_side] {
+                    continue;
+                }
+                let king_square = self.king_square(side);
+                let rook_square = self.castle_rook_square(side, castle_side == KINGSIDE);
+                let standard_placement = king_square.file() == File::E
+                    && rook_square.file().index()
+                        == STANDARD_CASTLE_ROOK_FILES[side][castle_side] as usize;
+                if king_square.rank().index() != side * 7
+                    || !Bitboard(self.pieces[ROOK][side]).contains(rook_square)
+                    || (rook_square > king_square) != (castle_side == KINGSIDE)
+                    || !self.chess960 && !standard_placement
+                {
+                    errors.push(PositionError::CastlingRights(side, castle_side));
+                }
+            }
+        }
+
+        //The en passant square has to lie between an empty origin square and the pawn
+        //that was just pushed
+        if let Some(square) = self.en_passant_square() {
+            let square = square.index();
+            let expected_rank = if us == WHITE { 5 } else { 2 };
+            let valid = self.en_passant.count() == 1 && square / 8 == expected_rank && {
+                let (pushed, origin) = if us == WHITE {
+                    (square - 8, square + 8)
+                } else {
+                    (square + 8, square - 8)
+                };
+                self.pieces[PAWN][them] & (1u64 << pushed) != 0u64
+                    && all_pieces & (1u64 << square | 1u64 << origin) == 0u64
+            };
+            if !valid {
+                errors.push(PositionError::InvalidEnPassant(self.en_passant));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     pub fn gives_check(&self, mv: GameMove) -> bool {
         if mv.move_type == GameMoveType::Castle {
             return false; // In theory a castle move can give_check, but it is too much hasssle to compute that
         }
         //We also ignore en passant discovered checks here
         let mut occ_board = self.get_all_pieces();
-        occ_board ^= 1u64 << mv.from;
-        occ_board |= 1u64 << mv.to;
-        let king_position = self.king_square(1 - self.color_to_move);
+        occ_board ^= 1u64 << mv.from.index();
+        occ_board |= 1u64 << mv.to.index();
+        let king_position = self.king_square(1 - self.color_to_move).index();
         let bishop_like_attack = bishop_attack(king_position, occ_board);
         let rook_like_attack = rook_attack(king_position, occ_board);
         //CHeck discovered check
@@ -923,29 +1233,35 @@ impl GameState {
         }
         match mv.piece_type {
             PieceType::King => false,
-            PieceType::Queen => (bishop_like_attack | rook_like_attack) & (1u64 << mv.to) != 0u64,
-            PieceType::Knight => KNIGHT_ATTACKS[king_position] & (1u64 << mv.to) != 0u64,
-            PieceType::Bishop => bishop_like_attack & (1u64 << mv.to) != 0u64,
-            PieceType::Rook => rook_like_attack & (1u64 << mv.to) != 0u64,
+            PieceType::Queen => {
+                (bishop_like_attack | rook_like_attack) & (1u64 << mv.to.index()) != 0u64
+            }
+            PieceType::Knight => KNIGHT_ATTACKS[king_position] & (1u64 << mv.to.index()) != 0u64,
+            PieceType::Bishop => bishop_like_attack & (1u64 << mv.to.index()) != 0u64,
+            PieceType::Rook => rook_like_attack & (1u64 << mv.to.index()) != 0u64,
             PieceType::Pawn => match mv.move_type {
                 GameMoveType::Quiet | GameMoveType::Capture(_) | GameMoveType::EnPassant => {
                     if self.color_to_move == WHITE {
-                        (w_pawn_east_targets(1u64 << mv.to) | w_pawn_west_targets(1u64 << mv.to))
+                        (w_pawn_east_targets(1u64 << mv.to.index())
+                            | w_pawn_west_targets(1u64 << mv.to.index()))
                             & (1u64 << king_position)
                             != 0u64
                     } else {
-                        (b_pawn_east_targets(1u64 << mv.to) | b_pawn_west_targets(1u64 << mv.to))
+                        (b_pawn_east_targets(1u64 << mv.to.index())
+                            | b_pawn_west_targets(1u64 << mv.to.index()))
                             & (1u64 << king_position)
                             != 0u64
                     }
                 }
                 GameMoveType::Promotion(p, _) => match p {
-                    PieceType::Rook => rook_like_attack & (1u64 << mv.to) != 0u64,
+                    PieceType::Rook => rook_like_attack & (1u64 << mv.to.index()) != 0u64,
                     PieceType::Queen => {
-                        (bishop_like_attack | rook_like_attack) & (1u64 << mv.to) != 0u64
+                        (bishop_like_attack | rook_like_attack) & (1u64 << mv.to.index()) != 0u64
+                    }
+                    PieceType::Bishop => bishop_like_attack & (1u64 << mv.to.index()) != 0u64,
+                    PieceType::Knight => {
+                        KNIGHT_ATTACKS[king_position] & (1u64 << mv.to.index()) != 0u64
                     }
-                    PieceType::Bishop => bishop_like_attack & (1u64 << mv.to) != 0u64,
-                    PieceType::Knight => KNIGHT_ATTACKS[king_position] & (1u64 << mv.to) != 0u64,
                     _ => panic!("Not a valid promotion piece. Game_state.rs #1"),
                 },
                 _ => panic!("Not a valid pawn move. Game_state.rs #2"),
@@ -960,15 +1276,17 @@ impl GameState {
     ) -> bool {
         //println!("{}",self.to_fen());
         //println!("{:?}", mv);
-        if self.pieces[mv.piece_type.to_index()][self.color_to_move] & (1u64 << mv.from) == 0u64 {
+        if self.pieces[mv.piece_type.to_index()][self.color_to_move] & (1u64 << mv.from.index())
+            == 0u64
+        {
             return false;
         }
         if mv.piece_type == PieceType::Pawn
             && if self.color_to_move == WHITE {
-                mv.to / 8
+                mv.to.rank()
             } else {
-                7 - mv.to / 8
-            } == 7
+                mv.to.rank().flip()
+            } == Rank::Eighth
         {
             if let GameMoveType::Promotion(_, _) = mv.move_type {
             } else {
@@ -977,53 +1295,24 @@ impl GameState {
         } else if let GameMoveType::Promotion(_, _) = mv.move_type {
             if mv.piece_type != PieceType::Pawn
                 || if self.color_to_move == WHITE {
-                    mv.to / 8
+                    mv.to.rank()
                 } else {
-                    7 - mv.to / 8
-                } != 7
+                    mv.to.rank().flip()
+                } != Rank::Eighth
             {
                 return false;
             }
         }
 
         if mv.move_type == GameMoveType::EnPassant {
-            if (self.en_passant & (1u64 << mv.to)) == 0u64 {
+            if !self.en_passant.contains(mv.to) {
                 return false;
             }
         } else if mv.move_type == GameMoveType::Castle {
-            if mv.piece_type != PieceType::King {
-                return false;
-            }
-            let all_piece = self.get_all_pieces();
-            let blocked = all_piece | attack_container.attacks_sum[1 - self.color_to_move];
-            if mv.to != 6 && mv.to != 2 && mv.to != 62 && mv.to != 58
-                || attack_container.attacks_sum[1 - self.color_to_move] & (1u64 << mv.from) != 0u64
-            {
-                return false;
-            }
-            let is_invalid_wk = || {
-                mv.to == 6
-                    && (!self.castle_white_kingside || blocked & (1u64 << 5 | 1u64 << 6) != 0u64)
-            };
-            let is_invalid_wq = || {
-                mv.to == 2
-                    && (!self.castle_white_queenside
-                        || blocked & (1u64 << 2 | 1u64 << 3) != 0u64
-                        || all_piece & (1u64 << 1) != 0u64)
-            };
-            let is_invalid_bk = || {
-                mv.to == 62
-                    && (!self.castle_black_kingside || blocked & (1u64 << 61 | 1u64 << 62) != 0u64)
-            };
-            let is_invalid_bq = || {
-                mv.to == 58
-                    && (!self.castle_black_queenside
-                        || blocked & (1u64 << 58 | 1u64 << 59) != 0u64
-                        || all_piece & (1u64 << 57) != 0u64)
-            };
-            if is_invalid_wk() || is_invalid_wq() || is_invalid_bk() || is_invalid_bq() {
-                return false;
-            }
+            let kingside = mv.to > mv.from;
+            return mv.piece_type == PieceType::King
+                && mv.to == self.castle_target_square(self.color_to_move, kingside)
+                && castle_permitted(self, kingside, attack_container);
         } else {
             let captured_piece = match mv.move_type {
                 GameMoveType::Capture(p) => Some(p),
@@ -1031,11 +1320,11 @@ impl GameState {
                 _ => None,
             };
             if captured_piece.is_none() {
-                if self.get_all_pieces() & (1u64 << mv.to) != 0u64 {
+                if self.get_all_pieces() & (1u64 << mv.to.index()) != 0u64 {
                     return false;
                 }
             } else if self.pieces[captured_piece.unwrap().to_index()][1 - self.color_to_move]
-                & (1u64 << mv.to)
+                & (1u64 << mv.to.index())
                 == 0u64
             {
                 return false;
@@ -1044,55 +1333,56 @@ impl GameState {
         let mut all_pieces = self.get_all_pieces();
         match mv.piece_type {
             PieceType::King => {
-                if 1u64 << mv.to & (attack_container.attacks_sum[1 - self.color_to_move]) != 0u64
+                if 1u64 << mv.to.index() & (attack_container.attacks_sum[1 - self.color_to_move])
+                    != 0u64
                     || mv.move_type != GameMoveType::Castle
-                        && (1u64 << mv.to) & (KING_ATTACKS[mv.from as usize]) == 0u64
+                        && (1u64 << mv.to.index()) & (KING_ATTACKS[mv.from.index()]) == 0u64
                 {
                     return false;
                 }
             }
             PieceType::Bishop => {
-                if 1u64 << mv.to & (bishop_attack(mv.from as usize, all_pieces)) == 0u64 {
+                if 1u64 << mv.to.index() & (bishop_attack(mv.from.index(), all_pieces)) == 0u64 {
                     return false;
                 }
             }
             PieceType::Rook => {
-                if 1u64 << mv.to & (rook_attack(mv.from as usize, all_pieces)) == 0u64 {
+                if 1u64 << mv.to.index() & (rook_attack(mv.from.index(), all_pieces)) == 0u64 {
                     return false;
                 }
             }
             PieceType::Queen => {
-                if 1u64 << mv.to
-                    & (bishop_attack(mv.from as usize, all_pieces)
-                        | rook_attack(mv.from as usize, all_pieces))
+                if 1u64 << mv.to.index()
+                    & (bishop_attack(mv.from.index(), all_pieces)
+                        | rook_attack(mv.from.index(), all_pieces))
                     == 0u64
                 {
                     return false;
                 }
             }
             PieceType::Knight => {
-                if 1u64 << mv.to & (KNIGHT_ATTACKS[mv.from as usize]) == 0u64 {
+                if 1u64 << mv.to.index() & (KNIGHT_ATTACKS[mv.from.index()]) == 0u64 {
                     return false;
                 }
             }
             PieceType::Pawn => {
                 if mv.is_capture() {
-                    if (pawn_west_targets(self.color_to_move, 1u64 << mv.from)
-                        | pawn_east_targets(self.color_to_move, 1u64 << mv.from))
-                        & 1u64 << mv.to
+                    if (pawn_west_targets(self.color_to_move, 1u64 << mv.from.index())
+                        | pawn_east_targets(self.color_to_move, 1u64 << mv.from.index()))
+                        & 1u64 << mv.to.index()
                         == 0u64
                     {
                         return false;
                     }
                 } else if (single_push_pawn_targets(
                     self.color_to_move,
-                    1u64 << mv.from,
+                    1u64 << mv.from.index(),
                     !all_pieces,
                 ) | double_push_pawn_targets(
                     self.color_to_move,
-                    1u64 << mv.from,
+                    1u64 << mv.from.index(),
                     !all_pieces,
-                )) & 1u64 << mv.to
+                )) & 1u64 << mv.to.index()
                     == 0u64
                 {
                     return false;
@@ -1100,21 +1390,21 @@ impl GameState {
             }
         }
         if mv.move_type != GameMoveType::Castle {
-            all_pieces ^= 1u64 << mv.from;
-            all_pieces |= 1u64 << mv.to;
+            all_pieces ^= 1u64 << mv.from.index();
+            all_pieces |= 1u64 << mv.to.index();
             let cap_piece = if mv.move_type == GameMoveType::EnPassant {
                 1u64 << (if self.color_to_move == WHITE {
-                    mv.to - 8
+                    mv.to.index() - 8
                 } else {
-                    mv.to + 8
+                    mv.to.index() + 8
                 })
             } else {
-                1u64 << mv.to
+                1u64 << mv.to.index()
             };
             let king_square = if mv.piece_type == PieceType::King {
-                mv.to as usize
+                mv.to.index()
             } else {
-                self.king_square(self.color_to_move)
+                self.king_square(self.color_to_move).index()
             };
             if bishop_attack(king_square, all_pieces)
                 & (self.pieces[BISHOP][1 - self.color_to_move]
@@ -1166,16 +1456,22 @@ impl Clone for GameState {
         GameState {
             color_to_move: self.color_to_move,
             pieces: self.pieces,
+            mailbox: self.mailbox,
             castle_white_kingside: self.castle_white_kingside,
             castle_white_queenside: self.castle_white_queenside,
             castle_black_kingside: self.castle_black_kingside,
             castle_black_queenside: self.castle_black_queenside,
+            castle_rook_files: self.castle_rook_files,
+            chess960: self.chess960,
             en_passant: self.en_passant,
             half_moves: self.half_moves,
             full_moves: self.full_moves,
             hash: self.hash,
+            pawn_hash: self.pawn_hash,
             psqt: self.psqt,
             phase: self.phase.clone(),
+            #[cfg(feature = "nnue")]
+            nnue: self.nnue,
         }
     }
 }
@@ -1230,7 +1526,8 @@ impl Display for GameState {
             "Black Queenside: {}\n",
             self.castle_black_queenside
         ));
-        res_str.push_str(&format!("En Passant Possible: {:x}\n", self.en_passant));
+        res_str.push_str(&format!("Chess960: {}\n", self.chess960));
+        res_str.push_str(&format!("En Passant Possible: {:x}\n", self.en_passant.0));
         res_str.push_str(&format!("Half-Counter: {}\n", self.half_moves));
         res_str.push_str(&format!("Full-Counter: {}\n", self.full_moves));
         res_str.push_str(&format!("Side to Move: {}\n", self.color_to_move));
@@ -1290,7 +1587,7 @@ impl Debug for GameState {
         res_str.push_str(&format!("CWQ: {}\n", self.castle_white_queenside));
         res_str.push_str(&format!("CBK: {}\n", self.castle_black_kingside));
         res_str.push_str(&format!("CBQ: {}\n", self.castle_black_queenside));
-        res_str.push_str(&format!("En-Passant: 0x{:x}u64\n", self.en_passant));
+        res_str.push_str(&format!("En-Passant: 0x{:x}u64\n", self.en_passant.0));
         res_str.push_str(&format!("half_moves: {}\n", self.half_moves));
         res_str.push_str(&format!("full_moves: {}\n", self.full_moves));
         res_str.push_str(&format!("Side to Move: {}\n", self.color_to_move));
@@ -0,0 +1,87 @@
+use crate::board_representation::game_state::{
+    GameResult, GameState, BISHOP, BLACK, KNIGHT, PAWN, QUEEN, ROOK, WHITE,
+};
+use crate::board_representation::game_state_attack_container::GameStateAttackContainer;
+use crate::move_generation::movegen::{generate_moves, MoveList};
+use std::fmt::{Display, Formatter, Result};
+
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Reason {
+    Checkmate,
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    //Neither side can mate by any series of legal moves
+    InsufficientMaterial,
+}
+impl Display for Reason {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        write!(
+            formatter,
+            "{}",
+            match self {
+                Reason::Checkmate => "checkmate",
+                Reason::Stalemate => "stalemate",
+                Reason::FiftyMoveRule => "fifty move rule",
+                Reason::ThreefoldRepetition => "threefold repetition",
+                Reason::InsufficientMaterial => "insufficient material",
+            }
+        )
+    }
+}
+
+impl GameState {
+    //Whether the game has ended in this position. history holds the positions played before it,
+    //oldest first
+    pub fn game_status(&self, history: &[GameState]) -> (GameResult, Option<Reason>) {
+        let mut movelist = MoveList::default();
+        let attack_container = GameStateAttackContainer::from_state(self);
+        let agsi = generate_moves(self, false, &mut movelist, &attack_container);
+        if !agsi.stm_haslegalmove {
+            return if !agsi.stm_incheck {
+                (GameResult::Draw, Some(Reason::Stalemate))
+            } else if self.color_to_move == WHITE {
+                (GameResult::BlackWin, Some(Reason::Checkmate))
+            } else {
+                (GameResult::WhiteWin, Some(Reason::Checkmate))
+            };
+        }
+        if self.has_insufficient_material() {
+            return (GameResult::Draw, Some(Reason::InsufficientMaterial));
+        }
+        if self.half_moves >= 100 {
+            return (GameResult::Draw, Some(Reason::FiftyMoveRule));
+        }
+        if history
+            .iter()
+            .filter(|other| other.hash == self.hash)
+            .count()
+            >= 2
+        {
+            return (GameResult::Draw, Some(Reason::ThreefoldRepetition));
+        }
+        (GameResult::Ingame, None)
+    }
+
+    //Detects the dead positions which come down to material: a lone minor piece against a bare
+    //king, and any number of bishops which all stand on squares of the same colour
+    pub fn has_insufficient_material(&self) -> bool {
+        let pawns_and_majors = self.pieces[PAWN][WHITE]
+            | self.pieces[PAWN][BLACK]
+            | self.pieces[ROOK][WHITE]
+            | self.pieces[ROOK][BLACK]
+            | self.pieces[QUEEN][WHITE]
+            | self.pieces[QUEEN][BLACK];
+        if pawns_and_majors != 0u64 {
+            return false;
+        }
+        let knights = self.pieces[KNIGHT][WHITE] | self.pieces[KNIGHT][BLACK];
+        let bishops = self.pieces[BISHOP][WHITE] | self.pieces[BISHOP][BLACK];
+        if (knights | bishops).count_ones() <= 1 {
+            return true;
+        }
+        knights == 0u64 && (bishops & LIGHT_SQUARES == 0u64 || bishops & !LIGHT_SQUARES == 0u64)
+    }
+}
@@ -1,3 +1,6 @@
 pub mod game_state;
 pub mod game_state_attack_container;
+pub mod game_status;
+pub mod polyglot_hashing;
+pub mod san;
 pub mod zobrist_hashing;
@@ -0,0 +1,208 @@
+use crate::board_representation::game_state::{
+    File, GameMove, GameMoveType, GameState, PieceType, Rank, Square,
+};
+use crate::board_representation::game_state_attack_container::GameStateAttackContainer;
+use crate::move_generation::makemove::make_move_mut;
+use crate::move_generation::movegen::{generate_moves, MoveList};
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum SanError {
+    Empty,
+    //The string is not a move in standard algebraic notation
+    InvalidSyntax(String),
+    //No legal move matches the string
+    IllegalMove(String),
+    //More than one legal move matches the string
+    AmbiguousMove(String),
+}
+impl Display for SanError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        match self {
+            SanError::Empty => write!(formatter, "empty move"),
+            SanError::InvalidSyntax(s) => write!(formatter, "invalid SAN '{}'", s),
+            SanError::IllegalMove(s) => write!(formatter, "illegal move '{}'", s),
+            SanError::AmbiguousMove(s) => write!(formatter, "ambiguous move '{}'", s),
+        }
+    }
+}
+impl std::error::Error for SanError {}
+
+fn piece_to_char(piece: PieceType) -> Option<char> {
+    match piece {
+        PieceType::Pawn => None,
+        PieceType::Knight => Some('N'),
+        PieceType::Bishop => Some('B'),
+        PieceType::Rook => Some('R'),
+        PieceType::Queen => Some('Q'),
+        PieceType::King => Some('K'),
+    }
+}
+
+fn char_to_piece(c: char) -> Option<PieceType> {
+    match c {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+//SAN of a legal move without the check suffix. movelist has to hold the legal moves of game_state
+fn san_without_suffix(mv: GameMove, game_state: &GameState, movelist: &MoveList) -> String {
+    let mut res_str = String::new();
+    if let GameMoveType::Castle = mv.move_type {
+        //Holds for both castle encodings, as the rook is on the same side as the king's target
+        res_str.push_str(if mv.to > mv.from { "O-O" } else { "O-O-O" });
+        return res_str;
+    }
+    if let Some(c) = piece_to_char(mv.piece_type) {
+        res_str.push(c);
+    }
+    let from = mv.from;
+    //Other pieces of the same type which can reach the target square
+    let rivals = movelist.move_list.iter().map(|gmv| gmv.0).filter(|other| {
+        other.piece_type == mv.piece_type && other.to == mv.to && other.from != mv.from
+    });
+    let mut ambiguous = false;
+    let mut file_shared = false;
+    let mut rank_shared = false;
+    for other in rivals {
+        ambiguous = true;
+        file_shared |= other.from.file() == from.file();
+        rank_shared |= other.from.rank() == from.rank();
+    }
+    let is_capture =
+        game_state.piece_on(mv.to).is_some() || mv.move_type == GameMoveType::EnPassant;
+    let file_needed = ambiguous && (!file_shared || rank_shared)
+        || is_capture && mv.piece_type == PieceType::Pawn;
+    let rank_needed = ambiguous && file_shared;
+    if file_needed {
+        res_str.push(from.file().to_char());
+    }
+    if rank_needed {
+        res_str.push(from.rank().to_char());
+    }
+    if is_capture {
+        res_str.push('x');
+    }
+    res_str.push_str(&mv.to.to_string());
+    if let GameMoveType::Promotion(promo_piece, _) = mv.move_type {
+        res_str.push('=');
+        res_str.push(piece_to_char(promo_piece).expect("Invalid promotion piece"));
+    }
+    res_str
+}
+
+impl GameState {
+    //Parses a move in standard algebraic notation. Accepts castling written with zeros,
+    //promotions with or without '=' and trailing check or annotation symbols
+    pub fn parse_san(&self, san: &str) -> std::result::Result<GameMove, SanError> {
+        let trimmed = san.trim().trim_end_matches(|c| "+#!?".contains(c));
+        if trimmed.is_empty() {
+            return Err(SanError::Empty);
+        }
+        let mut movelist = MoveList::default();
+        let attack_container = GameStateAttackContainer::from_state(self);
+        generate_moves(self, false, &mut movelist, &attack_container);
+        let mut legal_moves = movelist.move_list.iter().map(|gmv| gmv.0);
+
+        let castle = match trimmed {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(kingside) = castle {
+            return legal_moves
+                .find(|mv| mv.move_type == GameMoveType::Castle && (mv.to > mv.from) == kingside)
+                .ok_or_else(|| SanError::IllegalMove(san.to_string()));
+        }
+
+        let mut chars: Vec<char> = trimmed.chars().filter(|&c| c != 'x' && c != ':').collect();
+        let piece_type = match chars.first().and_then(|&c| char_to_piece(c)) {
+            Some(piece) => {
+                chars.remove(0);
+                piece
+            }
+            None => PieceType::Pawn,
+        };
+        let mut promotion = None;
+        if chars.len() >= 3 && chars[chars.len() - 2].is_ascii_digit() {
+            promotion = char_to_piece(chars[chars.len() - 1].to_ascii_uppercase());
+            if promotion.is_none() || promotion == Some(PieceType::King) {
+                return Err(SanError::InvalidSyntax(san.to_string()));
+            }
+            chars.pop();
+        } else if chars.len() >= 4 && chars[chars.len() - 2] == '=' {
+            promotion = char_to_piece(chars[chars.len() - 1].to_ascii_uppercase());
+            if promotion.is_none() || promotion == Some(PieceType::King) {
+                return Err(SanError::InvalidSyntax(san.to_string()));
+            }
+            chars.truncate(chars.len() - 2);
+        }
+        if chars.len() < 2 || chars.len() > 4 {
+            return Err(SanError::InvalidSyntax(san.to_string()));
+        }
+        let target = match (
+            File::from_char(chars[chars.len() - 2]),
+            Rank::from_char(chars[chars.len() - 1]),
+        ) {
+            (Some(file), Some(rank)) => Square::new(file, rank),
+            _ => return Err(SanError::InvalidSyntax(san.to_string())),
+        };
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &c in &chars[..chars.len() - 2] {
+            if let Some(file) = File::from_char(c).filter(|_| from_file.is_none()) {
+                from_file = Some(file);
+            } else if let Some(rank) = Rank::from_char(c).filter(|_| from_rank.is_none()) {
+                from_rank = Some(rank);
+            } else {
+                return Err(SanError::InvalidSyntax(san.to_string()));
+            }
+        }
+
+        let mut candidates = legal_moves.filter(|mv| {
+            mv.move_type != GameMoveType::Castle
+                && mv.piece_type == piece_type
+                && mv.to == target
+                && (from_file.is_none() || from_file == Some(mv.from.file()))
+                && (from_rank.is_none() || from_rank == Some(mv.from.rank()))
+                && promotion
+                    == match mv.move_type {
+                        GameMoveType::Promotion(piece, _) => Some(piece),
+                        _ => None,
+                    }
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) => Ok(mv),
+            (Some(_), Some(_)) => Err(SanError::AmbiguousMove(san.to_string())),
+            _ => Err(SanError::IllegalMove(san.to_string())),
+        }
+    }
+
+    //SAN of a line of legal moves played from this position. Every position is only generated
+    //once, serving both the disambiguation of its own move and the check suffix of the one before
+    pub fn moves_to_san(&self, moves: &[GameMove]) -> Vec<String> {
+        let mut res = Vec::with_capacity(moves.len());
+        let mut movelist = MoveList::default();
+        let mut attack_container = GameStateAttackContainer::from_state(self);
+        generate_moves(self, false, &mut movelist, &attack_container);
+        let mut state = self.clone();
+        for &mv in moves {
+            let mut san = san_without_suffix(mv, &state, &movelist);
+            make_move_mut(&mut state, mv);
+            attack_container.write_state(&state);
+            let agsi = generate_moves(&state, false, &mut movelist, &attack_container);
+            if agsi.stm_incheck && !agsi.stm_haslegalmove {
+                san.push('#');
+            } else if agsi.stm_incheck {
+                san.push('+');
+            }
+            res.push(san);
+        }
+        res
+    }
+}
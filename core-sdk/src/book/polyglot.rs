@@ -0,0 +1,172 @@
+use crate::board_representation::game_state::{GameMove, GameMoveType, GameState, PieceType};
+use crate::board_representation::game_state_attack_container::GameStateAttackContainer;
+use crate::move_generation::movegen::{generate_moves, MoveList};
+use rand::Rng;
+use std::fs;
+use std::io;
+
+pub const ENTRY_SIZE: usize = 16;
+
+//One 16 byte record of a Polyglot book, stored big endian
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BookEntry {
+    pub key: u64,
+    //Bits 0-5: to square, 6-11: from square, 12-14: promotion piece (1 = knight ... 4 = queen).
+    //Castling is written as the king capturing its own rook
+    pub mv: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    pub fn from_bytes(bytes: &[u8; ENTRY_SIZE]) -> BookEntry {
+        let mut key = [0u8; 8];
+        key.copy_from_slice(&bytes[0..8]);
+        BookEntry {
+            key: u64::from_be_bytes(key),
+            mv: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+            learn: u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; ENTRY_SIZE] {
+        let mut res = [0u8; ENTRY_SIZE];
+        res[0..8].copy_from_slice(&self.key.to_be_bytes());
+        res[8..10].copy_from_slice(&self.mv.to_be_bytes());
+        res[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        res[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        res
+    }
+}
+
+pub fn encode_move(game_state: &GameState, mv: GameMove) -> u16 {
+    let to = if mv.move_type == GameMoveType::Castle && !game_state.chess960 {
+        game_state
+            .castle_rook_square(game_state.color_to_move, mv.to > mv.from)
+            .index() as u16
+    } else {
+        mv.to.index() as u16
+    };
+    let promotion = match mv.move_type {
+        GameMoveType::Promotion(PieceType::Knight, _) => 1,
+        GameMoveType::Promotion(PieceType::Bishop, _) => 2,
+        GameMoveType::Promotion(PieceType::Rook, _) => 3,
+        GameMoveType::Promotion(PieceType::Queen, _) => 4,
+        _ => 0,
+    };
+    promotion << 12 | (mv.from.index() as u16) << 6 | to
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BookSelection {
+    //Always the move with the highest weight
+    Best,
+    //Chance proportional to the weight
+    Weighted,
+    //Every move with non zero weight is equally likely
+    Random,
+}
+
+pub struct PolyglotBook {
+    //Sorted by key
+    pub entries: Vec<BookEntry>,
+}
+
+impl PolyglotBook {
+    pub fn open(path: &str) -> io::Result<PolyglotBook> {
+        let bytes = fs::read(path)?;
+        if bytes.len() % ENTRY_SIZE != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a multiple of {} bytes", path, ENTRY_SIZE),
+            ));
+        }
+        Ok(PolyglotBook::from_bytes(&bytes))
+    }
+
+    //Trailing bytes which don't form a whole entry are ignored
+    pub fn from_bytes(bytes: &[u8]) -> PolyglotBook {
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| {
+                let mut entry = [0u8; ENTRY_SIZE];
+                entry.copy_from_slice(chunk);
+                BookEntry::from_bytes(&entry)
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.key);
+        PolyglotBook { entries }
+    }
+
+    pub fn entries_for(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        &self.entries[start..end]
+    }
+
+    //Legal book moves of the position together with their weights. Entries whose move is not
+    //legal, which only happens on key collisions, are skipped
+    pub fn book_moves(&self, game_state: &GameState) -> Vec<(GameMove, u16)> {
+        let entries = self.entries_for(game_state.polyglot_key());
+        if entries.is_empty() {
+            return Vec::new();
+        }
+        let mut movelist = MoveList::default();
+        let attack_container = GameStateAttackContainer::from_state(game_state);
+        generate_moves(game_state, false, &mut movelist, &attack_container);
+        entries
+            .iter()
+            .filter_map(|entry| {
+                movelist
+                    .move_list
+                    .iter()
+                    .map(|gmv| gmv.0)
+                    .find(|mv| encode_move(game_state, *mv) == entry.mv)
+                    .map(|mv| (mv, entry.weight))
+            })
+            .collect()
+    }
+
+    pub fn get_move<R: Rng>(
+        &self,
+        game_state: &GameState,
+        selection: BookSelection,
+        rng: &mut R,
+    ) -> Option<GameMove> {
+        let moves = self.book_moves(game_state);
+        match selection {
+            BookSelection::Best => moves
+                .iter()
+                .filter(|(_, weight)| *weight > 0)
+                .max_by_key(|(_, weight)| *weight)
+                .map(|(mv, _)| *mv),
+            BookSelection::Weighted => {
+                let total: u32 = moves.iter().map(|(_, weight)| u32::from(*weight)).sum();
+                if total == 0 {
+                    return None;
+                }
+                let mut pick = rng.gen_range(0, total);
+                for (mv, weight) in moves.iter() {
+                    if pick < u32::from(*weight) {
+                        return Some(*mv);
+                    }
+                    pick -= u32::from(*weight);
+                }
+                unreachable!()
+            }
+            BookSelection::Random => {
+                let candidates: Vec<GameMove> = moves
+                    .iter()
+                    .filter(|(_, weight)| *weight > 0)
+                    .map(|(mv, _)| *mv)
+                    .collect();
+                if candidates.is_empty() {
+                    None
+                } else {
+                    Some(candidates[rng.gen_range(0, candidates.len())])
+                }
+            }
+        }
+    }
+}
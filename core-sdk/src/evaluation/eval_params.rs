@@ -0,0 +1,336 @@
+//The evaluation parameters as the engine uses them. The tables in params.rs are the compiled-in
+//defaults, a tuned set can be loaded at runtime instead (UCI option EvalParams). The tuner writes
+//its Parameters in the text format read here, so tuned values no longer need to be pasted into
+//params.rs to be tried out.
+use super::params::*;
+use super::EvaluationScore;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+
+//First line of a parameter file. Tables are identified by name, so the version only changes if
+//a table changes its meaning
+const HEADER: &str = "FabChess evaluation parameters 1";
+
+//Field names are the ones of Parameters
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvalParams {
+    pub tempo_bonus: EvaluationScore,
+    pub shielding_pawn_missing: [EvaluationScore; 4],
+    pub shielding_pawn_onopen_missing: [EvaluationScore; 4],
+    pub pawn_doubled: EvaluationScore,
+    pub pawn_isolated: EvaluationScore,
+    pub pawn_backward: EvaluationScore,
+    pub pawn_supported: [[EvaluationScore; 8]; 8],
+    pub pawn_attack_center: EvaluationScore,
+    pub pawn_mobility: EvaluationScore,
+    pub pawn_passed: [EvaluationScore; 7],
+    pub pawn_passed_notblocked: [EvaluationScore; 7],
+    pub pawn_passed_kingdistance: [EvaluationScore; 7],
+    pub pawn_passed_enemykingdistance: [EvaluationScore; 7],
+    pub pawn_passed_subdistance: [EvaluationScore; 13],
+    pub rook_behind_support_passer: EvaluationScore,
+    pub rook_behind_enemy_passer: EvaluationScore,
+    pub pawn_passed_weak: EvaluationScore,
+    pub knight_supported: EvaluationScore,
+    pub knight_outpost_table: [[EvaluationScore; 8]; 8],
+    pub bishop_xray_king: EvaluationScore,
+    pub rook_xray_king: EvaluationScore,
+    pub queen_xray_king: EvaluationScore,
+    pub rook_on_open: EvaluationScore,
+    pub rook_on_semi_open: EvaluationScore,
+    pub queen_on_open: EvaluationScore,
+    pub queen_on_semi_open: EvaluationScore,
+    pub rook_on_seventh: EvaluationScore,
+    pub pawn_piece_value: EvaluationScore,
+    pub knight_piece_value: EvaluationScore,
+    pub knight_value_with_pawns: [i16; 17],
+    pub bishop_piece_value: EvaluationScore,
+    pub bishop_pair: EvaluationScore,
+    pub rook_piece_value: EvaluationScore,
+    pub queen_piece_value: EvaluationScore,
+    pub diagonally_adjacent_squares_withpawns: [EvaluationScore; 5],
+    pub knight_mobility: [EvaluationScore; 9],
+    pub bishop_mobility: [EvaluationScore; 14],
+    pub rook_mobility: [EvaluationScore; 15],
+    pub queen_mobility: [EvaluationScore; 28],
+    pub attack_weight: [EvaluationScore; 8],
+    pub safety_table: [EvaluationScore; 100],
+    pub knight_attack_value: EvaluationScore,
+    pub bishop_attack_value: EvaluationScore,
+    pub rook_attack_value: EvaluationScore,
+    pub queen_attack_value: EvaluationScore,
+    pub knight_check_value: EvaluationScore,
+    pub bishop_check_value: EvaluationScore,
+    pub rook_check_value: EvaluationScore,
+    pub queen_check_value: EvaluationScore,
+    pub psqt_pawn: [[EvaluationScore; 8]; 8],
+    pub psqt_knight: [[EvaluationScore; 8]; 8],
+    pub psqt_bishop: [[EvaluationScore; 8]; 8],
+    pub psqt_rook: [[EvaluationScore; 8]; 8],
+    pub psqt_queen: [[EvaluationScore; 8]; 8],
+    pub psqt_king: [[EvaluationScore; 8]; 8],
+}
+
+pub const DEFAULT_EVAL_PARAMS: EvalParams = EvalParams {
+    tempo_bonus: TEMPO_BONUS,
+    shielding_pawn_missing: SHIELDING_PAWN_MISSING,
+    shielding_pawn_onopen_missing: SHIELDING_PAWN_MISSING_ON_OPEN_FILE,
+    pawn_doubled: PAWN_DOUBLED_VALUE,
+    pawn_isolated: PAWN_ISOLATED_VALUE,
+    pawn_backward: PAWN_BACKWARD_VALUE,
+    pawn_supported: PAWN_SUPPORTED_VALUE,
+    pawn_attack_center: PAWN_ATTACK_CENTER,
+    pawn_mobility: PAWN_MOBILITY,
+    pawn_passed: PAWN_PASSED_VALUES,
+    pawn_passed_notblocked: PAWN_PASSED_NOT_BLOCKED_VALUES,
+    pawn_passed_kingdistance: PASSED_KING_DISTANCE,
+    pawn_passed_enemykingdistance: PASSED_ENEMY_KING_DISTANCE,
+    pawn_passed_subdistance: PASSED_SUBTRACT_DISTANCE,
+    rook_behind_support_passer: ROOK_BEHIND_SUPPORT_PASSER,
+    rook_behind_enemy_passer: ROOK_BEHIND_ENEMY_PASSER,
+    pawn_passed_weak: PAWN_PASSED_WEAK,
+    knight_supported: KNIGHT_SUPPORTED_BY_PAWN,
+    knight_outpost_table: KNIGHT_OUTPOST_TABLE,
+    bishop_xray_king: BISHOP_XRAY_KING,
+    rook_xray_king: ROOK_XRAY_KING,
+    queen_xray_king: QUEEN_XRAY_KING,
+    rook_on_open: ROOK_ON_OPEN_FILE_BONUS,
+    rook_on_semi_open: ROOK_ON_SEMI_OPEN_FILE_BONUS,
+    queen_on_open: QUEEN_ON_OPEN_FILE_BONUS,
+    queen_on_semi_open: QUEEN_ON_SEMI_OPEN_FILE_BONUS,
+    rook_on_seventh: ROOK_ON_SEVENTH,
+    pawn_piece_value: PAWN_PIECE_VALUE,
+    knight_piece_value: KNIGHT_PIECE_VALUE,
+    knight_value_with_pawns: KNIGHT_VALUE_WITH_PAWNS,
+    bishop_piece_value: BISHOP_PIECE_VALUE,
+    bishop_pair: BISHOP_PAIR_BONUS,
+    rook_piece_value: ROOK_PIECE_VALUE,
+    queen_piece_value: QUEEN_PIECE_VALUE,
+    diagonally_adjacent_squares_withpawns: DIAGONALLY_ADJACENT_SQUARES_WITH_OWN_PAWNS,
+    knight_mobility: KNIGHT_MOBILITY_BONUS,
+    bishop_mobility: BISHOP_MOBILITY_BONUS,
+    rook_mobility: ROOK_MOBILITY_BONUS,
+    queen_mobility: QUEEN_MOBILITY_BONUS,
+    attack_weight: ATTACK_WEIGHT,
+    safety_table: SAFETY_TABLE,
+    knight_attack_value: KNIGHT_ATTACK_WORTH,
+    bishop_attack_value: BISHOP_ATTACK_WORTH,
+    rook_attack_value: ROOK_ATTACK_WORTH,
+    queen_attack_value: QUEEN_ATTACK_WORTH,
+    knight_check_value: KNIGHT_SAFE_CHECK,
+    bishop_check_value: BISHOP_SAFE_CHECK,
+    rook_check_value: ROOK_SAFE_CHECK,
+    queen_check_value: QUEEN_SAFE_CHECK,
+    psqt_pawn: PSQT_PAWN,
+    psqt_knight: PSQT_KNIGHT,
+    psqt_bishop: PSQT_BISHOP,
+    psqt_rook: PSQT_ROOK,
+    psqt_queen: PSQT_QUEEN,
+    psqt_king: PSQT_KING,
+};
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_EVAL_PARAMS
+    }
+}
+
+impl EvalParams {
+    //Every table with its name, in file order
+    fn tables_mut(&mut self) -> Vec<(&'static str, &mut dyn ParamTable)> {
+        vec![
+            ("tempo_bonus", &mut self.tempo_bonus),
+            ("shielding_pawn_missing", &mut self.shielding_pawn_missing),
+            (
+                "shielding_pawn_onopen_missing",
+                &mut self.shielding_pawn_onopen_missing,
+            ),
+            ("pawn_doubled", &mut self.pawn_doubled),
+            ("pawn_isolated", &mut self.pawn_isolated),
+            ("pawn_backward", &mut self.pawn_backward),
+            ("pawn_supported", &mut self.pawn_supported),
+            ("pawn_attack_center", &mut self.pawn_attack_center),
+            ("pawn_mobility", &mut self.pawn_mobility),
+            ("pawn_passed", &mut self.pawn_passed),
+            ("pawn_passed_notblocked", &mut self.pawn_passed_notblocked),
+            (
+                "pawn_passed_kingdistance",
+                &mut self.pawn_passed_kingdistance,
+            ),
+            (
+                "pawn_passed_enemykingdistance",
+                &mut self.pawn_passed_enemykingdistance,
+            ),
+            ("pawn_passed_subdistance", &mut self.pawn_passed_subdistance),
+            (
+                "rook_behind_support_passer",
+                &mut self.rook_behind_support_passer,
+            ),
+            (
+                "rook_behind_enemy_passer",
+                &mut self.rook_behind_enemy_passer,
+            ),
+            ("pawn_passed_weak", &mut self.pawn_passed_weak),
+            ("knight_supported", &mut self.knight_supported),
+            ("knight_outpost_table", &mut self.knight_outpost_table),
+            ("bishop_xray_king", &mut self.bishop_xray_king),
+            ("rook_xray_king", &mut self.rook_xray_king),
+            ("queen_xray_king", &mut self.queen_xray_king),
+            ("rook_on_open", &mut self.rook_on_open),
+            ("rook_on_semi_open", &mut self.rook_on_semi_open),
+            ("queen_on_open", &mut self.queen_on_open),
+            ("queen_on_semi_open", &mut self.queen_on_semi_open),
+            ("rook_on_seventh", &mut self.rook_on_seventh),
+            ("pawn_piece_value", &mut self.pawn_piece_value),
+            ("knight_piece_value", &mut self.knight_piece_value),
+            ("knight_value_with_pawns", &mut self.knight_value_with_pawns),
+            ("bishop_piece_value", &mut self.bishop_piece_value),
+            ("bishop_pair", &mut self.bishop_pair),
+            ("rook_piece_value", &mut self.rook_piece_value),
+            ("queen_piece_value", &mut self.queen_piece_value),
+            (
+                "diagonally_adjacent_squares_withpawns",
+                &mut self.diagonally_adjacent_squares_withpawns,
+            ),
+            ("knight_mobility", &mut self.knight_mobility),
+            ("bishop_mobility", &mut self.bishop_mobility),
+            ("rook_mobility", &mut self.rook_mobility),
+            ("queen_mobility", &mut self.queen_mobility),
+            ("attack_weight", &mut self.attack_weight),
+            ("safety_table", &mut self.safety_table),
+            ("knight_attack_value", &mut self.knight_attack_value),
+            ("bishop_attack_value", &mut self.bishop_attack_value),
+            ("rook_attack_value", &mut self.rook_attack_value),
+            ("queen_attack_value", &mut self.queen_attack_value),
+            ("knight_check_value", &mut self.knight_check_value),
+            ("bishop_check_value", &mut self.bishop_check_value),
+            ("rook_check_value", &mut self.rook_check_value),
+            ("queen_check_value", &mut self.queen_check_value),
+            ("psqt_pawn", &mut self.psqt_pawn),
+            ("psqt_knight", &mut self.psqt_knight),
+            ("psqt_bishop", &mut self.psqt_bishop),
+            ("psqt_rook", &mut self.psqt_rook),
+            ("psqt_queen", &mut self.psqt_queen),
+            ("psqt_king", &mut self.psqt_king),
+        ]
+    }
+
+    pub fn open(path: &str) -> io::Result<EvalParams> {
+        EvalParams::parse(&fs::read_to_string(path)?)
+    }
+
+    //The header line, then one line per table: its name followed by its values, for scores the
+    //midgame value before the endgame value. Every table has to be given exactly once, empty lines
+    //and lines starting with # are ignored
+    pub fn parse(text: &str) -> io::Result<EvalParams> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("not an evaluation parameter file".to_owned()));
+        }
+        let mut res = DEFAULT_EVAL_PARAMS;
+        let mut tables = res.tables_mut();
+        let mut seen = vec![false; tables.len()];
+        for line in lines {
+            let mut tokens = line.split_whitespace();
+            let name = tokens.next().unwrap_or_default();
+            let index = tables
+                .iter()
+                .position(|(table, _)| *table == name)
+                .ok_or_else(|| invalid(format!("unknown table {}", name)))?;
+            if seen[index] {
+                return Err(invalid(format!("table {} is given twice", name)));
+            }
+            seen[index] = true;
+            let values = tokens
+                .map(str::parse::<i16>)
+                .collect::<Result<Vec<i16>, _>>()
+                .map_err(|e| invalid(format!("table {}: {}", name, e)))?;
+            let table = &mut tables[index].1;
+            if values.len() != table.len() {
+                return Err(invalid(format!(
+                    "table {} has {} values, expected {}",
+                    name,
+                    values.len(),
+                    table.len()
+                )));
+            }
+            table.set_values(&values);
+        }
+        if let Some(index) = seen.iter().position(|seen| !seen) {
+            return Err(invalid(format!("table {} is missing", tables[index].0)));
+        }
+        Ok(res)
+    }
+
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl Display for EvalParams {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        writeln!(formatter, "{}", HEADER)?;
+        let mut copy = self.clone();
+        for (name, table) in copy.tables_mut() {
+            write!(formatter, "{}", name)?;
+            for value in table.values() {
+                write!(formatter, " {}", value)?;
+            }
+            writeln!(formatter)?;
+        }
+        Ok(())
+    }
+}
+
+//A table of parameters seen as a flat list of values
+trait ParamTable {
+    fn len(&self) -> usize;
+    fn values(&self) -> Vec<i16>;
+    fn set_values(&mut self, values: &[i16]);
+}
+
+impl ParamTable for EvaluationScore {
+    fn len(&self) -> usize {
+        2
+    }
+    fn values(&self) -> Vec<i16> {
+        vec![self.0, self.1]
+    }
+    fn set_values(&mut self, values: &[i16]) {
+        *self = EvaluationScore(values[0], values[1]);
+    }
+}
+
+impl ParamTable for i16 {
+    fn len(&self) -> usize {
+        1
+    }
+    fn values(&self) -> Vec<i16> {
+        vec![*self]
+    }
+    fn set_values(&mut self, values: &[i16]) {
+        *self = values[0];
+    }
+}
+
+impl<T: ParamTable, const N: usize> ParamTable for [T; N] {
+    fn len(&self) -> usize {
+        self.iter().map(ParamTable::len).sum()
+    }
+    fn values(&self) -> Vec<i16> {
+        self.iter().flat_map(ParamTable::values).collect()
+    }
+    fn set_values(&mut self, values: &[i16]) {
+        let mut start = 0;
+        for entry in self.iter_mut() {
+            let len = entry.len();
+            entry.set_values(&values[start..start + len]);
+            start += len;
+        }
+    }
+}
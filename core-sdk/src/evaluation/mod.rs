@@ -1,5 +1,8 @@
+pub mod eval_params;
+pub mod nnue;
 pub mod parameters;
 pub mod params;
+pub mod pawn_hash;
 pub mod phase;
 pub mod psqt_evaluation;
 pub mod trace;
@@ -7,18 +10,18 @@ pub mod trace;
 use crate::bitboards::bitboards;
 use crate::bitboards::bitboards::constants::*;
 use crate::board_representation::game_state::{
-    GameState, BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE,
+    File, GameState, BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE,
 };
 use crate::board_representation::game_state_attack_container::{
     GameStateAttackContainer, MGSA_BISHOP, MGSA_KNIGHT, MGSA_QUEEN, MGSA_ROOKS,
 };
-#[cfg(feature = "texel-tuning")]
+use crate::evaluation::pawn_hash::{PawnHashEntry, PawnHashTable};
 use crate::evaluation::trace::Trace;
 #[cfg(feature = "display-eval")]
 use crate::logging::log;
 use crate::move_generation::movegen;
 use crate::move_generation::movegen::{bishop_attack, rook_attack};
-use params::*;
+use eval_params::{EvalParams, DEFAULT_EVAL_PARAMS};
 use psqt_evaluation::psqt;
 use psqt_evaluation::BLACK_INDEX;
 use std::fmt::{Debug, Display, Formatter, Result};
@@ -100,8 +103,8 @@ impl ops::MulAssign<i16> for EvaluationScore {
 
 pub struct EvaluationResult {
     pub final_eval: i16,
-    #[cfg(feature = "texel-tuning")]
-    pub trace: Trace,
+    //Only recorded when asked for, see trace_game_state
+    pub trace: Option<Box<Trace>>,
 }
 
 pub fn eval_game_state_from_null(g: &GameState) -> EvaluationResult {
@@ -115,51 +118,143 @@ pub fn eval_game_state(
     _alpha: i16, //Lazy Eval components, unneeded currently
     _beta: i16,
 ) -> EvaluationResult {
+    evaluate(g, attacks, None, None).0
+}
+
+//Same as eval_game_state, but with loaded parameters instead of the compiled-in ones
+pub fn eval_game_state_with_params(
+    g: &GameState,
+    attacks: &GameStateAttackContainer,
+    params: &EvalParams,
+) -> EvaluationResult {
+    evaluate(g, attacks, Some(params), None).0
+}
+
+//Same as eval_game_state, but looks the pawn structure up in the table first and tells whether it
+//was found there. Without params the compiled-in ones are used
+pub fn eval_game_state_with_pawn_hash(
+    g: &GameState,
+    attacks: &GameStateAttackContainer,
+    params: Option<&EvalParams>,
+    pawn_hash: &mut PawnHashTable,
+    _alpha: i16,
+    _beta: i16,
+) -> (EvaluationResult, bool) {
+    evaluate(g, attacks, params, Some(pawn_hash))
+}
+
+fn evaluate(
+    g: &GameState,
+    attacks: &GameStateAttackContainer,
+    params: Option<&EvalParams>,
+    pawn_hash: Option<&mut PawnHashTable>,
+) -> (EvaluationResult, bool) {
     #[cfg(feature = "display-eval")]
     {
         log(&format!("Evaluating GameState fen: {}\n", g.to_fen()));
     }
-    let mut result = EvaluationResult {
-        final_eval: 0,
-        #[cfg(feature = "texel-tuning")]
-        trace: Trace::default(),
-    };
+    classical_eval(
+        g,
+        attacks,
+        params,
+        pawn_hash,
+        EvaluationResult {
+            final_eval: 0,
+            trace: None,
+        },
+    )
+}
+
+//The classical evaluation, recording how often every parameter was used. The trace is what the
+//tuner optimizes and what the eval command breaks down
+pub fn trace_game_state(g: &GameState) -> EvaluationResult {
+    let attacks = GameStateAttackContainer::from_state(g);
+    classical_eval(
+        g,
+        &attacks,
+        None,
+        None,
+        EvaluationResult {
+            final_eval: 0,
+            trace: Some(Box::new(Trace::default())),
+        },
+    )
+    .0
+}
+
+//Traces of white's and black's terms on their own, adding up to the trace of the whole
+//evaluation. Black's terms count negatively there as well. The tempo bonus goes to the side to move
+pub fn trace_sides(g: &GameState) -> [Trace; 2] {
+    let attacks = GameStateAttackContainer::from_state(g);
+    let params = &DEFAULT_EVAL_PARAMS;
+    let mut res = [Trace::default(), Trace::default()];
+    for (side, trace) in res.iter_mut().enumerate() {
+        let white = side == WHITE;
+        let mut result = EvaluationResult {
+            final_eval: 0,
+            trace: Some(Box::new(Trace::default())),
+        };
+        psqt(white, &g.pieces, params, &mut result);
+        piece_values(white, g, params, &mut result);
+        pawns(white, g, params, &mut result, &attacks);
+        knights(white, g, params, &mut result, &attacks);
+        piecewise(white, g, params, &mut result, &attacks);
+        king(white, g, params, &mut result);
+        *trace = *result.trace.expect("traced evaluation");
+        trace.phase = g.phase.phase;
+        if side == g.color_to_move {
+            trace.tempo_bonus = if white { 1 } else { -1 };
+        }
+    }
+    res
+}
+
+//Also returns whether the pawn structure was found in the pawn hash table
+fn classical_eval(
+    g: &GameState,
+    attacks: &GameStateAttackContainer,
+    params: Option<&EvalParams>,
+    pawn_hash: Option<&mut PawnHashTable>,
+    mut result: EvaluationResult,
+) -> (EvaluationResult, bool) {
+    //Positions keep the piece-square score of the compiled-in tables up to date, so it has to be
+    //recomputed for loaded ones
+    let recompute_psqt =
+        params.is_some() || cfg!(feature = "display-eval") || result.trace.is_some();
+    let params = params.unwrap_or(&DEFAULT_EVAL_PARAMS);
     let phase = g.phase.phase;
-    #[cfg(feature = "texel-tuning")]
-    {
-        result.trace.phase = phase;
+    if let Some(trace) = result.trace.as_mut() {
+        trace.phase = phase;
     }
     let mut res = EvaluationScore::default();
 
     if g.color_to_move == WHITE {
-        res += TEMPO_BONUS;
+        res += params.tempo_bonus;
     } else {
-        res -= TEMPO_BONUS;
+        res -= params.tempo_bonus;
     }
     #[cfg(feature = "display-eval")]
     {
         let tempo = if g.color_to_move == WHITE {
-            TEMPO_BONUS
+            params.tempo_bonus
         } else {
-            TEMPO_BONUS * -1
+            params.tempo_bonus * -1
         };
         log(&format!("\nTempo:{}\n", tempo));
     }
-    #[cfg(feature = "texel-tuning")]
-    {
-        result.trace.tempo_bonus = if g.color_to_move == WHITE { 1 } else { -1 };
+    if let Some(trace) = result.trace.as_mut() {
+        trace.tempo_bonus = if g.color_to_move == WHITE { 1 } else { -1 };
     }
 
-    let psqt_score: EvaluationScore =
-        if cfg!(feature = "display-eval") || cfg!(feature = "texel-tuning") {
-            let (psqt_w, psqt_b) = (
-                psqt(true, &g.pieces, &mut result),
-                psqt(false, &g.pieces, &mut result),
-            );
-            psqt_w - psqt_b
-        } else {
-            g.psqt
-        };
+    let psqt_score: EvaluationScore = if recompute_psqt {
+        let (psqt_w, psqt_b) = (
+            psqt(true, &g.pieces, params, &mut result),
+            psqt(false, &g.pieces, params, &mut result),
+        );
+        psqt_w - psqt_b
+    } else {
+        g.psqt
+    };
     #[cfg(feature = "display-eval")]
     {
         log(&format!("\nPSQT Sum: {}\n", psqt_score));
@@ -167,8 +262,8 @@ pub fn eval_game_state(
     res += psqt_score;
 
     let (pieces_w, pieces_b) = (
-        piece_values(true, g, &mut result),
-        piece_values(false, g, &mut result),
+        piece_values(true, g, params, &mut result),
+        piece_values(false, g, params, &mut result),
     );
     #[cfg(feature = "display-eval")]
     {
@@ -191,10 +286,40 @@ pub fn eval_game_state(
         result.final_eval = lazy_eval - FIRST_LAZY_MARGIN;
         return result;
     }*/
-    let (pawns_w, pawns_b) = (
-        pawns(true, g, &mut result, attacks),
-        pawns(false, g, &mut result, attacks),
-    );
+    //A trace has to count the cached terms as well
+    let mut pawn_hash_hit = false;
+    let (pawns_w, pawns_b) = match pawn_hash {
+        Some(table) if result.trace.is_none() => {
+            let entry = match table.probe(g.pawn_hash) {
+                Some(entry) => {
+                    pawn_hash_hit = true;
+                    entry
+                }
+                None => {
+                    let (structure_w, passers_w) =
+                        pawn_structure(true, g, params, &mut result, attacks);
+                    let (structure_b, passers_b) =
+                        pawn_structure(false, g, params, &mut result, attacks);
+                    let entry = PawnHashEntry {
+                        key: g.pawn_hash,
+                        score: structure_w - structure_b,
+                        passers: [passers_w, passers_b],
+                    };
+                    table.store(entry);
+                    entry
+                }
+            };
+            (
+                entry.score
+                    + pawn_dynamics(true, g, params, &mut result, attacks, entry.passers[WHITE]),
+                pawn_dynamics(false, g, params, &mut result, attacks, entry.passers[BLACK]),
+            )
+        }
+        _ => (
+            pawns(true, g, params, &mut result, attacks),
+            pawns(false, g, params, &mut result, attacks),
+        ),
+    };
     #[cfg(feature = "display-eval")]
     {
         log(&format!(
@@ -218,8 +343,8 @@ pub fn eval_game_state(
     }*/
 
     let (knights_w, knights_b) = (
-        knights(true, g, &mut result, attacks),
-        knights(false, g, &mut result, attacks),
+        knights(true, g, params, &mut result, attacks),
+        knights(false, g, params, &mut result, attacks),
     );
     #[cfg(feature = "display-eval")]
     {
@@ -233,8 +358,8 @@ pub fn eval_game_state(
     res += knights_w - knights_b;
 
     let (piecewise_w, piecewise_b) = (
-        piecewise(true, g, &mut result, attacks),
-        piecewise(false, g, &mut result, attacks),
+        piecewise(true, g, params, &mut result, attacks),
+        piecewise(false, g, params, &mut result, attacks),
     );
     #[cfg(feature = "display-eval")]
     {
@@ -247,7 +372,10 @@ pub fn eval_game_state(
     }
     res += piecewise_w - piecewise_b;
 
-    let (king_w, king_b) = (king(true, g, &mut result), king(false, g, &mut result));
+    let (king_w, king_b) = (
+        king(true, g, params, &mut result),
+        king(false, g, params, &mut result),
+    );
     #[cfg(feature = "display-eval")]
     {
         log(&format!(
@@ -273,9 +401,9 @@ pub fn eval_game_state(
             pawns_w - pawns_b,
             pieces_w - pieces_b,
             if g.color_to_move == 0 {
-                TEMPO_BONUS
+                params.tempo_bonus
             } else {
-                TEMPO_BONUS * -1
+                params.tempo_bonus * -1
             },
             res
         ));
@@ -286,12 +414,13 @@ pub fn eval_game_state(
         ));
     }
     result.final_eval = final_res;
-    result
+    (result, pawn_hash_hit)
 }
 
 pub fn knights(
     white: bool,
     g: &GameState,
+    params: &EvalParams,
     _eval: &mut EvaluationResult,
     attack_container: &GameStateAttackContainer,
 ) -> EvaluationScore {
@@ -302,10 +431,9 @@ pub fn knights(
 
     let supported_knights = g.pieces[KNIGHT][side] & my_pawn_attacks;
     let supported_knights_amount = supported_knights.count_ones() as i16;
-    res += KNIGHT_SUPPORTED_BY_PAWN * supported_knights_amount;
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.knight_supported +=
+    res += params.knight_supported * supported_knights_amount;
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.knight_supported +=
             supported_knights_amount as i8 * if side == WHITE { 1 } else { -1 };
     }
     let mut outpost = EvaluationScore::default();
@@ -325,11 +453,9 @@ pub fn knights(
                 idx = BLACK_INDEX[idx];
             }
             _outposts += 1;
-            outpost += KNIGHT_OUTPOST_TABLE[idx / 8][idx % 8];
-            #[cfg(feature = "texel-tuning")]
-            {
-                _eval.trace.knight_outpost_table[idx / 8][idx % 8] +=
-                    if side == WHITE { 1 } else { -1 };
+            outpost += params.knight_outpost_table[idx / 8][idx % 8];
+            if let Some(trace) = _eval.trace.as_mut() {
+                trace.knight_outpost_table[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
             }
         }
     }
@@ -343,7 +469,7 @@ pub fn knights(
         log(&format!(
             "\tSupported by pawns: {} -> {}\n",
             supported_knights_amount,
-            KNIGHT_SUPPORTED_BY_PAWN * supported_knights_amount,
+            params.knight_supported * supported_knights_amount,
         ));
         log(&format!("\tOutposts: {} -> {}\n", _outposts, outpost));
         log(&format!("Sum: {}\n", res));
@@ -355,6 +481,7 @@ pub fn knights(
 pub fn piecewise(
     white: bool,
     g: &GameState,
+    params: &EvalParams,
     _eval: &mut EvaluationResult,
     attack_container: &GameStateAttackContainer,
 ) -> EvaluationScore {
@@ -364,7 +491,7 @@ pub fn piecewise(
     let defended_squares = attack_container.attacks_sum[1 - side];
     let my_pieces = g.get_pieces_from_side(side);
 
-    let enemy_king_idx = g.king_square(1 - side);
+    let enemy_king_idx = g.king_square(1 - side).index();
     let enemy_king_attackable = if white {
         KING_ZONE_BLACK[enemy_king_idx]
     } else {
@@ -386,23 +513,23 @@ pub fn piecewise(
         let targets = attack_container.attack[MGSA_KNIGHT][side][index] & !my_pieces;
 
         let mobility = targets.count_ones() as usize;
-        mk += KNIGHT_MOBILITY_BONUS[mobility];
+        mk += params.knight_mobility[mobility];
 
         let has_safe_check = (targets & knight_checks & !defended_squares) != 0u64;
         let enemy_king_attacks = targets & enemy_king_attackable;
         if has_safe_check || enemy_king_attacks != 0u64 {
             knight_attackers += 1;
         }
-        knight_attacker_values += KNIGHT_ATTACK_WORTH * enemy_king_attacks.count_ones() as i16;
+        knight_attacker_values +=
+            params.knight_attack_value * enemy_king_attacks.count_ones() as i16;
         if has_safe_check {
-            knight_attacker_values += KNIGHT_SAFE_CHECK;
+            knight_attacker_values += params.knight_check_value;
         }
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.knight_mobility[mobility] += if side == WHITE { 1 } else { -1 };
-            _eval.trace.knight_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.knight_mobility[mobility] += if side == WHITE { 1 } else { -1 };
+            trace.knight_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
             if has_safe_check {
-                _eval.trace.knight_safe_check[side] += 1;
+                trace.knight_safe_check[side] += 1;
             }
         }
         knights ^= 1u64 << idx;
@@ -430,29 +557,29 @@ pub fn piecewise(
         }
         let diagonally_adjacent_pawns =
             (DIAGONALLY_ADJACENT[idx] & g.pieces[PAWN][side]).count_ones() as usize;
-        mb_diag += DIAGONALLY_ADJACENT_SQUARES_WITH_OWN_PAWNS[diagonally_adjacent_pawns];
+        mb_diag += params.diagonally_adjacent_squares_withpawns[diagonally_adjacent_pawns];
 
         let targets = attack_container.attack[MGSA_BISHOP][side][index] & !my_pieces;
         let mobility = targets.count_ones() as usize;
-        mb += BISHOP_MOBILITY_BONUS[mobility];
+        mb += params.bishop_mobility[mobility];
 
         let has_safe_check = (targets & bishop_checks & !defended_squares) != 0u64;
         let enemy_king_attacks = targets & enemy_king_attackable;
         if has_safe_check || enemy_king_attacks != 0u64 {
             bishop_attackers += 1;
         }
-        bishop_attacker_values += BISHOP_ATTACK_WORTH * enemy_king_attacks.count_ones() as i16;
+        bishop_attacker_values +=
+            params.bishop_attack_value * enemy_king_attacks.count_ones() as i16;
         if has_safe_check {
-            bishop_attacker_values += BISHOP_SAFE_CHECK;
+            bishop_attacker_values += params.bishop_check_value;
         }
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.diagonally_adjacent_squares_withpawns[diagonally_adjacent_pawns] +=
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.diagonally_adjacent_squares_withpawns[diagonally_adjacent_pawns] +=
                 if side == WHITE { 1 } else { -1 };
-            _eval.trace.bishop_mobility[mobility] += if side == WHITE { 1 } else { -1 };
-            _eval.trace.bishop_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
+            trace.bishop_mobility[mobility] += if side == WHITE { 1 } else { -1 };
+            trace.bishop_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
             if has_safe_check {
-                _eval.trace.bishop_safe_check[side] += 1;
+                trace.bishop_safe_check[side] += 1;
             }
         }
         bishops ^= 1u64 << idx;
@@ -493,23 +620,22 @@ pub fn piecewise(
         let targets = attack_container.attack[MGSA_ROOKS][side][index] & !my_pieces;
 
         let mobility = targets.count_ones() as usize;
-        mr += ROOK_MOBILITY_BONUS[mobility];
+        mr += params.rook_mobility[mobility];
 
         let has_safe_check = (targets & rook_checks & !defended_squares) != 0u64;
         let enemy_king_attacks = targets & enemy_king_attackable;
         if has_safe_check || enemy_king_attacks != 0u64 {
             rook_attackers += 1;
         }
-        rook_attacker_values += ROOK_ATTACK_WORTH * enemy_king_attacks.count_ones() as i16;
+        rook_attacker_values += params.rook_attack_value * enemy_king_attacks.count_ones() as i16;
         if has_safe_check {
-            rook_attacker_values += ROOK_SAFE_CHECK;
+            rook_attacker_values += params.rook_check_value;
         }
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.rook_mobility[mobility] += if side == WHITE { 1 } else { -1 };
-            _eval.trace.rook_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.rook_mobility[mobility] += if side == WHITE { 1 } else { -1 };
+            trace.rook_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
             if has_safe_check {
-                _eval.trace.rook_safe_check[side] += 1;
+                trace.rook_safe_check[side] += 1;
             }
         }
         rooks ^= 1u64 << idx;
@@ -557,67 +683,68 @@ pub fn piecewise(
         let targets = attack_container.attack[MGSA_QUEEN][side][index] & !my_pieces;
 
         let mobility = targets.count_ones() as usize;
-        mq += QUEEN_MOBILITY_BONUS[mobility];
+        mq += params.queen_mobility[mobility];
 
         let has_safe_check = (targets & (bishop_checks | rook_checks) & !defended_squares) != 0u64;
         let enemy_king_attacks = targets & enemy_king_attackable;
         if has_safe_check || enemy_king_attacks != 0u64 {
             queen_attackers += 1;
         }
-        queen_attacker_values += QUEEN_ATTACK_WORTH * enemy_king_attacks.count_ones() as i16;
+        queen_attacker_values += params.queen_attack_value * enemy_king_attacks.count_ones() as i16;
         if has_safe_check {
-            queen_attacker_values += QUEEN_SAFE_CHECK;
+            queen_attacker_values += params.queen_check_value;
         }
 
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.queen_mobility[mobility] += if side == WHITE { 1 } else { -1 };
-            _eval.trace.queen_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.queen_mobility[mobility] += if side == WHITE { 1 } else { -1 };
+            trace.queen_attacked_sq[side] += enemy_king_attacks.count_ones() as u8;
             if has_safe_check {
-                _eval.trace.queen_safe_check[side] += 1;
+                trace.queen_safe_check[side] += 1;
             }
         }
         queens ^= 1u64 << idx;
         index += 1;
     }
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.rook_on_open += rooks_onopen as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.rook_on_semi_open +=
-            rooks_on_semi_open as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.rook_on_seventh += rooks_onseventh as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.queen_on_open += queens_onopen as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.queen_on_semi_open +=
-            queens_on_semi_open as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.bishop_xray_king += bishop_xray_king as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.rook_xray_king += rook_xray_king as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.queen_xray_king += queen_xray_king as i8 * if side == WHITE { 1 } else { -1 };
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.rook_on_open += rooks_onopen as i8 * if side == WHITE { 1 } else { -1 };
+        trace.rook_on_semi_open += rooks_on_semi_open as i8 * if side == WHITE { 1 } else { -1 };
+        trace.rook_on_seventh += rooks_onseventh as i8 * if side == WHITE { 1 } else { -1 };
+        trace.queen_on_open += queens_onopen as i8 * if side == WHITE { 1 } else { -1 };
+        trace.queen_on_semi_open += queens_on_semi_open as i8 * if side == WHITE { 1 } else { -1 };
+        trace.bishop_xray_king += bishop_xray_king as i8 * if side == WHITE { 1 } else { -1 };
+        trace.rook_xray_king += rook_xray_king as i8 * if side == WHITE { 1 } else { -1 };
+        trace.queen_xray_king += queen_xray_king as i8 * if side == WHITE { 1 } else { -1 };
     }
 
-    let attack_mg = ((SAFETY_TABLE[(knight_attacker_values.0
+    let attack_mg = ((params.safety_table[(knight_attacker_values.0
         + bishop_attacker_values.0
         + rook_attacker_values.0
         + queen_attacker_values.0)
         .min(99) as usize]
         .0 as isize
-        * ATTACK_WEIGHT[(knight_attackers + bishop_attackers + rook_attackers + queen_attackers)
+        * params.attack_weight[(knight_attackers
+            + bishop_attackers
+            + rook_attackers
+            + queen_attackers)
             .min(7) as usize]
             .0 as isize) as f64
         / 100.0) as i16;
-    let attack_eg = ((SAFETY_TABLE[(knight_attacker_values.1
+    let attack_eg = ((params.safety_table[(knight_attacker_values.1
         + bishop_attacker_values.1
         + rook_attacker_values.1
         + queen_attacker_values.1)
         .min(99) as usize]
         .1 as isize
-        * ATTACK_WEIGHT[(knight_attackers + bishop_attackers + rook_attackers + queen_attackers)
+        * params.attack_weight[(knight_attackers
+            + bishop_attackers
+            + rook_attackers
+            + queen_attackers)
             .min(7) as usize]
             .1 as isize) as f64
         / 100.0) as i16;
     let attack = EvaluationScore(attack_mg, attack_eg);
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.attackers[side] =
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.attackers[side] =
             (knight_attackers + bishop_attackers + rook_attackers + queen_attackers).min(7) as u8;
     }
     #[allow(clippy::let_and_return)]
@@ -626,14 +753,14 @@ pub fn piecewise(
         + mr
         + mq
         + mb_diag
-        + ROOK_ON_OPEN_FILE_BONUS * rooks_onopen
-        + ROOK_ON_SEMI_OPEN_FILE_BONUS * rooks_on_semi_open
-        + ROOK_ON_SEVENTH * rooks_onseventh
-        + QUEEN_ON_OPEN_FILE_BONUS * queens_onopen
-        + QUEEN_ON_SEMI_OPEN_FILE_BONUS * queens_on_semi_open
-        + BISHOP_XRAY_KING * bishop_xray_king
-        + ROOK_XRAY_KING * rook_xray_king
-        + QUEEN_XRAY_KING * queen_xray_king
+        + params.rook_on_open * rooks_onopen
+        + params.rook_on_semi_open * rooks_on_semi_open
+        + params.rook_on_seventh * rooks_onseventh
+        + params.queen_on_open * queens_onopen
+        + params.queen_on_semi_open * queens_on_semi_open
+        + params.bishop_xray_king * bishop_xray_king
+        + params.rook_xray_king * rook_xray_king
+        + params.queen_xray_king * queen_xray_king
         + attack;
 
     #[cfg(feature = "display-eval")]
@@ -650,42 +777,42 @@ pub fn piecewise(
         log(&format!(
             "\tBishopXrayKing : {} -> {}\n",
             bishop_xray_king,
-            BISHOP_XRAY_KING * bishop_xray_king,
+            params.bishop_xray_king * bishop_xray_king,
         ));
         log(&format!(
             "\tRookXrayKing : {} -> {}\n",
             rook_xray_king,
-            ROOK_XRAY_KING * rook_xray_king,
+            params.rook_xray_king * rook_xray_king,
         ));
         log(&format!(
             "\tQueenXrayKing : {} -> {}\n",
             queen_xray_king,
-            QUEEN_XRAY_KING * queen_xray_king,
+            params.queen_xray_king * queen_xray_king,
         ));
         log(&format!(
             "\tRooks on open  : {} -> {}\n",
             rooks_onopen,
-            ROOK_ON_OPEN_FILE_BONUS * rooks_onopen,
+            params.rook_on_open * rooks_onopen,
         ));
         log(&format!(
             "\tRooks on semi-open  : {} -> {}\n",
             rooks_on_semi_open,
-            ROOK_ON_SEMI_OPEN_FILE_BONUS * rooks_on_semi_open,
+            params.rook_on_semi_open * rooks_on_semi_open,
         ));
         log(&format!(
             "\tQueens on open  : {} -> {}\n",
             queens_onopen,
-            QUEEN_ON_OPEN_FILE_BONUS * queens_onopen,
+            params.queen_on_open * queens_onopen,
         ));
         log(&format!(
             "\tQueens on semi-open  : {} -> {}\n",
             queens_on_semi_open,
-            QUEEN_ON_SEMI_OPEN_FILE_BONUS * queens_on_semi_open,
+            params.queen_on_semi_open * queens_on_semi_open,
         ));
         log(&format!(
             "\tRooks on seventh: {} -> {}\n",
             rooks_onseventh,
-            ROOK_ON_SEVENTH * rooks_onseventh
+            params.rook_on_seventh * rooks_onseventh
         ));
         log(&format!(
             "\tKnight Attackers: Num: {} , Val: {}\n",
@@ -713,26 +840,32 @@ pub fn piecewise(
         ));
         log(&format!(
             "\tAttack MG value: {} * {} / 100.0 -> {}\n",
-            SAFETY_TABLE[(knight_attacker_values.0
+            params.safety_table[(knight_attacker_values.0
                 + bishop_attacker_values.0
                 + rook_attacker_values.0
                 + queen_attacker_values.0)
                 .min(99) as usize]
                 .0,
-            ATTACK_WEIGHT[(knight_attackers + bishop_attackers + rook_attackers + queen_attackers)
+            params.attack_weight[(knight_attackers
+                + bishop_attackers
+                + rook_attackers
+                + queen_attackers)
                 .min(7) as usize]
                 .0,
             attack_mg
         ));
         log(&format!(
             "\tAttack EG value: {} * {} / 100.0 -> {}\n",
-            SAFETY_TABLE[(knight_attacker_values.1
+            params.safety_table[(knight_attacker_values.1
                 + bishop_attacker_values.1
                 + rook_attacker_values.1
                 + queen_attacker_values.1)
                 .min(99) as usize]
                 .1,
-            ATTACK_WEIGHT[(knight_attackers + bishop_attackers + rook_attackers + queen_attackers)
+            params.attack_weight[(knight_attackers
+                + bishop_attackers
+                + rook_attackers
+                + queen_attackers)
                 .min(7) as usize]
                 .1,
             attack_eg
@@ -742,7 +875,12 @@ pub fn piecewise(
     res
 }
 
-pub fn king(white: bool, g: &GameState, _eval: &mut EvaluationResult) -> EvaluationScore {
+pub fn king(
+    white: bool,
+    g: &GameState,
+    params: &EvalParams,
+    _eval: &mut EvaluationResult,
+) -> EvaluationScore {
     let side = if white { WHITE } else { BLACK };
     let mut pawn_shield = if white {
         SHIELDING_PAWNS_WHITE[g.pieces[KING][side].trailing_zeros() as usize]
@@ -755,10 +893,10 @@ pub fn king(white: bool, g: &GameState, _eval: &mut EvaluationResult) -> Evaluat
         bitboards::b_front_span(g.pieces[KING][side])
     };
     king_front_span |= bitboards::west_one(king_front_span) | bitboards::east_one(king_front_span);
-    let file = g.king_square(side) % 8;
-    if file == 7 {
+    let file = g.king_square(side).file();
+    if file == File::H {
         king_front_span |= bitboards::west_one(king_front_span);
-    } else if file == 0 {
+    } else if file == File::A {
         king_front_span |= bitboards::east_one(king_front_span);
     }
     let mut shields_missing = 0;
@@ -775,15 +913,14 @@ pub fn king(white: bool, g: &GameState, _eval: &mut EvaluationResult) -> Evaluat
             pawn_shield &= !FILES[idx % 8];
         }
     }
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.shielding_pawn_missing[shields_missing] += if side == WHITE { 1 } else { -1 };
-        _eval.trace.shielding_pawn_onopen_missing[shields_on_open_missing] +=
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.shielding_pawn_missing[shields_missing] += if side == WHITE { 1 } else { -1 };
+        trace.shielding_pawn_onopen_missing[shields_on_open_missing] +=
             if side == WHITE { 1 } else { -1 };
     }
     #[allow(clippy::let_and_return)]
-    let res = SHIELDING_PAWN_MISSING[shields_missing]
-        + SHIELDING_PAWN_MISSING_ON_OPEN_FILE[shields_on_open_missing];
+    let res = params.shielding_pawn_missing[shields_missing]
+        + params.shielding_pawn_onopen_missing[shields_on_open_missing];
 
     #[cfg(feature = "display-eval")]
     {
@@ -793,11 +930,11 @@ pub fn king(white: bool, g: &GameState, _eval: &mut EvaluationResult) -> Evaluat
         ));
         log(&format!(
             "\tShield pawn missing: {} -> {}\n",
-            shields_missing, SHIELDING_PAWN_MISSING[shields_missing],
+            shields_missing, params.shielding_pawn_missing[shields_missing],
         ));
         log(&format!(
             "\tShield pawn on open file missing: {} -> {}\n",
-            shields_on_open_missing, SHIELDING_PAWN_MISSING_ON_OPEN_FILE[shields_on_open_missing],
+            shields_on_open_missing, params.shielding_pawn_onopen_missing[shields_on_open_missing],
         ));
         log(&format!("Sum: {}\n", res));
     }
@@ -811,12 +948,25 @@ pub fn get_distance(sq: isize, sq2: isize) -> usize {
 pub fn pawns(
     white: bool,
     g: &GameState,
+    params: &EvalParams,
     _eval: &mut EvaluationResult,
     attack_container: &GameStateAttackContainer,
 ) -> EvaluationScore {
+    let (structure, passed_pawns) = pawn_structure(white, g, params, _eval, attack_container);
+    structure + pawn_dynamics(white, g, params, _eval, attack_container, passed_pawns)
+}
+
+//The terms which only depend on the pawns, so that they can be kept in the pawn hash table.
+//Returns them along with the passed pawns
+pub fn pawn_structure(
+    white: bool,
+    g: &GameState,
+    params: &EvalParams,
+    _eval: &mut EvaluationResult,
+    attack_container: &GameStateAttackContainer,
+) -> (EvaluationScore, u64) {
     let mut res = EvaluationScore::default();
     let side = if white { WHITE } else { BLACK };
-    let empty = !g.get_all_pieces();
     //Bitboards
     let pawn_file_fill = bitboards::file_fill(g.pieces[PAWN][side]);
     let front_span = if white {
@@ -837,19 +987,7 @@ pub fn pawns(
         attack_container.pawn_attacks[1 - side],
     );
     let my_pawn_attacks = my_west_attacks | my_east_attacks;
-    let (my_pawn_pushes, my_pawn_double_pushes) = if white {
-        (
-            movegen::w_single_push_pawn_targets(g.pieces[PAWN][side], empty),
-            movegen::w_double_push_pawn_targets(g.pieces[PAWN][side], empty),
-        )
-    } else {
-        (
-            movegen::b_single_push_pawn_targets(g.pieces[PAWN][side], empty),
-            movegen::b_double_push_pawn_targets(g.pieces[PAWN][side], empty),
-        )
-    };
     let is_attackable = bitboards::west_one(front_span) | bitboards::east_one(front_span);
-    let enemy_pieces = g.get_pieces_from_side(1 - side);
 
     let doubled_pawns = (g.pieces[PAWN][side] & front_span).count_ones() as i16;
     let isolated_pawns = (g.pieces[PAWN][side]
@@ -872,10 +1010,9 @@ pub fn pawns(
         if !white {
             index = BLACK_INDEX[index];
         }
-        supp += PAWN_SUPPORTED_VALUE[index / 8][index % 8];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.pawn_supported[index / 8][index % 8] += if side == WHITE { 1 } else { -1 };
+        supp += params.pawn_supported[index / 8][index % 8];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.pawn_supported[index / 8][index % 8] += if side == WHITE { 1 } else { -1 };
         }
     }
     res += supp;
@@ -886,27 +1023,19 @@ pub fn pawns(
             bitboards::north_east_one(INNER_CENTER) | bitboards::north_west_one(INNER_CENTER)
         })
     .count_ones() as i16;
-    let pawn_mobility = (my_west_attacks.count_ones()
-        + my_east_attacks.count_ones()
-        + my_pawn_pushes.count_ones()
-        + my_pawn_double_pushes.count_ones()) as i16;
-    res += PAWN_DOUBLED_VALUE * doubled_pawns
-        + PAWN_ISOLATED_VALUE * isolated_pawns
-        + PAWN_BACKWARD_VALUE * backward_pawns
-        + PAWN_ATTACK_CENTER * center_attack_pawns
-        + PAWN_MOBILITY * pawn_mobility;
+    res += params.pawn_doubled * doubled_pawns
+        + params.pawn_isolated * isolated_pawns
+        + params.pawn_backward * backward_pawns
+        + params.pawn_attack_center * center_attack_pawns;
 
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.pawn_doubled += doubled_pawns as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.pawn_isolated += isolated_pawns as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.pawn_backward += backward_pawns as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.pawn_attack_center +=
-            center_attack_pawns as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.pawn_mobility += pawn_mobility as i8 * if side == WHITE { 1 } else { -1 };
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.pawn_doubled += doubled_pawns as i8 * if side == WHITE { 1 } else { -1 };
+        trace.pawn_isolated += isolated_pawns as i8 * if side == WHITE { 1 } else { -1 };
+        trace.pawn_backward += backward_pawns as i8 * if side == WHITE { 1 } else { -1 };
+        trace.pawn_attack_center += center_attack_pawns as i8 * if side == WHITE { 1 } else { -1 };
     }
     //Passers
-    let mut passed_pawns: u64 = g.pieces[PAWN][side]
+    let passed_pawns: u64 = g.pieces[PAWN][side]
 
         /*& !if white {
             bitboards::w_rear_span(g.pieces[PAWN][side])
@@ -914,6 +1043,75 @@ pub fn pawns(
             bitboards::b_rear_span(g.pieces[PAWN][side])
         }*/
         & !enemy_front_spans;
+    #[cfg(feature = "display-eval")]
+    {
+        log(&format!(
+            "\nPawns for {}:\n",
+            if white { "White" } else { "Black" }
+        ));
+        log(&format!(
+            "\tDoubled: {} -> {}\n",
+            doubled_pawns,
+            params.pawn_doubled * doubled_pawns
+        ));
+        log(&format!(
+            "\tIsolated: {} -> {}\n",
+            isolated_pawns,
+            params.pawn_isolated * isolated_pawns,
+        ));
+        log(&format!(
+            "\tBackward: {} -> {}\n",
+            backward_pawns,
+            params.pawn_backward * backward_pawns,
+        ));
+        log(&format!("\tSupported: {} -> {}\n", _supported_amt, supp));
+        log(&format!(
+            "\tAttack Center: {} -> {}\n",
+            center_attack_pawns,
+            params.pawn_attack_center * center_attack_pawns,
+        ));
+        log(&format!("Structure Sum: {}\n", res));
+    }
+    (res, passed_pawns)
+}
+
+//The pawn terms which depend on the other pieces as well
+pub fn pawn_dynamics(
+    white: bool,
+    g: &GameState,
+    params: &EvalParams,
+    _eval: &mut EvaluationResult,
+    attack_container: &GameStateAttackContainer,
+    mut passed_pawns: u64,
+) -> EvaluationScore {
+    let mut res = EvaluationScore::default();
+    let side = if white { WHITE } else { BLACK };
+    let empty = !g.get_all_pieces();
+    let (my_west_attacks, my_east_attacks) = (
+        attack_container.pawn_west_attacks[side],
+        attack_container.pawn_east_attacks[side],
+    );
+    let (my_pawn_pushes, my_pawn_double_pushes) = if white {
+        (
+            movegen::w_single_push_pawn_targets(g.pieces[PAWN][side], empty),
+            movegen::w_double_push_pawn_targets(g.pieces[PAWN][side], empty),
+        )
+    } else {
+        (
+            movegen::b_single_push_pawn_targets(g.pieces[PAWN][side], empty),
+            movegen::b_double_push_pawn_targets(g.pieces[PAWN][side], empty),
+        )
+    };
+    let enemy_pieces = g.get_pieces_from_side(1 - side);
+    let pawn_mobility = (my_west_attacks.count_ones()
+        + my_east_attacks.count_ones()
+        + my_pawn_pushes.count_ones()
+        + my_pawn_double_pushes.count_ones()) as i16;
+    res += params.pawn_mobility * pawn_mobility;
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.pawn_mobility += pawn_mobility as i8 * if side == WHITE { 1 } else { -1 };
+    }
+    //Passers
     let (mut passer_score, mut _passer_normal, mut _passer_notblocked) =
         (EvaluationScore::default(), 0, 0);
     let mut passer_dist = EvaluationScore::default();
@@ -928,23 +1126,21 @@ pub fn pawns(
     let enemy_rooks_attack_passer = (behind_passers
         & (g.pieces[ROOK][1 - side] | g.pieces[QUEEN][1 - side]))
         .count_ones() as i16;
-    res += ROOK_BEHIND_SUPPORT_PASSER * rooks_support_passer
-        + ROOK_BEHIND_ENEMY_PASSER * enemy_rooks_attack_passer;
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.rook_behind_support_passer +=
+    res += params.rook_behind_support_passer * rooks_support_passer
+        + params.rook_behind_enemy_passer * enemy_rooks_attack_passer;
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.rook_behind_support_passer +=
             rooks_support_passer as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.rook_behind_enemy_passer +=
+        trace.rook_behind_enemy_passer +=
             enemy_rooks_attack_passer as i8 * if side == WHITE { 1 } else { -1 };
     }
     while passed_pawns != 0u64 {
         let idx = passed_pawns.trailing_zeros() as usize;
         //Passed and blocked
         _passer_normal += 1;
-        passer_score += PAWN_PASSED_VALUES[if white { idx / 8 } else { 7 - idx / 8 }];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.pawn_passed[if white { idx / 8 } else { 7 - idx / 8 }] +=
+        passer_score += params.pawn_passed[if white { idx / 8 } else { 7 - idx / 8 }];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.pawn_passed[if white { idx / 8 } else { 7 - idx / 8 }] +=
                 if side == WHITE { 1 } else { -1 };
         }
         //A weak passer is an attacked and not defended passer
@@ -967,67 +1163,42 @@ pub fn pawns(
             //Passed and not blocked
             _passer_notblocked += 1;
             passer_score +=
-                PAWN_PASSED_NOT_BLOCKED_VALUES[if white { idx / 8 } else { 7 - idx / 8 }];
-            #[cfg(feature = "texel-tuning")]
-            {
-                _eval.trace.pawn_passed_notblocked[if white { idx / 8 } else { 7 - idx / 8 }] +=
+                params.pawn_passed_notblocked[if white { idx / 8 } else { 7 - idx / 8 }];
+            if let Some(trace) = _eval.trace.as_mut() {
+                trace.pawn_passed_notblocked[if white { idx / 8 } else { 7 - idx / 8 }] +=
                     if side == WHITE { 1 } else { -1 };
             }
         }
 
         //Distance to kings
-        let d_myking = get_distance(idx as isize, g.king_square(side) as isize);
-        let d_enemyking = get_distance(idx as isize, g.king_square(1 - side) as isize);
+        let d_myking = get_distance(idx as isize, g.king_square(side).index() as isize);
+        let d_enemyking = get_distance(idx as isize, g.king_square(1 - side).index() as isize);
         let sub_dist = ((d_myking as isize - d_enemyking as isize) + 6) as usize;
-        passer_dist += PASSED_KING_DISTANCE[d_myking - 1]
-            + PASSED_ENEMY_KING_DISTANCE[d_enemyking - 1]
-            + PASSED_SUBTRACT_DISTANCE[sub_dist];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.pawn_passed_kingdistance[d_myking - 1] +=
+        passer_dist += params.pawn_passed_kingdistance[d_myking - 1]
+            + params.pawn_passed_enemykingdistance[d_enemyking - 1]
+            + params.pawn_passed_subdistance[sub_dist];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.pawn_passed_kingdistance[d_myking - 1] += if side == WHITE { 1 } else { -1 };
+            trace.pawn_passed_enemykingdistance[d_enemyking - 1] +=
                 if side == WHITE { 1 } else { -1 };
-            _eval.trace.pawn_passed_enemykingdistance[d_enemyking - 1] +=
-                if side == WHITE { 1 } else { -1 };
-            _eval.trace.pawn_passed_subdistance[sub_dist] += if side == WHITE { 1 } else { -1 };
+            trace.pawn_passed_subdistance[sub_dist] += if side == WHITE { 1 } else { -1 };
         }
         passed_pawns ^= 1u64 << idx;
     }
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.pawn_passed_weak += weak_passers as i8 * if side == WHITE { 1 } else { -1 };
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.pawn_passed_weak += weak_passers as i8 * if side == WHITE { 1 } else { -1 };
     }
-    res += passer_score + PAWN_PASSED_WEAK * weak_passers + passer_dist;
+    res += passer_score + params.pawn_passed_weak * weak_passers + passer_dist;
     #[cfg(feature = "display-eval")]
     {
         log(&format!(
-            "\nPawns for {}:\n",
+            "\nPawn dynamics for {}:\n",
             if white { "White" } else { "Black" }
         ));
-        log(&format!(
-            "\tDoubled: {} -> {}\n",
-            doubled_pawns,
-            PAWN_DOUBLED_VALUE * doubled_pawns
-        ));
-        log(&format!(
-            "\tIsolated: {} -> {}\n",
-            isolated_pawns,
-            PAWN_ISOLATED_VALUE * isolated_pawns,
-        ));
-        log(&format!(
-            "\tBackward: {} -> {}\n",
-            backward_pawns,
-            PAWN_BACKWARD_VALUE * backward_pawns,
-        ));
-        log(&format!("\tSupported: {} -> {}\n", _supported_amt, supp));
-        log(&format!(
-            "\tAttack Center: {} -> {}\n",
-            center_attack_pawns,
-            PAWN_ATTACK_CENTER * center_attack_pawns,
-        ));
         log(&format!(
             "\tMobility: {} -> {}\n",
             pawn_mobility,
-            PAWN_MOBILITY * pawn_mobility,
+            params.pawn_mobility * pawn_mobility,
         ));
         log(&format!(
             "\tPasser Blocked/Not Blocked: {} , {} -> {}\n",
@@ -1036,17 +1207,17 @@ pub fn pawns(
         log(&format!(
             "\tRook behind passer: {} -> {}\n",
             rooks_support_passer,
-            ROOK_BEHIND_SUPPORT_PASSER * rooks_support_passer,
+            params.rook_behind_support_passer * rooks_support_passer,
         ));
         log(&format!(
             "\tEnemy Rook behind passer: {} -> {}\n",
             enemy_rooks_attack_passer,
-            ROOK_BEHIND_ENEMY_PASSER * enemy_rooks_attack_passer,
+            params.rook_behind_enemy_passer * enemy_rooks_attack_passer,
         ));
         log(&format!(
             "\tWeak passer: {} -> {}\n",
             weak_passers,
-            PAWN_PASSED_WEAK * weak_passers,
+            params.pawn_passed_weak * weak_passers,
         ));
         log(&format!("\tPassers distance to kings -> {}\n", passer_dist));
         log(&format!("Sum: {}\n", res));
@@ -1054,7 +1225,12 @@ pub fn pawns(
     res
 }
 
-pub fn piece_values(white: bool, g: &GameState, _eval: &mut EvaluationResult) -> EvaluationScore {
+pub fn piece_values(
+    white: bool,
+    g: &GameState,
+    params: &EvalParams,
+    _eval: &mut EvaluationResult,
+) -> EvaluationScore {
     let mut res = EvaluationScore::default();
     let side = if white { WHITE } else { BLACK };
 
@@ -1067,32 +1243,32 @@ pub fn piece_values(white: bool, g: &GameState, _eval: &mut EvaluationResult) ->
         my_knights = 0;
         my_bishops = 0;
     }
-    res += PAWN_PIECE_VALUE * my_pawns;
+    res += params.pawn_piece_value * my_pawns;
 
     let pawns_on_board = (g.pieces[PAWN][WHITE] | g.pieces[PAWN][BLACK]).count_ones() as usize;
 
-    res += (KNIGHT_PIECE_VALUE + KNIGHT_VALUE_WITH_PAWNS[pawns_on_board]) * my_knights;
+    res +=
+        (params.knight_piece_value + params.knight_value_with_pawns[pawns_on_board]) * my_knights;
 
-    res += BISHOP_PIECE_VALUE * my_bishops;
+    res += params.bishop_piece_value * my_bishops;
     if my_bishops > 1 {
-        res += BISHOP_PAIR_BONUS;
+        res += params.bishop_pair;
     }
 
-    res += ROOK_PIECE_VALUE * my_rooks;
+    res += params.rook_piece_value * my_rooks;
 
-    res += QUEEN_PIECE_VALUE * my_queens;
+    res += params.queen_piece_value * my_queens;
 
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.pawns += my_pawns as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.knight_value_with_pawns = pawns_on_board as u8;
-        _eval.trace.knights += my_knights as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.bishops += my_bishops as i8 * if side == WHITE { 1 } else { -1 };
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.pawns += my_pawns as i8 * if side == WHITE { 1 } else { -1 };
+        trace.knight_value_with_pawns = pawns_on_board as u8;
+        trace.knights += my_knights as i8 * if side == WHITE { 1 } else { -1 };
+        trace.bishops += my_bishops as i8 * if side == WHITE { 1 } else { -1 };
         if my_bishops > 1 {
-            _eval.trace.bishop_bonus += if side == WHITE { 1 } else { -1 };
+            trace.bishop_bonus += if side == WHITE { 1 } else { -1 };
         }
-        _eval.trace.rooks += my_rooks as i8 * if side == WHITE { 1 } else { -1 };
-        _eval.trace.queens += my_queens as i8 * if side == WHITE { 1 } else { -1 };
+        trace.rooks += my_rooks as i8 * if side == WHITE { 1 } else { -1 };
+        trace.queens += my_queens as i8 * if side == WHITE { 1 } else { -1 };
     }
     #[cfg(feature = "display-eval")]
     {
@@ -1103,30 +1279,31 @@ pub fn piece_values(white: bool, g: &GameState, _eval: &mut EvaluationResult) ->
         log(&format!(
             "\tPawns: {} -> {}\n",
             my_pawns,
-            PAWN_PIECE_VALUE * my_pawns,
+            params.pawn_piece_value * my_pawns,
         ));
         log(&format!(
             "\tKnights: {} -> {}\n",
             my_knights,
-            (KNIGHT_PIECE_VALUE + KNIGHT_VALUE_WITH_PAWNS[pawns_on_board]) * my_knights,
+            (params.knight_piece_value + params.knight_value_with_pawns[pawns_on_board])
+                * my_knights,
         ));
         log(&format!(
             "\tBishops: {} -> {}\n",
             my_bishops,
-            BISHOP_PIECE_VALUE * my_bishops,
+            params.bishop_piece_value * my_bishops,
         ));
         if my_bishops > 1 {
-            log(&format!("\tBishop-Pair: {} -> {}\n", 1, BISHOP_PAIR_BONUS));
+            log(&format!("\tBishop-Pair: {} -> {}\n", 1, params.bishop_pair));
         }
         log(&format!(
             "\tRooks: {} -> {}\n",
             my_rooks,
-            ROOK_PIECE_VALUE * my_rooks,
+            params.rook_piece_value * my_rooks,
         ));
         log(&format!(
             "\tQueens: {} -> {}\n",
             my_queens,
-            QUEEN_PIECE_VALUE * my_queens,
+            params.queen_piece_value * my_queens,
         ));
         log(&format!("Sum: {}\n", res));
     }
@@ -0,0 +1,307 @@
+//Efficiently updatable network evaluation. Every side looks at the board from its own point of
+//view (ranks flipped for black) and has an accumulator holding the first layer of the network for
+//the pieces on the board. Moves only add and remove a few features, so make_move keeps the
+//accumulators up to date instead of recomputing them: make_move records the move and the network
+//of the search applies it. The rest of the network is a single output neuron over the clipped
+//accumulators of the side to move and the other side.
+//Only integer arithmetic is used, so results are the same on every target, wasm32 included.
+//The network itself is always available for training tools, the nnue feature makes the engine
+//use it.
+#[cfg(feature = "nnue")]
+use crate::board_representation::game_state::GameState;
+use crate::board_representation::game_state::{GameMove, PieceType, Square, KING, WHITE};
+#[cfg(feature = "nnue")]
+use crate::move_generation::makemove::update_accumulator;
+use std::fs;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+//Features are bucketed by the file pair the own king stands on
+pub const KING_BUCKETS: usize = 4;
+pub const FEATURES: usize = KING_BUCKETS * 12 * 64;
+pub const HIDDEN_SIZE: usize = 128;
+//Accumulator values are clipped to [0, QA], output weights are scaled by QB
+pub const QA: i32 = 255;
+pub const QB: i32 = 64;
+//Centipawns per unit of network output
+pub const EVAL_SCALE: i32 = 400;
+
+const MAGIC: &[u8; 4] = b"FNUE";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+pub const FILE_SIZE: usize =
+    HEADER_SIZE + 2 * (FEATURES * HIDDEN_SIZE + HIDDEN_SIZE + 2 * HIDDEN_SIZE) + 4;
+
+//Id 0 belongs to no network, so accumulators start out stale
+static NETWORK_IDS: AtomicU32 = AtomicU32::new(1);
+
+pub struct Network {
+    //FEATURES rows of HIDDEN_SIZE weights
+    pub feature_weights: Vec<i16>,
+    pub feature_biases: Vec<i16>,
+    //Weights for the accumulator of the side to move, followed by the ones for the other side
+    pub output_weights: Vec<i16>,
+    //Scaled by QA * QB
+    pub output_bias: i32,
+    id: u32,
+}
+
+impl Network {
+    pub fn new(
+        feature_weights: Vec<i16>,
+        feature_biases: Vec<i16>,
+        output_weights: Vec<i16>,
+        output_bias: i32,
+    ) -> Network {
+        assert_eq!(feature_weights.len(), FEATURES * HIDDEN_SIZE);
+        assert_eq!(feature_biases.len(), HIDDEN_SIZE);
+        assert_eq!(output_weights.len(), 2 * HIDDEN_SIZE);
+        Network {
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+            id: NETWORK_IDS.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    //Accumulators remember which network they belong to
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn open(path: &str) -> io::Result<Network> {
+        Network::from_bytes(&fs::read(path)?)
+    }
+
+    //Layout, all little endian: "FNUE", version, KING_BUCKETS and HIDDEN_SIZE as u32, then the
+    //feature weights, feature biases and output weights as i16 and the output bias as i32
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Network> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != MAGIC {
+            return Err(invalid("not a network file".to_owned()));
+        }
+        let header_u32 = |at: usize| {
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        if header_u32(4) != VERSION {
+            return Err(invalid(format!("unsupported version {}", header_u32(4))));
+        }
+        if header_u32(8) as usize != KING_BUCKETS || header_u32(12) as usize != HIDDEN_SIZE {
+            return Err(invalid(format!(
+                "network has {} king buckets and {} hidden neurons, expected {} and {}",
+                header_u32(8),
+                header_u32(12),
+                KING_BUCKETS,
+                HIDDEN_SIZE
+            )));
+        }
+        if bytes.len() != FILE_SIZE {
+            return Err(invalid(format!(
+                "network file has {} bytes, expected {}",
+                bytes.len(),
+                FILE_SIZE
+            )));
+        }
+        let mut offset = HEADER_SIZE;
+        let mut read_i16s = |count: usize| -> Vec<i16> {
+            let res = bytes[offset..offset + 2 * count]
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                .collect();
+            offset += 2 * count;
+            res
+        };
+        let feature_weights = read_i16s(FEATURES * HIDDEN_SIZE);
+        let feature_biases = read_i16s(HIDDEN_SIZE);
+        let output_weights = read_i16s(2 * HIDDEN_SIZE);
+        let b = &bytes[FILE_SIZE - 4..];
+        let output_bias = i32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        Ok(Network::new(
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        ))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(FILE_SIZE);
+        res.extend_from_slice(MAGIC);
+        res.extend_from_slice(&VERSION.to_le_bytes());
+        res.extend_from_slice(&(KING_BUCKETS as u32).to_le_bytes());
+        res.extend_from_slice(&(HIDDEN_SIZE as u32).to_le_bytes());
+        for value in self
+            .feature_weights
+            .iter()
+            .chain(self.feature_biases.iter())
+            .chain(self.output_weights.iter())
+        {
+            res.extend_from_slice(&value.to_le_bytes());
+        }
+        res.extend_from_slice(&self.output_bias.to_le_bytes());
+        res
+    }
+
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    //Accumulators for a position, computed from scratch
+    pub fn accumulator(&self, pieces: &[[u64; 2]; 6]) -> Accumulator {
+        let mut acc = Accumulator {
+            network_id: self.id,
+            pending: None,
+            values: [[0; HIDDEN_SIZE]; 2],
+        };
+        self.refresh(&mut acc, pieces, WHITE);
+        self.refresh(&mut acc, pieces, 1 - WHITE);
+        acc
+    }
+
+    pub fn refresh(&self, acc: &mut Accumulator, pieces: &[[u64; 2]; 6], perspective: usize) {
+        acc.values[perspective].copy_from_slice(&self.feature_biases);
+        let king_square = pieces[KING][perspective].trailing_zeros() as usize;
+        for (piece_index, piece_boards) in pieces.iter().enumerate() {
+            for (color, board) in piece_boards.iter().enumerate() {
+                let mut board = *board;
+                while board != 0u64 {
+                    let square = board.trailing_zeros() as usize;
+                    board ^= 1u64 << square;
+                    let feature =
+                        feature_index(perspective, king_square, piece_index, color, square);
+                    self.add_feature(acc, perspective, feature);
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn add_feature(&self, acc: &mut Accumulator, perspective: usize, feature: usize) {
+        let weights = &self.feature_weights[feature * HIDDEN_SIZE..(feature + 1) * HIDDEN_SIZE];
+        for (value, weight) in acc.values[perspective].iter_mut().zip(weights) {
+            *value = value.wrapping_add(*weight);
+        }
+    }
+
+    #[inline(always)]
+    pub fn remove_feature(&self, acc: &mut Accumulator, perspective: usize, feature: usize) {
+        let weights = &self.feature_weights[feature * HIDDEN_SIZE..(feature + 1) * HIDDEN_SIZE];
+        for (value, weight) in acc.values[perspective].iter_mut().zip(weights) {
+            *value = value.wrapping_sub(*weight);
+        }
+    }
+
+    //From the point of view of the side to move, in centipawns
+    pub fn evaluate(&self, acc: &Accumulator, color_to_move: usize) -> i16 {
+        let mut output = i64::from(self.output_bias);
+        let perspectives = [color_to_move, 1 - color_to_move];
+        for (perspective, weights) in perspectives
+            .iter()
+            .zip(self.output_weights.chunks_exact(HIDDEN_SIZE))
+        {
+            for (value, weight) in acc.values[*perspective].iter().zip(weights) {
+                output += i64::from(i32::from(*value).clamp(0, QA) * i32::from(*weight));
+            }
+        }
+        (output * i64::from(EVAL_SCALE) / i64::from(QA * QB)).clamp(-10000, 10000) as i16
+    }
+
+    //The accumulators of the state, updated incrementally if they only miss the last move and
+    //computed from scratch if they belong to another network
+    #[cfg(feature = "nnue")]
+    pub fn state_accumulator(&self, g: &GameState) -> Accumulator {
+        let mut acc = g.nnue;
+        if acc.network_id != self.id {
+            return self.accumulator(&g.pieces);
+        }
+        if let Some((mv, captured_piece)) = acc.pending.take() {
+            update_accumulator(self, &mut acc, g, mv, captured_piece);
+        }
+        acc
+    }
+
+    //Keeps the accumulators of a position the search goes on from up to date, so that the
+    //positions after it are updated incrementally as well
+    #[cfg(feature = "nnue")]
+    pub fn update(&self, g: &mut GameState) {
+        g.nnue = self.state_accumulator(g);
+    }
+
+    #[cfg(feature = "nnue")]
+    pub fn evaluate_state(&self, g: &GameState) -> i16 {
+        self.evaluate(&self.state_accumulator(g), g.color_to_move)
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Accumulator {
+    //The network the values were computed with, 0 if none
+    pub network_id: u32,
+    //The move played since and the piece it captured, which the network still has to apply
+    pub pending: Option<(GameMove, Option<PieceType>)>,
+    //Index 1: Perspective
+    pub values: [[i16; HIDDEN_SIZE]; 2],
+}
+
+//Stale until a network computes the values
+impl Default for Accumulator {
+    fn default() -> Self {
+        Accumulator {
+            network_id: 0,
+            pending: None,
+            values: [[0; HIDDEN_SIZE]; 2],
+        }
+    }
+}
+
+impl Accumulator {
+    //Only the last move can be applied incrementally, the values are stale after a second one
+    #[inline(always)]
+    pub fn play(&mut self, mv: GameMove, captured_piece: Option<PieceType>) {
+        if self.pending.is_some() {
+            self.network_id = 0;
+        }
+        self.pending = Some((mv, captured_piece));
+    }
+}
+
+//Flipping the ranks keeps the file, so the bucket is the same from both points of view
+#[inline(always)]
+pub fn king_bucket(king_square: usize) -> usize {
+    (king_square % 8) / 2
+}
+
+#[inline(always)]
+pub fn feature_index(
+    perspective: usize,
+    king_square: usize,
+    piece_index: usize,
+    color: usize,
+    square: usize,
+) -> usize {
+    let relative_square = if perspective == WHITE {
+        square
+    } else {
+        square ^ 56
+    };
+    let relative_piece = piece_index + if color == perspective { 0 } else { 6 };
+    king_bucket(king_square) * 768 + relative_piece * 64 + relative_square
+}
+
+#[inline(always)]
+pub fn piece_feature(
+    perspective: usize,
+    king_square: usize,
+    piece: PieceType,
+    color: usize,
+    square: Square,
+) -> usize {
+    feature_index(
+        perspective,
+        king_square,
+        piece.to_index(),
+        color,
+        square.index(),
+    )
+}
@@ -1,5 +1,5 @@
-use crate::evaluation::params::*;
-use crate::evaluation::{EG, MG};
+use crate::evaluation::eval_params::{EvalParams, DEFAULT_EVAL_PARAMS};
+use crate::evaluation::{EvaluationScore, EG, MG};
 use std::fmt::{Debug, Display, Formatter, Result};
 use std::fs;
 
@@ -428,272 +428,218 @@ impl Parameters {
         fs::write(file, &format!("{}", self)).expect("Unable to write file");
     }
 
-    #[allow(clippy::needless_range_loop)]
     pub fn default() -> Self {
-        let mut shielding_pawn_missing: [[f64; 4]; 2] = [[0.; 4]; 2];
-        for i in 0..4 {
-            shielding_pawn_missing[MG][i] = f64::from(SHIELDING_PAWN_MISSING[i].0);
-            shielding_pawn_missing[EG][i] = f64::from(SHIELDING_PAWN_MISSING[i].1);
-        }
-        let mut shielding_pawn_onopen_missing: [[f64; 4]; 2] = [[0.; 4]; 2];
-        for i in 0..4 {
-            shielding_pawn_onopen_missing[MG][i] =
-                f64::from(SHIELDING_PAWN_MISSING_ON_OPEN_FILE[i].0);
-            shielding_pawn_onopen_missing[EG][i] =
-                f64::from(SHIELDING_PAWN_MISSING_ON_OPEN_FILE[i].1);
-        }
-        let mut pawn_passed: [[f64; 7]; 2] = [[0.; 7]; 2];
-        for i in 0..7 {
-            pawn_passed[MG][i] = f64::from(PAWN_PASSED_VALUES[i].0);
-            pawn_passed[EG][i] = f64::from(PAWN_PASSED_VALUES[i].1);
-        }
-        let mut pawn_passed_notblocked: [[f64; 7]; 2] = [[0.; 7]; 2];
-        for i in 0..7 {
-            pawn_passed_notblocked[MG][i] = f64::from(PAWN_PASSED_NOT_BLOCKED_VALUES[i].0);
-            pawn_passed_notblocked[EG][i] = f64::from(PAWN_PASSED_NOT_BLOCKED_VALUES[i].1);
-        }
-        let mut pawn_passed_kingdistance: [[f64; 7]; 2] = [[0.; 7]; 2];
-        for i in 0..7 {
-            pawn_passed_kingdistance[MG][i] = f64::from(PASSED_KING_DISTANCE[i].0);
-            pawn_passed_kingdistance[EG][i] = f64::from(PASSED_KING_DISTANCE[i].1);
-        }
-        let mut pawn_passed_enemykingdistance: [[f64; 7]; 2] = [[0.; 7]; 2];
-        for i in 0..7 {
-            pawn_passed_enemykingdistance[MG][i] = f64::from(PASSED_ENEMY_KING_DISTANCE[i].0);
-            pawn_passed_enemykingdistance[EG][i] = f64::from(PASSED_ENEMY_KING_DISTANCE[i].1);
-        }
-        let mut pawn_passed_subdistance: [[f64; 13]; 2] = [[0.; 13]; 2];
-        for i in 0..13 {
-            pawn_passed_subdistance[MG][i] = f64::from(PASSED_SUBTRACT_DISTANCE[i].0);
-            pawn_passed_subdistance[EG][i] = f64::from(PASSED_SUBTRACT_DISTANCE[i].1);
-        }
-        let mut knight_outpost_table: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                knight_outpost_table[MG][i][j] = f64::from(KNIGHT_OUTPOST_TABLE[i][j].0);
-                knight_outpost_table[EG][i][j] = f64::from(KNIGHT_OUTPOST_TABLE[i][j].1);
+        Parameters::from_eval_params(&DEFAULT_EVAL_PARAMS)
+    }
+
+    pub fn from_eval_params(params: &EvalParams) -> Self {
+        fn score(score: EvaluationScore) -> [f64; 2] {
+            [f64::from(score.0), f64::from(score.1)]
+        }
+        fn scores<const N: usize>(scores: &[EvaluationScore; N]) -> [[f64; N]; 2] {
+            let mut res = [[0.; N]; 2];
+            for (i, s) in scores.iter().enumerate() {
+                res[MG][i] = f64::from(s.0);
+                res[EG][i] = f64::from(s.1);
             }
+            res
+        }
+        fn psqt(psqt: &[[EvaluationScore; 8]; 8]) -> [[[f64; 8]; 8]; 2] {
+            let mut res = [[[0.; 8]; 8]; 2];
+            for (i, rank) in psqt.iter().enumerate() {
+                let [mg, eg] = scores(rank);
+                res[MG][i] = mg;
+                res[EG][i] = eg;
+            }
+            res
         }
-        let mut knight_value_with_pawns: [f64; 17] = [0.; 17];
-        for i in 0..17 {
-            knight_value_with_pawns[i] = f64::from(KNIGHT_VALUE_WITH_PAWNS[i]);
-        }
-        let mut diagonally_adjacent_squares_withpawns: [[f64; 5]; 2] = [[0.; 5]; 2];
-        for i in 0..5 {
-            diagonally_adjacent_squares_withpawns[MG][i] =
-                f64::from(DIAGONALLY_ADJACENT_SQUARES_WITH_OWN_PAWNS[i].0);
-            diagonally_adjacent_squares_withpawns[EG][i] =
-                f64::from(DIAGONALLY_ADJACENT_SQUARES_WITH_OWN_PAWNS[i].1);
-        }
-        let mut knight_mobility: [[f64; 9]; 2] = [[0.; 9]; 2];
-        for i in 0..9 {
-            knight_mobility[MG][i] = f64::from(KNIGHT_MOBILITY_BONUS[i].0);
-            knight_mobility[EG][i] = f64::from(KNIGHT_MOBILITY_BONUS[i].1);
-        }
-        let mut bishop_mobility: [[f64; 14]; 2] = [[0.; 14]; 2];
-        for i in 0..14 {
-            bishop_mobility[MG][i] = f64::from(BISHOP_MOBILITY_BONUS[i].0);
-            bishop_mobility[EG][i] = f64::from(BISHOP_MOBILITY_BONUS[i].1);
-        }
-        let mut rook_mobility: [[f64; 15]; 2] = [[0.; 15]; 2];
-        for i in 0..15 {
-            rook_mobility[MG][i] = f64::from(ROOK_MOBILITY_BONUS[i].0);
-            rook_mobility[EG][i] = f64::from(ROOK_MOBILITY_BONUS[i].1);
-        }
-        let mut queen_mobility: [[f64; 28]; 2] = [[0.; 28]; 2];
-        for i in 0..28 {
-            queen_mobility[MG][i] = f64::from(QUEEN_MOBILITY_BONUS[i].0);
-            queen_mobility[EG][i] = f64::from(QUEEN_MOBILITY_BONUS[i].1);
-        }
-        let mut attack_weight: [[f64; 8]; 2] = [[0.; 8]; 2];
-        for i in 0..8 {
-            attack_weight[MG][i] = f64::from(ATTACK_WEIGHT[i].0);
-            attack_weight[EG][i] = f64::from(ATTACK_WEIGHT[i].1);
-        }
-        let mut safety_table: [SafetyTable; 2] = [
-            SafetyTable {
-                safety_table: [0.; 100],
+        Parameters {
+            tempo_bonus: score(params.tempo_bonus),
+            shielding_pawn_missing: scores(&params.shielding_pawn_missing),
+            shielding_pawn_onopen_missing: scores(&params.shielding_pawn_onopen_missing),
+            pawn_doubled: score(params.pawn_doubled),
+            pawn_isolated: score(params.pawn_isolated),
+            pawn_backward: score(params.pawn_backward),
+            pawn_supported: psqt(&params.pawn_supported),
+            pawn_attack_center: score(params.pawn_attack_center),
+            pawn_mobility: score(params.pawn_mobility),
+            pawn_passed: scores(&params.pawn_passed),
+            pawn_passed_notblocked: scores(&params.pawn_passed_notblocked),
+            pawn_passed_kingdistance: scores(&params.pawn_passed_kingdistance),
+            pawn_passed_enemykingdistance: scores(&params.pawn_passed_enemykingdistance),
+            pawn_passed_subdistance: scores(&params.pawn_passed_subdistance),
+            rook_behind_support_passer: score(params.rook_behind_support_passer),
+            rook_behind_enemy_passer: score(params.rook_behind_enemy_passer),
+            pawn_passed_weak: score(params.pawn_passed_weak),
+            knight_supported: score(params.knight_supported),
+            knight_outpost_table: psqt(&params.knight_outpost_table),
+            bishop_xray_king: score(params.bishop_xray_king),
+            rook_xray_king: score(params.rook_xray_king),
+            queen_xray_king: score(params.queen_xray_king),
+            rook_on_open: score(params.rook_on_open),
+            rook_on_semi_open: score(params.rook_on_semi_open),
+            queen_on_open: score(params.queen_on_open),
+            queen_on_semi_open: score(params.queen_on_semi_open),
+            rook_on_seventh: score(params.rook_on_seventh),
+            pawn_piece_value: score(params.pawn_piece_value),
+            knight_piece_value: score(params.knight_piece_value),
+            knight_value_with_pawns: {
+                let mut res = [0.; 17];
+                for (to, from) in res.iter_mut().zip(params.knight_value_with_pawns.iter()) {
+                    *to = f64::from(*from);
+                }
+                res
             },
-            SafetyTable {
-                safety_table: [0.; 100],
+            bishop_piece_value: score(params.bishop_piece_value),
+            bishop_pair: score(params.bishop_pair),
+            rook_piece_value: score(params.rook_piece_value),
+            queen_piece_value: score(params.queen_piece_value),
+            diagonally_adjacent_squares_withpawns: scores(
+                &params.diagonally_adjacent_squares_withpawns,
+            ),
+            knight_mobility: scores(&params.knight_mobility),
+            bishop_mobility: scores(&params.bishop_mobility),
+            rook_mobility: scores(&params.rook_mobility),
+            queen_mobility: scores(&params.queen_mobility),
+            attack_weight: scores(&params.attack_weight),
+            safety_table: {
+                let [mg, eg] = scores(&params.safety_table);
+                [
+                    SafetyTable { safety_table: mg },
+                    SafetyTable { safety_table: eg },
+                ]
             },
-        ];
-        for i in 0..100 {
-            safety_table[MG].safety_table[i] = f64::from(SAFETY_TABLE[i].0);
-            safety_table[EG].safety_table[i] = f64::from(SAFETY_TABLE[i].1);
+            knight_attack_value: score(params.knight_attack_value),
+            bishop_attack_value: score(params.bishop_attack_value),
+            rook_attack_value: score(params.rook_attack_value),
+            queen_attack_value: score(params.queen_attack_value),
+            knight_check_value: score(params.knight_check_value),
+            bishop_check_value: score(params.bishop_check_value),
+            rook_check_value: score(params.rook_check_value),
+            queen_check_value: score(params.queen_check_value),
+            psqt_pawn: psqt(&params.psqt_pawn),
+            psqt_knight: psqt(&params.psqt_knight),
+            psqt_bishop: psqt(&params.psqt_bishop),
+            psqt_rook: psqt(&params.psqt_rook),
+            psqt_queen: psqt(&params.psqt_queen),
+            psqt_king: psqt(&params.psqt_king),
         }
+    }
 
-        let mut psqt_pawn: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                psqt_pawn[MG][i][j] = f64::from(PSQT_PAWN[i][j].0);
-                psqt_pawn[EG][i][j] = f64::from(PSQT_PAWN[i][j].1);
-            }
-        }
-        let mut psqt_knight: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                psqt_knight[MG][i][j] = f64::from(PSQT_KNIGHT[i][j].0);
-                psqt_knight[EG][i][j] = f64::from(PSQT_KNIGHT[i][j].1);
-            }
-        }
-        let mut psqt_bishop: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                psqt_bishop[MG][i][j] = f64::from(PSQT_BISHOP[i][j].0);
-                psqt_bishop[EG][i][j] = f64::from(PSQT_BISHOP[i][j].1);
-            }
-        }
-        let mut psqt_rook: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                psqt_rook[MG][i][j] = f64::from(PSQT_ROOK[i][j].0);
-                psqt_rook[EG][i][j] = f64::from(PSQT_ROOK[i][j].1);
-            }
-        }
-        let mut psqt_queen: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                psqt_queen[MG][i][j] = f64::from(PSQT_QUEEN[i][j].0);
-                psqt_queen[EG][i][j] = f64::from(PSQT_QUEEN[i][j].1);
-            }
-        }
-        let mut psqt_king: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                psqt_king[MG][i][j] = f64::from(PSQT_KING[i][j].0);
-                psqt_king[EG][i][j] = f64::from(PSQT_KING[i][j].1);
+    //Rounded to the integer table the engine evaluates with
+    pub fn to_eval_params(&self) -> EvalParams {
+        fn score(values: &[f64; 2]) -> EvaluationScore {
+            EvaluationScore(values[MG].round() as i16, values[EG].round() as i16)
+        }
+        fn scores<const N: usize>(mg: &[f64; N], eg: &[f64; N]) -> [EvaluationScore; N] {
+            let mut res = [EvaluationScore::default(); N];
+            for (i, to) in res.iter_mut().enumerate() {
+                *to = score(&[mg[i], eg[i]]);
             }
+            res
         }
-        let mut psqt_pawn_supported: [[[f64; 8]; 8]; 2] = [[[0.; 8]; 8]; 2];
-        for i in 0..8 {
-            for j in 0..8 {
-                psqt_pawn_supported[MG][i][j] = f64::from(PAWN_SUPPORTED_VALUE[i][j].0);
-                psqt_pawn_supported[EG][i][j] = f64::from(PAWN_SUPPORTED_VALUE[i][j].1);
+        fn psqt(values: &[[[f64; 8]; 8]; 2]) -> [[EvaluationScore; 8]; 8] {
+            let mut res = [[EvaluationScore::default(); 8]; 8];
+            for (i, to) in res.iter_mut().enumerate() {
+                *to = scores(&values[MG][i], &values[EG][i]);
             }
+            res
         }
-        Parameters {
-            tempo_bonus: [f64::from(TEMPO_BONUS.0), f64::from(TEMPO_BONUS.1)],
-            shielding_pawn_missing,
-            shielding_pawn_onopen_missing,
-            pawn_doubled: [
-                f64::from(PAWN_DOUBLED_VALUE.0),
-                f64::from(PAWN_DOUBLED_VALUE.1),
-            ],
-            pawn_isolated: [
-                f64::from(PAWN_ISOLATED_VALUE.0),
-                f64::from(PAWN_ISOLATED_VALUE.1),
-            ],
-            pawn_backward: [
-                f64::from(PAWN_BACKWARD_VALUE.0),
-                f64::from(PAWN_BACKWARD_VALUE.1),
-            ],
-            pawn_supported: psqt_pawn_supported,
-            pawn_attack_center: [
-                f64::from(PAWN_ATTACK_CENTER.0),
-                f64::from(PAWN_ATTACK_CENTER.1),
-            ],
-            pawn_mobility: [f64::from(PAWN_MOBILITY.0), f64::from(PAWN_MOBILITY.1)],
-            pawn_passed,
-            pawn_passed_notblocked,
-            pawn_passed_kingdistance,
-            pawn_passed_enemykingdistance,
-            pawn_passed_subdistance,
-            rook_behind_support_passer: [
-                f64::from(ROOK_BEHIND_SUPPORT_PASSER.0),
-                f64::from(ROOK_BEHIND_SUPPORT_PASSER.1),
-            ],
-            rook_behind_enemy_passer: [
-                f64::from(ROOK_BEHIND_ENEMY_PASSER.0),
-                f64::from(ROOK_BEHIND_ENEMY_PASSER.1),
-            ],
-            pawn_passed_weak: [f64::from(PAWN_PASSED_WEAK.0), f64::from(PAWN_PASSED_WEAK.1)],
-            knight_supported: [
-                f64::from(KNIGHT_SUPPORTED_BY_PAWN.0),
-                f64::from(KNIGHT_SUPPORTED_BY_PAWN.1),
-            ],
-            knight_outpost_table,
-            bishop_xray_king: [f64::from(BISHOP_XRAY_KING.0), f64::from(BISHOP_XRAY_KING.1)],
-            rook_xray_king: [f64::from(ROOK_XRAY_KING.0), f64::from(ROOK_XRAY_KING.1)],
-            queen_xray_king: [f64::from(QUEEN_XRAY_KING.0), f64::from(QUEEN_XRAY_KING.1)],
-            rook_on_open: [
-                f64::from(ROOK_ON_OPEN_FILE_BONUS.0),
-                f64::from(ROOK_ON_OPEN_FILE_BONUS.1),
-            ],
-            rook_on_semi_open: [
-                f64::from(ROOK_ON_SEMI_OPEN_FILE_BONUS.0),
-                f64::from(ROOK_ON_SEMI_OPEN_FILE_BONUS.1),
-            ],
-            queen_on_open: [
-                f64::from(QUEEN_ON_OPEN_FILE_BONUS.0),
-                f64::from(QUEEN_ON_OPEN_FILE_BONUS.1),
-            ],
-            queen_on_semi_open: [
-                f64::from(QUEEN_ON_SEMI_OPEN_FILE_BONUS.0),
-                f64::from(QUEEN_ON_SEMI_OPEN_FILE_BONUS.1),
-            ],
-            rook_on_seventh: [f64::from(ROOK_ON_SEVENTH.0), f64::from(ROOK_ON_SEVENTH.1)],
-            pawn_piece_value: [f64::from(PAWN_PIECE_VALUE.0), f64::from(PAWN_PIECE_VALUE.1)],
-            knight_piece_value: [
-                f64::from(KNIGHT_PIECE_VALUE.0),
-                f64::from(KNIGHT_PIECE_VALUE.1),
-            ],
-            knight_value_with_pawns,
-            bishop_piece_value: [
-                f64::from(BISHOP_PIECE_VALUE.0),
-                f64::from(BISHOP_PIECE_VALUE.1),
-            ],
-            bishop_pair: [
-                f64::from(BISHOP_PAIR_BONUS.0),
-                f64::from(BISHOP_PAIR_BONUS.1),
-            ],
-            rook_piece_value: [f64::from(ROOK_PIECE_VALUE.0), f64::from(ROOK_PIECE_VALUE.1)],
-            queen_piece_value: [
-                f64::from(QUEEN_PIECE_VALUE.0),
-                f64::from(QUEEN_PIECE_VALUE.1),
-            ],
-            knight_attack_value: [
-                f64::from(KNIGHT_ATTACK_WORTH.0),
-                f64::from(KNIGHT_ATTACK_WORTH.1),
-            ],
-            bishop_attack_value: [
-                f64::from(BISHOP_ATTACK_WORTH.0),
-                f64::from(BISHOP_ATTACK_WORTH.1),
-            ],
-            rook_attack_value: [
-                f64::from(ROOK_ATTACK_WORTH.0),
-                f64::from(ROOK_ATTACK_WORTH.1),
-            ],
-            queen_attack_value: [
-                f64::from(QUEEN_ATTACK_WORTH.0),
-                f64::from(QUEEN_ATTACK_WORTH.1),
-            ],
-            knight_check_value: [
-                f64::from(KNIGHT_SAFE_CHECK.0),
-                f64::from(KNIGHT_SAFE_CHECK.1),
-            ],
-            bishop_check_value: [
-                f64::from(BISHOP_SAFE_CHECK.0),
-                f64::from(BISHOP_SAFE_CHECK.1),
-            ],
-            rook_check_value: [f64::from(ROOK_SAFE_CHECK.0), f64::from(ROOK_SAFE_CHECK.1)],
-            queen_check_value: [f64::from(QUEEN_SAFE_CHECK.0), f64::from(QUEEN_SAFE_CHECK.1)],
-            diagonally_adjacent_squares_withpawns,
-            knight_mobility,
-            bishop_mobility,
-            rook_mobility,
-            queen_mobility,
-            attack_weight,
-            safety_table,
-            psqt_pawn,
-            psqt_knight,
-            psqt_bishop,
-            psqt_rook,
-            psqt_queen,
-            psqt_king,
+        EvalParams {
+            tempo_bonus: score(&self.tempo_bonus),
+            shielding_pawn_missing: scores(
+                &self.shielding_pawn_missing[MG],
+                &self.shielding_pawn_missing[EG],
+            ),
+            shielding_pawn_onopen_missing: scores(
+                &self.shielding_pawn_onopen_missing[MG],
+                &self.shielding_pawn_onopen_missing[EG],
+            ),
+            pawn_doubled: score(&self.pawn_doubled),
+            pawn_isolated: score(&self.pawn_isolated),
+            pawn_backward: score(&self.pawn_backward),
+            pawn_supported: psqt(&self.pawn_supported),
+            pawn_attack_center: score(&self.pawn_attack_center),
+            pawn_mobility: score(&self.pawn_mobility),
+            pawn_passed: scores(&self.pawn_passed[MG], &self.pawn_passed[EG]),
+            pawn_passed_notblocked: scores(
+                &self.pawn_passed_notblocked[MG],
+                &self.pawn_passed_notblocked[EG],
+            ),
+            pawn_passed_kingdistance: scores(
+                &self.pawn_passed_kingdistance[MG],
+                &self.pawn_passed_kingdistance[EG],
+            ),
+            pawn_passed_enemykingdistance: scores(
+                &self.pawn_passed_enemykingdistance[MG],
+                &self.pawn_passed_enemykingdistance[EG],
+            ),
+            pawn_passed_subdistance: scores(
+                &self.pawn_passed_subdistance[MG],
+                &self.pawn_passed_subdistance[EG],
+            ),
+            rook_behind_support_passer: score(&self.rook_behind_support_passer),
+            rook_behind_enemy_passer: score(&self.rook_behind_enemy_passer),
+            pawn_passed_weak: score(&self.pawn_passed_weak),
+            knight_supported: score(&self.knight_supported),
+            knight_outpost_table: psqt(&self.knight_outpost_table),
+            bishop_xray_king: score(&self.bishop_xray_king),
+            rook_xray_king: score(&self.rook_xray_king),
+            queen_xray_king: score(&self.queen_xray_king),
+            rook_on_open: score(&self.rook_on_open),
+            rook_on_semi_open: score(&self.rook_on_semi_open),
+            queen_on_open: score(&self.queen_on_open),
+            queen_on_semi_open: score(&self.queen_on_semi_open),
+            rook_on_seventh: score(&self.rook_on_seventh),
+            pawn_piece_value: score(&self.pawn_piece_value),
+            knight_piece_value: score(&self.knight_piece_value),
+            knight_value_with_pawns: {
+                let mut res = [0; 17];
+                for (to, from) in res.iter_mut().zip(self.knight_value_with_pawns.iter()) {
+                    *to = from.round() as i16;
+                }
+                res
+            },
+            bishop_piece_value: score(&self.bishop_piece_value),
+            bishop_pair: score(&self.bishop_pair),
+            rook_piece_value: score(&self.rook_piece_value),
+            queen_piece_value: score(&self.queen_piece_value),
+            diagonally_adjacent_squares_withpawns: scores(
+                &self.diagonally_adjacent_squares_withpawns[MG],
+                &self.diagonally_adjacent_squares_withpawns[EG],
+            ),
+            knight_mobility: scores(&self.knight_mobility[MG], &self.knight_mobility[EG]),
+            bishop_mobility: scores(&self.bishop_mobility[MG], &self.bishop_mobility[EG]),
+            rook_mobility: scores(&self.rook_mobility[MG], &self.rook_mobility[EG]),
+            queen_mobility: scores(&self.queen_mobility[MG], &self.queen_mobility[EG]),
+            attack_weight: scores(&self.attack_weight[MG], &self.attack_weight[EG]),
+            safety_table: scores(
+                &self.safety_table[MG].safety_table,
+                &self.safety_table[EG].safety_table,
+            ),
+            knight_attack_value: score(&self.knight_attack_value),
+            bishop_attack_value: score(&self.bishop_attack_value),
+            rook_attack_value: score(&self.rook_attack_value),
+            queen_attack_value: score(&self.queen_attack_value),
+            knight_check_value: score(&self.knight_check_value),
+            bishop_check_value: score(&self.bishop_check_value),
+            rook_check_value: score(&self.rook_check_value),
+            queen_check_value: score(&self.queen_check_value),
+            psqt_pawn: psqt(&self.psqt_pawn),
+            psqt_knight: psqt(&self.psqt_knight),
+            psqt_bishop: psqt(&self.psqt_bishop),
+            psqt_rook: psqt(&self.psqt_rook),
+            psqt_queen: psqt(&self.psqt_queen),
+            psqt_king: psqt(&self.psqt_king),
         }
     }
 
+    pub fn write_eval_params(&self, file: &str) {
+        self.to_eval_params()
+            .write(file)
+            .expect("Unable to write file");
+    }
+
     pub fn zero() -> Self {
         Parameters {
             tempo_bonus: [0.; 2],
@@ -0,0 +1,56 @@
+use crate::evaluation::EvaluationScore;
+
+pub const PAWN_HASH_ENTRIES: usize = 1 << 14;
+
+//What the evaluation of a pawn structure leaves for the terms which depend on other pieces as well
+#[derive(Copy, Clone, Default)]
+pub struct PawnHashEntry {
+    pub key: u64,
+    //Pawn structure score, white's minus black's
+    pub score: EvaluationScore,
+    //Passed pawns by side
+    pub passers: [u64; 2],
+}
+
+//Every search thread has its own table, so no synchronization is needed.
+//An empty slot has key 0, which only positions without pawns share and their entry is empty too
+pub struct PawnHashTable {
+    entries: Vec<PawnHashEntry>,
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        PawnHashTable {
+            entries: vec![PawnHashEntry::default(); PAWN_HASH_ENTRIES],
+        }
+    }
+}
+
+impl PawnHashTable {
+    #[inline(always)]
+    fn index(key: u64) -> usize {
+        key as usize & (PAWN_HASH_ENTRIES - 1)
+    }
+
+    #[inline(always)]
+    pub fn probe(&self, key: u64) -> Option<PawnHashEntry> {
+        let entry = self.entries[PawnHashTable::index(key)];
+        if entry.key == key {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    //Always replaces, the newest pawn structures are the ones most likely to come up again
+    #[inline(always)]
+    pub fn store(&mut self, entry: PawnHashEntry) {
+        self.entries[PawnHashTable::index(entry.key)] = entry;
+    }
+
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = PawnHashEntry::default();
+        }
+    }
+}
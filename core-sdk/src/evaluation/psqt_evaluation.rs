@@ -1,8 +1,8 @@
-use super::params::*;
+use super::eval_params::EvalParams;
 use super::EvaluationResult;
 use super::EvaluationScore;
 use crate::board_representation::game_state::{
-    PieceType, BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE,
+    PieceType, Square, BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE,
 };
 #[cfg(feature = "display-eval")]
 use crate::logging::log;
@@ -13,7 +13,12 @@ pub const BLACK_INDEX: [usize; 64] = [
     8, 9, 10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 6, 7,
 ];
 
-pub fn psqt(white: bool, pieces: &[[u64; 2]; 6], _eval: &mut EvaluationResult) -> EvaluationScore {
+pub fn psqt(
+    white: bool,
+    pieces: &[[u64; 2]; 6],
+    params: &EvalParams,
+    _eval: &mut EvaluationResult,
+) -> EvaluationScore {
     let mut pawn = EvaluationScore::default();
     let mut knight = EvaluationScore::default();
     let mut bishop = EvaluationScore::default();
@@ -30,10 +35,9 @@ pub fn psqt(white: bool, pieces: &[[u64; 2]; 6], _eval: &mut EvaluationResult) -
         if !white {
             idx = BLACK_INDEX[idx];
         }
-        pawn += PSQT_PAWN[idx / 8][idx % 8];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.psqt_pawn[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
+        pawn += params.psqt_pawn[idx / 8][idx % 8];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.psqt_pawn[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
         }
     }
 
@@ -44,10 +48,9 @@ pub fn psqt(white: bool, pieces: &[[u64; 2]; 6], _eval: &mut EvaluationResult) -
         if !white {
             idx = BLACK_INDEX[idx]
         }
-        knight += PSQT_KNIGHT[idx / 8][idx % 8];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.psqt_knight[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
+        knight += params.psqt_knight[idx / 8][idx % 8];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.psqt_knight[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
         }
     }
 
@@ -58,10 +61,9 @@ pub fn psqt(white: bool, pieces: &[[u64; 2]; 6], _eval: &mut EvaluationResult) -
         if !white {
             idx = BLACK_INDEX[idx];
         }
-        bishop += PSQT_BISHOP[idx / 8][idx % 8];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.psqt_bishop[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
+        bishop += params.psqt_bishop[idx / 8][idx % 8];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.psqt_bishop[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
         }
     }
 
@@ -72,10 +74,9 @@ pub fn psqt(white: bool, pieces: &[[u64; 2]; 6], _eval: &mut EvaluationResult) -
         if !white {
             idx = BLACK_INDEX[idx];
         }
-        rook += PSQT_ROOK[idx / 8][idx % 8];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.psqt_rook[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
+        rook += params.psqt_rook[idx / 8][idx % 8];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.psqt_rook[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
         }
     }
 
@@ -86,20 +87,18 @@ pub fn psqt(white: bool, pieces: &[[u64; 2]; 6], _eval: &mut EvaluationResult) -
         if !white {
             idx = BLACK_INDEX[idx];
         }
-        queen += PSQT_QUEEN[idx / 8][idx % 8];
-        #[cfg(feature = "texel-tuning")]
-        {
-            _eval.trace.psqt_queen[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
+        queen += params.psqt_queen[idx / 8][idx % 8];
+        if let Some(trace) = _eval.trace.as_mut() {
+            trace.psqt_queen[idx / 8][idx % 8] += if side == WHITE { 1 } else { -1 };
         }
     }
     let mut king_idx = pieces[KING][side].trailing_zeros() as usize;
     if !white {
         king_idx = BLACK_INDEX[king_idx];
     }
-    king = PSQT_KING[king_idx / 8][king_idx % 8];
-    #[cfg(feature = "texel-tuning")]
-    {
-        _eval.trace.psqt_king[king_idx / 8][king_idx % 8] += if side == WHITE { 1 } else { -1 };
+    king = params.psqt_king[king_idx / 8][king_idx % 8];
+    if let Some(trace) = _eval.trace.as_mut() {
+        trace.psqt_king[king_idx / 8][king_idx % 8] += if side == WHITE { 1 } else { -1 };
     }
     #[allow(clippy::let_and_return)]
     let sum = pawn + knight + bishop + rook + queen + king;
@@ -124,18 +123,15 @@ pub fn psqt(white: bool, pieces: &[[u64; 2]; 6], _eval: &mut EvaluationResult) -
 pub fn psqt_toggle_piece(
     pieces: &mut [[u64; 2]; 6],
     piece: PieceType,
-    square: usize,
+    square: Square,
     side: usize,
     score: &mut EvaluationScore,
 ) {
     let temp = pieces[piece.to_index()][side];
-    let (rank, file) = if side == WHITE {
-        (square / 8, square % 8)
-    } else {
-        (BLACK_INDEX[square] / 8, BLACK_INDEX[square] % 8)
-    };
-    let mut new_score = piece.to_psqt()[rank][file];
-    if (temp & 1u64 << square) == 0u64 {
+    let relative_square = if side == WHITE { square } else { square.flip() };
+    let mut new_score =
+        piece.to_psqt()[relative_square.rank().index()][relative_square.file().index()];
+    if (temp & 1u64 << square.index()) == 0u64 {
         new_score *= -1;
     }
     new_score *= if side == WHITE { 1 } else { -1 };
@@ -1,7 +1,19 @@
-use crate::evaluation::parameters::Parameters;
 use crate::board_representation::game_state::{BLACK, WHITE};
+use crate::evaluation::parameters::Parameters;
 use crate::evaluation::{EG, MG};
 
+//Groups of parameters, in the order Trace::terms scores them
+pub const TERMS: [&str; 8] = [
+    "Material",
+    "PSQT",
+    "Pawns",
+    "Passed pawns",
+    "Pieces",
+    "Mobility",
+    "King safety",
+    "Tempo",
+];
+
 pub struct Trace {
     pub tempo_bonus: i8,
     pub shielding_pawn_missing: [i8; 4],
@@ -84,7 +96,8 @@ pub fn evaluate_single2(score: &mut (f64, f64), trace: i8, param_mg: f64, param_
 }
 
 impl Trace {
-    pub fn evaluate(&self, params: &Parameters) -> f64 {
+    //Midgame and endgame score of every group in TERMS
+    pub fn terms(&self, params: &Parameters) -> [(f64, f64); 8] {
         //PSQT Evaluation
         let mut psqt_res = (0., 0.);
         evaluate_psqt(&mut psqt_res, &self.psqt_pawn, &params.psqt_pawn);
@@ -94,24 +107,24 @@ impl Trace {
         evaluate_psqt(&mut psqt_res, &self.psqt_queen, &params.psqt_queen);
         evaluate_psqt(&mut psqt_res, &self.psqt_king, &params.psqt_king);
 
-        //Knight evaluation
-        let mut knight_res = (0., 0.);
+        //Pieces
+        let mut pieces_res = (0., 0.);
         evaluate_single(
-            &mut knight_res,
+            &mut pieces_res,
             self.knight_supported,
             &params.knight_supported,
         );
         evaluate_psqt(
-            &mut knight_res,
+            &mut pieces_res,
             &self.knight_outpost_table,
             &params.knight_outpost_table,
         );
 
-        //Piecewise
-        let mut piecewise_res = (0., 0.);
+        //Mobility
+        let mut mobility_res = (0., 0.);
         for i in 0..9 {
             evaluate_single2(
-                &mut piecewise_res,
+                &mut mobility_res,
                 self.knight_mobility[i],
                 params.knight_mobility[MG][i],
                 params.knight_mobility[EG][i],
@@ -119,7 +132,7 @@ impl Trace {
         }
         for i in 0..14 {
             evaluate_single2(
-                &mut piecewise_res,
+                &mut mobility_res,
                 self.bishop_mobility[i],
                 params.bishop_mobility[MG][i],
                 params.bishop_mobility[EG][i],
@@ -127,7 +140,7 @@ impl Trace {
         }
         for i in 0..5 {
             evaluate_single2(
-                &mut piecewise_res,
+                &mut pieces_res,
                 self.diagonally_adjacent_squares_withpawns[i],
                 params.diagonally_adjacent_squares_withpawns[MG][i],
                 params.diagonally_adjacent_squares_withpawns[EG][i],
@@ -135,7 +148,7 @@ impl Trace {
         }
         for i in 0..15 {
             evaluate_single2(
-                &mut piecewise_res,
+                &mut mobility_res,
                 self.rook_mobility[i],
                 params.rook_mobility[MG][i],
                 params.rook_mobility[EG][i],
@@ -143,49 +156,43 @@ impl Trace {
         }
         for i in 0..28 {
             evaluate_single2(
-                &mut piecewise_res,
+                &mut mobility_res,
                 self.queen_mobility[i],
                 params.queen_mobility[MG][i],
                 params.queen_mobility[EG][i],
             );
         }
         evaluate_single(
-            &mut piecewise_res,
+            &mut pieces_res,
             self.bishop_xray_king,
             &params.bishop_xray_king,
         );
+        evaluate_single(&mut pieces_res, self.rook_xray_king, &params.rook_xray_king);
         evaluate_single(
-            &mut piecewise_res,
-            self.rook_xray_king,
-            &params.rook_xray_king,
-        );
-        evaluate_single(
-            &mut piecewise_res,
+            &mut pieces_res,
             self.queen_xray_king,
             &params.queen_xray_king,
         );
-        evaluate_single(&mut piecewise_res, self.rook_on_open, &params.rook_on_open);
+        evaluate_single(&mut pieces_res, self.rook_on_open, &params.rook_on_open);
         evaluate_single(
-            &mut piecewise_res,
+            &mut pieces_res,
             self.rook_on_semi_open,
             &params.rook_on_semi_open,
         );
+        evaluate_single(&mut pieces_res, self.queen_on_open, &params.queen_on_open);
         evaluate_single(
-            &mut piecewise_res,
-            self.queen_on_open,
-            &params.queen_on_open,
-        );
-        evaluate_single(
-            &mut piecewise_res,
+            &mut pieces_res,
             self.queen_on_semi_open,
             &params.queen_on_semi_open,
         );
         evaluate_single(
-            &mut piecewise_res,
+            &mut pieces_res,
             self.rook_on_seventh,
             &params.rook_on_seventh,
         );
-        piecewise_res.0 += (params.attack_weight[MG][self.attackers[WHITE] as usize]
+        //King safety
+        let mut king_res = (0., 0.);
+        king_res.0 += (params.attack_weight[MG][self.attackers[WHITE] as usize]
             * params.safety_table[MG].safety_table[((f64::from(self.knight_attacked_sq[WHITE])
                 * params.knight_attack_value[MG]
                 + f64::from(self.bishop_attacked_sq[WHITE]) * params.bishop_attack_value[MG]
@@ -213,7 +220,7 @@ impl Trace {
                         .max(0)
                         .min(99)])
             / 100.0;
-        piecewise_res.1 += (params.attack_weight[EG][self.attackers[WHITE] as usize]
+        king_res.1 += (params.attack_weight[EG][self.attackers[WHITE] as usize]
             * params.safety_table[EG].safety_table[((f64::from(self.knight_attacked_sq[WHITE])
                 * params.knight_attack_value[EG]
                 + f64::from(self.bishop_attacked_sq[WHITE]) * params.bishop_attack_value[EG]
@@ -242,8 +249,6 @@ impl Trace {
                         .min(99)])
             / 100.0;
 
-        //King-Safety
-        let mut king_res = (0., 0.);
         for i in 0..4 {
             evaluate_single2(
                 &mut king_res,
@@ -271,27 +276,30 @@ impl Trace {
             &params.pawn_attack_center,
         );
         evaluate_single(&mut pawn_res, self.pawn_mobility, &params.pawn_mobility);
+
+        //Passed pawns
+        let mut passer_res = (0., 0.);
         for i in 0..7 {
             evaluate_single2(
-                &mut pawn_res,
+                &mut passer_res,
                 self.pawn_passed[i],
                 params.pawn_passed[MG][i],
                 params.pawn_passed[EG][i],
             );
             evaluate_single2(
-                &mut pawn_res,
+                &mut passer_res,
                 self.pawn_passed_notblocked[i],
                 params.pawn_passed_notblocked[MG][i],
                 params.pawn_passed_notblocked[EG][i],
             );
             evaluate_single2(
-                &mut pawn_res,
+                &mut passer_res,
                 self.pawn_passed_kingdistance[i],
                 params.pawn_passed_kingdistance[MG][i],
                 params.pawn_passed_kingdistance[EG][i],
             );
             evaluate_single2(
-                &mut pawn_res,
+                &mut passer_res,
                 self.pawn_passed_enemykingdistance[i],
                 params.pawn_passed_enemykingdistance[MG][i],
                 params.pawn_passed_enemykingdistance[EG][i],
@@ -299,24 +307,24 @@ impl Trace {
         }
         for i in 0..13 {
             evaluate_single2(
-                &mut pawn_res,
+                &mut passer_res,
                 self.pawn_passed_subdistance[i],
                 params.pawn_passed_subdistance[MG][i],
                 params.pawn_passed_subdistance[EG][i],
             );
         }
         evaluate_single(
-            &mut pawn_res,
+            &mut passer_res,
             self.rook_behind_support_passer,
             &params.rook_behind_support_passer,
         );
         evaluate_single(
-            &mut pawn_res,
+            &mut passer_res,
             self.rook_behind_enemy_passer,
             &params.rook_behind_enemy_passer,
         );
         evaluate_single(
-            &mut pawn_res,
+            &mut passer_res,
             self.pawn_passed_weak,
             &params.pawn_passed_weak,
         );
@@ -346,31 +354,28 @@ impl Trace {
         let mut tempo_bonus = (0., 0.);
         evaluate_single(&mut tempo_bonus, self.tempo_bonus, &params.tempo_bonus);
 
-        let res = (
-            psqt_res.0
-                + knight_res.0
-                + piecewise_res.0
-                + king_res.0
-                + pawn_res.0
-                + piecevalue_res.0
-                + tempo_bonus.0,
-            psqt_res.1
-                + knight_res.1
-                + piecewise_res.1
-                + king_res.1
-                + pawn_res.1
-                + piecevalue_res.1
-                + tempo_bonus.1,
-        );
+        [
+            piecevalue_res,
+            psqt_res,
+            pawn_res,
+            passer_res,
+            pieces_res,
+            mobility_res,
+            king_res,
+            tempo_bonus,
+        ]
+    }
+
+    pub fn evaluate(&self, params: &Parameters) -> f64 {
+        let terms = self.terms(params);
+        let res = terms
+            .iter()
+            .fold((0., 0.), |sum, term| (sum.0 + term.0, sum.1 + term.1));
         #[cfg(feature = "display-eval")]
         {
-            println!("PSQT: {},{}", psqt_res.0, psqt_res.1);
-            println!("KNIGHT:{},{}", knight_res.0, knight_res.1);
-            println!("PIECEWISE:{},{}", piecewise_res.0, piecewise_res.1);
-            println!("KING:{},{}", king_res.0, king_res.1);
-            println!("PAWNS:{},{}", pawn_res.0, pawn_res.1);
-            println!("PieceValue:{},{}", piecevalue_res.0, piecevalue_res.1);
-            println!("Tempo:{},{}", tempo_bonus.0, tempo_bonus.1);
+            for (name, term) in TERMS.iter().zip(terms.iter()) {
+                println!("{}: {},{}", name, term.0, term.1);
+            }
             println!("Res:{},{}", res.0, res.1);
         }
         (res.0 * self.phase + res.1 / 1.5 * (128.0 - self.phase)) / 128.0
@@ -439,22 +444,13 @@ impl Trace {
 
 #[cfg(test)]
 mod tests {
-    #[cfg(feature = "texel-tuning")]
     use super::super::parameters::Parameters;
-    #[cfg(feature = "texel-tuning")]
     use crate::board_representation::game_state::GameState;
-    #[cfg(feature = "texel-tuning")]
-    use crate::evaluation::eval_game_state_from_null;
+    use crate::evaluation::trace_game_state;
 
     #[test]
-    #[ignore]
     pub fn traceeval() {
-        if !cfg!(feature = "texel-tuning") {
-            panic!("Feature texel-tuning has to be enabled");
-        }
-        #[cfg(feature = "texel-tuning")]
-        {
-            let positions: &str = "3r1r1k/pb2b3/1p1q3p/1Pnp1pp1/P7/1QN1PN2/5PPP/1R1R1BK1 w - - 0 21
+        let positions: &str = "3r1r1k/pb2b3/1p1q3p/1Pnp1pp1/P7/1QN1PN2/5PPP/1R1R1BK1 w - - 0 21
 r1q1kr2/1bp1n1np/p7/1p3pp1/3N4/NP2R1P1/3PP1BP/R4QK1 w q - 2 21
 4r1k1/1pqnnp1p/p3b1p1/P3p3/8/1NPB4/2P3PP/R3QR1K w - - 0 21
 r1q1rbk1/pp1n2pp/2p1np2/5N1b/N3PP2/6PP/PPQB2B1/4RRK1 w - - 0 21
@@ -549,17 +545,16 @@ r3k2r/1pqb2p1/p4p2/P2npP2/2pB2Bp/2P4P/2P1Q1P1/R4RK1 w kq - 0 21
 2rr2k1/1b3ppp/p3p3/1p6/1P1B1Pnq/P2BP3/1Q4PP/3R1RK1 w - - 7 21
 2rqk2r/1p1n1p2/p3p1p1/P2pP2p/1P1NbP2/2P1Q3/4B1PP/R2R2K1 w k - 1 21";
 
-            let params = Parameters::default();
-            let new_linesplit = positions.split("\n").collect::<Vec<&str>>();
-            for line in new_linesplit {
-                let position = GameState::from_fen(line);
-                let evaluation = eval_game_state_from_null(&position);
-                let trace_eval = evaluation.trace.evaluate(&params) as i16;
-                //Rounding erros can make up for max 2 error (only 2 place where rounding can make a difference )
-                if (evaluation.final_eval - trace_eval).abs() > 2 {
-                    println!("{}", position.to_fen());
-                    panic!(format!("{} != {}", evaluation.final_eval, trace_eval));
-                }
+        let params = Parameters::default();
+        let new_linesplit = positions.split("\n").collect::<Vec<&str>>();
+        for line in new_linesplit {
+            let position = GameState::from_fen(line);
+            let evaluation = trace_game_state(&position);
+            let trace_eval = evaluation.trace.unwrap().evaluate(&params) as i16;
+            //Rounding erros can make up for max 2 error (only 2 place where rounding can make a difference )
+            if (evaluation.final_eval - trace_eval).abs() > 2 {
+                println!("{}", position.to_fen());
+                panic!("{} != {}", evaluation.final_eval, trace_eval);
             }
         }
     }
@@ -4,18 +4,22 @@ extern crate rand;
 
 pub mod bitboards;
 pub mod board_representation;
+pub mod book;
 pub mod evaluation;
 pub mod move_generation;
 pub mod search;
 
 use crate::board_representation::game_state::GameState;
-use crate::move_generation::makemove::make_move;
+use crate::move_generation::makemove::{make_move, make_move_mut, unmake_move};
 use crate::move_generation::movegen;
+use crate::search::alphabeta::{DEFAULT_SINGULAR_DEPTH, DEFAULT_SINGULAR_MARGIN};
 use crate::search::cache::DEFAULT_HASH_SIZE;
 use crate::search::reserved_memory::{ReservedAttackContainer, ReservedMoveList};
 use crate::search::searcher::{
-    InterThreadCommunicationSystem, DEFAULT_SKIP_RATIO, DEFAULT_THREADS,
+    InterThreadCommunicationSystem, DEFAULT_CONTEMPT, DEFAULT_MULTI_PV, DEFAULT_SKIP_RATIO,
+    DEFAULT_THREADS,
 };
+use crate::search::skill::{DEFAULT_ELO, DEFAULT_SKILL_LEVEL};
 use crate::search::timecontrol::DEFAULT_MOVE_OVERHEAD;
 use std::sync::Arc;
 #[cfg(not(target_arch = "wasm32"))]
@@ -30,6 +34,17 @@ pub struct UCIOptions {
     pub move_overhead: u64,
     pub debug_print: bool,
     pub skip_ratio: usize,
+    pub chess960: bool,
+    pub multi_pv: usize,
+    pub ponder: bool,
+    pub limit_strength: bool,
+    pub elo: u32, //See Skill::from_elo
+    pub skill_level: usize,
+    pub contempt: i16,
+    pub analyse_mode: bool,
+    pub singular_extensions: bool,
+    pub singular_depth: i16,
+    pub singular_margin: i16,
 }
 impl Default for UCIOptions {
     fn default() -> Self {
@@ -39,6 +54,17 @@ impl Default for UCIOptions {
             move_overhead: DEFAULT_MOVE_OVERHEAD,
             debug_print: false,
             skip_ratio: DEFAULT_SKIP_RATIO,
+            chess960: false,
+            multi_pv: DEFAULT_MULTI_PV,
+            ponder: false,
+            limit_strength: false,
+            elo: DEFAULT_ELO,
+            skill_level: DEFAULT_SKILL_LEVEL,
+            contempt: DEFAULT_CONTEMPT,
+            analyse_mode: false,
+            singular_extensions: false,
+            singular_depth: DEFAULT_SINGULAR_DEPTH,
+            singular_margin: DEFAULT_SINGULAR_MARGIN,
         }
     }
 }
@@ -109,6 +135,44 @@ pub fn perft(
         res
     }
 }
+
+//Same as perft, but plays the moves with make_move_mut/unmake_move instead of copy-make
+pub fn perft_mut(
+    g: &mut GameState,
+    depth: usize,
+    movelist: &mut ReservedMoveList,
+    attack_container: &mut ReservedAttackContainer,
+) -> u64 {
+    attack_container.attack_containers[depth].write_state(g);
+    if depth == 1 {
+        let _ = movegen::generate_moves(
+            g,
+            false,
+            &mut movelist.move_lists[depth],
+            &attack_container.attack_containers[depth],
+        );
+        movelist.move_lists[depth].move_list.len() as u64
+    } else {
+        if depth == 0 {
+            return 1;
+        }
+        let mut res = 0;
+        let _ = movegen::generate_moves(
+            g,
+            false,
+            &mut movelist.move_lists[depth],
+            &attack_container.attack_containers[depth],
+        );
+        let len = movelist.move_lists[depth].move_list.len();
+        for i in 0..len {
+            let mv = movelist.move_lists[depth].move_list[i].0;
+            let undo = make_move_mut(g, mv);
+            res += perft_mut(g, depth - 1, movelist, attack_container);
+            unmake_move(g, mv, undo);
+        }
+        res
+    }
+}
 const BENCHMARKING_POSITIONS: [&str; 50] = [
     "r3k2r/2pb1ppp/2pp1q2/p7/1nP1B3/1P2P3/P2N1PPP/R2QK2R w KQkq a6 0 14",
     "4rrk1/2p1b1p1/p1p3q1/4p3/2P2n1p/1P1NR2P/PB3PP1/3R1QK1 b - - 2 24",
@@ -175,6 +239,8 @@ pub fn bench(depth: usize) {
             state,
             Vec::new(),
             search::timecontrol::TimeControl::Infinite,
+            search::timecontrol::SearchLimits::default(),
+            search::searcher::CancellationToken::default(),
         );
         nodes += itcs.get_nodes_sum();
         itcs.cache().clear_threaded(1);
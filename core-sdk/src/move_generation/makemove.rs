@@ -1,29 +1,39 @@
 use crate::board_representation::game_state::{
-    GameMove, GameMoveType, GameState, PieceType, WHITE,
+    Bitboard, Color, File, GameMove, GameMoveType, GameState, PieceType, Square, BLACK, WHITE,
 };
 use crate::board_representation::zobrist_hashing::ZOBRIST_KEYS;
+#[cfg(feature = "nnue")]
+use crate::evaluation::nnue::{self, Accumulator, Network};
+use crate::evaluation::phase::Phase;
 use crate::evaluation::psqt_evaluation::psqt_toggle_piece;
+use crate::evaluation::EvaluationScore;
 
 #[inline(always)]
-pub fn toggle_piece(pieces: &mut [[u64; 2]; 6], piece: PieceType, square: u8, color: usize) {
-    pieces[piece.to_index()][color] ^= 1u64 << square;
+pub fn toggle_piece(pieces: &mut [[u64; 2]; 6], piece: PieceType, square: Square, color: usize) {
+    pieces[piece.to_index()][color] ^= 1u64 << square.index();
 }
 
 #[inline(always)]
-pub fn toggle_hash(piece: PieceType, square: u8, color: usize, hash: &mut u64) {
+pub fn toggle_hash(piece: PieceType, square: Square, color: usize, hash: &mut u64) {
     *hash ^= if color == WHITE {
         piece.to_zobrist_key().0
     } else {
         piece.to_zobrist_key().1
-    }[square as usize];
+    }[square.index()];
 }
 #[inline(always)]
-pub fn enpassant_hash(old: u64, new: u64, hash: &mut u64) {
-    if old != 0u64 {
-        *hash ^= ZOBRIST_KEYS.en_passant[old.trailing_zeros() as usize % 8];
+pub fn toggle_pawn_hash(piece: PieceType, square: Square, color: usize, pawn_hash: &mut u64) {
+    if piece == PieceType::Pawn {
+        toggle_hash(piece, square, color, pawn_hash);
     }
-    if new != 0u64 {
-        *hash ^= ZOBRIST_KEYS.en_passant[new.trailing_zeros() as usize % 8];
+}
+#[inline(always)]
+pub fn enpassant_hash(old: Bitboard, new: Bitboard, hash: &mut u64) {
+    if let Some(square) = old.lsb() {
+        *hash ^= ZOBRIST_KEYS.en_passant[square.file().index()];
+    }
+    if let Some(square) = new.lsb() {
+        *hash ^= ZOBRIST_KEYS.en_passant[square.file().index()];
     }
 }
 #[inline(always)]
@@ -52,7 +62,7 @@ pub fn castle_hash(
 pub fn make_nullmove(g: &GameState) -> GameState {
     let color_to_move = 1 - g.color_to_move;
     let pieces = g.pieces;
-    let en_passant = 0u64;
+    let en_passant = Bitboard::EMPTY;
     let half_moves = g.half_moves + 1;
     let full_moves = g.full_moves + g.color_to_move;
     let mut hash = g.hash ^ ZOBRIST_KEYS.side_to_move;
@@ -60,141 +70,281 @@ pub fn make_nullmove(g: &GameState) -> GameState {
     GameState {
         color_to_move,
         pieces,
+        mailbox: g.mailbox,
         castle_white_kingside: g.castle_white_kingside,
         castle_white_queenside: g.castle_white_queenside,
         castle_black_kingside: g.castle_black_kingside,
         castle_black_queenside: g.castle_black_queenside,
+        castle_rook_files: g.castle_rook_files,
+        chess960: g.chess960,
         en_passant,
         half_moves,
         full_moves,
         hash,
+        pawn_hash: g.pawn_hash,
         psqt: g.psqt,
         phase: g.phase.clone(),
+        #[cfg(feature = "nnue")]
+        nnue: g.nnue,
     }
 }
 
+//Returns (king_to, rook_from, rook_to) of a castling move.
+//The move's to-square is either the king's target (standard) or the castling rook (Chess960),
+//both lie on the same side of the king
 #[inline(always)]
-pub fn rook_castling(to: u8) -> (u8, u8) {
-    if to == 58 {
-        (56, 59)
-    } else if to == 2 {
-        (0, 3)
-    } else if to == 62 {
-        (63, 61)
-    } else if to == 6 {
-        (7, 5)
+pub fn castling_squares(g: &GameState, side: usize, mv: GameMove) -> (Square, Square, Square) {
+    let kingside = mv.to > mv.from;
+    let back_rank = mv.from.rank();
+    let rook_from = g.castle_rook_square(side, kingside);
+    if kingside {
+        (
+            Square::new(File::G, back_rank),
+            rook_from,
+            Square::new(File::F, back_rank),
+        )
     } else {
-        panic!("Invalid castling move!")
+        (
+            Square::new(File::C, back_rank),
+            rook_from,
+            Square::new(File::D, back_rank),
+        )
     }
 }
 
+//Everything unmake_move needs to restore the position before make_move_mut
+pub struct MoveUndo {
+    pub captured_piece: Option<PieceType>,
+    pub castle_white_kingside: bool,
+    pub castle_white_queenside: bool,
+    pub castle_black_kingside: bool,
+    pub castle_black_queenside: bool,
+    pub en_passant: Bitboard,
+    pub half_moves: usize,
+    pub hash: u64,
+    pub pawn_hash: u64,
+    pub psqt: EvaluationScore,
+    pub phase: Phase,
+    #[cfg(feature = "nnue")]
+    pub nnue: Accumulator,
+}
+
+#[inline(always)]
+fn captured_piece(mv: GameMove) -> Option<PieceType> {
+    if mv.is_capture() {
+        Some(mv.get_captured_piece())
+    } else {
+        None
+    }
+}
+
+//The square of the captured piece, which differs from the to-square for en passant
+#[inline(always)]
+fn capture_square(side: usize, mv: GameMove) -> Square {
+    if let GameMoveType::EnPassant = mv.move_type {
+        let behind = if side == WHITE { -1 } else { 1 };
+        mv.to
+            .offset(0, behind)
+            .expect("En passant target is never on the back rank")
+    } else {
+        mv.to
+    }
+}
+
+//Copy-make: returns the position after mv and leaves g untouched
 pub fn make_move(g: &GameState, mv: GameMove) -> GameState {
-    //Step 1. Update immediate fields
-    let color_to_move = 1 - g.color_to_move;
-    let full_moves = g.full_moves + g.color_to_move;
-    //Step 2. Update pieces, hash and other incremental fields
-    let mut pieces = g.pieces;
+    let mut new_state = g.clone();
+    apply_move(&mut new_state, mv, captured_piece(mv));
+    new_state
+}
+
+//Make/unmake: plays mv on g in place, the returned record is needed to take it back again
+pub fn make_move_mut(g: &mut GameState, mv: GameMove) -> MoveUndo {
+    let captured_piece = captured_piece(mv);
+    let undo = MoveUndo {
+        captured_piece,
+        castle_white_kingside: g.castle_white_kingside,
+        castle_white_queenside: g.castle_white_queenside,
+        castle_black_kingside: g.castle_black_kingside,
+        castle_black_queenside: g.castle_black_queenside,
+        en_passant: g.en_passant,
+        half_moves: g.half_moves,
+        hash: g.hash,
+        pawn_hash: g.pawn_hash,
+        psqt: g.psqt,
+        phase: g.phase.clone(),
+        #[cfg(feature = "nnue")]
+        nnue: g.nnue,
+    };
+    apply_move(g, mv, captured_piece);
+    undo
+}
+
+pub fn unmake_move(g: &mut GameState, mv: GameMove, undo: MoveUndo) {
+    g.color_to_move = 1 - g.color_to_move;
+    g.full_moves -= g.color_to_move;
+    let side = g.color_to_move;
+    //Pieces are toggled back, every incremental field is restored from the undo record
+    toggle_piece(&mut g.pieces, mv.piece_type, mv.from, side);
+    if let GameMoveType::Castle = mv.move_type {
+        let (king_to, rook_from, rook_to) = castling_squares(g, side, mv);
+        toggle_piece(&mut g.pieces, mv.piece_type, king_to, side);
+        toggle_piece(&mut g.pieces, PieceType::Rook, rook_from, side);
+        toggle_piece(&mut g.pieces, PieceType::Rook, rook_to, side);
+    } else if let GameMoveType::Promotion(promo_piece, _) = mv.move_type {
+        toggle_piece(&mut g.pieces, promo_piece, mv.to, side);
+    } else {
+        toggle_piece(&mut g.pieces, mv.piece_type, mv.to, side);
+    }
+    if let Some(piece) = undo.captured_piece {
+        toggle_piece(&mut g.pieces, piece, capture_square(side, mv), 1 - side);
+    }
+    if let GameMoveType::Castle = mv.move_type {
+        let (king_to, rook_from, rook_to) = castling_squares(g, side, mv);
+        g.mailbox[king_to.index()] = None;
+        g.mailbox[rook_to.index()] = None;
+        g.mailbox[rook_from.index()] = Some((PieceType::Rook, Color::from_index(side)));
+    } else {
+        g.mailbox[mv.to.index()] = None;
+    }
+    g.mailbox[mv.from.index()] = Some((mv.piece_type, Color::from_index(side)));
+    if let Some(piece) = undo.captured_piece {
+        g.mailbox[capture_square(side, mv).index()] = Some((piece, Color::from_index(1 - side)));
+    }
+    g.castle_white_kingside = undo.castle_white_kingside;
+    g.castle_white_queenside = undo.castle_white_queenside;
+    g.castle_black_kingside = undo.castle_black_kingside;
+    g.castle_black_queenside = undo.castle_black_queenside;
+    g.en_passant = undo.en_passant;
+    g.half_moves = undo.half_moves;
+    g.hash = undo.hash;
+    g.pawn_hash = undo.pawn_hash;
+    g.psqt = undo.psqt;
+    g.phase = undo.phase;
+    #[cfg(feature = "nnue")]
+    {
+        g.nnue = undo.nnue;
+    }
+}
+
+//Applies mv to accumulators computed by network for the position before it, g is the position
+//after it
+#[cfg(feature = "nnue")]
+#[inline(always)]
+pub(crate) fn update_accumulator(
+    network: &Network,
+    acc: &mut Accumulator,
+    g: &GameState,
+    mv: GameMove,
+    captured_piece: Option<PieceType>,
+) {
+    let side = 1 - g.color_to_move;
+    let castling_squares = if let GameMoveType::Castle = mv.move_type {
+        Some(castling_squares(g, side, mv))
+    } else {
+        None
+    };
+    for perspective in 0..2 {
+        let king_square =
+            g.pieces[PieceType::King.to_index()][perspective].trailing_zeros() as usize;
+        //A king move can change the bucket of all features
+        if perspective == side
+            && mv.piece_type == PieceType::King
+            && nnue::king_bucket(mv.from.index()) != nnue::king_bucket(king_square)
+        {
+            network.refresh(acc, &g.pieces, perspective);
+            continue;
+        }
+        let feature = |piece: PieceType, color: usize, square: Square| {
+            nnue::piece_feature(perspective, king_square, piece, color, square)
+        };
+        network.remove_feature(acc, perspective, feature(mv.piece_type, side, mv.from));
+        if let Some(piece) = captured_piece {
+            let square = capture_square(side, mv);
+            network.remove_feature(acc, perspective, feature(piece, 1 - side, square));
+        }
+        match mv.move_type {
+            GameMoveType::Castle => {
+                let (king_to, rook_from, rook_to) = castling_squares.unwrap();
+                network.add_feature(acc, perspective, feature(PieceType::King, side, king_to));
+                network.remove_feature(acc, perspective, feature(PieceType::Rook, side, rook_from));
+                network.add_feature(acc, perspective, feature(PieceType::Rook, side, rook_to));
+            }
+            GameMoveType::Promotion(promo_piece, _) => {
+                network.add_feature(acc, perspective, feature(promo_piece, side, mv.to));
+            }
+            _ => network.add_feature(acc, perspective, feature(mv.piece_type, side, mv.to)),
+        }
+    }
+}
+
+#[inline(always)]
+fn apply_move(g: &mut GameState, mv: GameMove, captured_piece: Option<PieceType>) {
+    let side = g.color_to_move;
+    //Step 1. Update pieces, hash and other incremental fields
     let mut hash = g.hash ^ ZOBRIST_KEYS.side_to_move;
+    let mut pawn_hash = g.pawn_hash;
     let mut psqt = g.psqt;
-    let mut phase = g.phase.clone();
     //Remove piece from original square
-    toggle_piece(&mut pieces, mv.piece_type, mv.from, g.color_to_move);
-    toggle_hash(mv.piece_type, mv.from, g.color_to_move, &mut hash);
-    psqt_toggle_piece(
-        &mut pieces,
-        mv.piece_type,
-        mv.from as usize,
-        g.color_to_move,
-        &mut psqt,
-    );
-    let captured_piece = match mv.move_type {
-        GameMoveType::Capture(c) => Some(c),
-        GameMoveType::EnPassant => Some(PieceType::Pawn),
-        GameMoveType::Promotion(_, c) => {
-            if let Some(c) = c {
-                Some(c)
-            } else {
-                None
-            }
-        }
-        _ => None,
-    };
+    toggle_piece(&mut g.pieces, mv.piece_type, mv.from, side);
+    toggle_hash(mv.piece_type, mv.from, side, &mut hash);
+    toggle_pawn_hash(mv.piece_type, mv.from, side, &mut pawn_hash);
+    psqt_toggle_piece(&mut g.pieces, mv.piece_type, mv.from, side, &mut psqt);
     //Delete piece if capture
     if let Some(piece) = captured_piece {
-        let square = if let GameMoveType::EnPassant = mv.move_type {
-            if g.color_to_move == WHITE {
-                mv.to - 8
-            } else {
-                mv.to + 8
-            }
-        } else {
-            mv.to
-        };
-        toggle_piece(&mut pieces, piece, square, color_to_move);
-        toggle_hash(piece, square, color_to_move, &mut hash);
-        psqt_toggle_piece(
-            &mut pieces,
-            piece,
-            square as usize,
-            color_to_move,
-            &mut psqt,
-        );
-        phase.delete_piece(piece);
+        let square = capture_square(side, mv);
+        toggle_piece(&mut g.pieces, piece, square, 1 - side);
+        toggle_hash(piece, square, 1 - side, &mut hash);
+        toggle_pawn_hash(piece, square, 1 - side, &mut pawn_hash);
+        psqt_toggle_piece(&mut g.pieces, piece, square, 1 - side, &mut psqt);
+        g.phase.delete_piece(piece);
     }
     //Move rook for castling
     if let GameMoveType::Castle = mv.move_type {
-        toggle_piece(&mut pieces, mv.piece_type, mv.to, g.color_to_move);
-        toggle_hash(mv.piece_type, mv.to, g.color_to_move, &mut hash);
-        psqt_toggle_piece(
-            &mut pieces,
-            mv.piece_type,
-            mv.to as usize,
-            g.color_to_move,
-            &mut psqt,
-        );
-        let (rook_from, rook_to) = rook_castling(mv.to);
-        toggle_piece(&mut pieces, PieceType::Rook, rook_from, g.color_to_move);
-        toggle_hash(PieceType::Rook, rook_from, g.color_to_move, &mut hash);
-        psqt_toggle_piece(
-            &mut pieces,
-            PieceType::Rook,
-            rook_from as usize,
-            g.color_to_move,
-            &mut psqt,
-        );
-        toggle_piece(&mut pieces, PieceType::Rook, rook_to, g.color_to_move);
-        toggle_hash(PieceType::Rook, rook_to, g.color_to_move, &mut hash);
-        psqt_toggle_piece(
-            &mut pieces,
-            PieceType::Rook,
-            rook_to as usize,
-            g.color_to_move,
-            &mut psqt,
-        );
+        let (king_to, rook_from, rook_to) = castling_squares(g, side, mv);
+        toggle_piece(&mut g.pieces, mv.piece_type, king_to, side);
+        toggle_hash(mv.piece_type, king_to, side, &mut hash);
+        psqt_toggle_piece(&mut g.pieces, mv.piece_type, king_to, side, &mut psqt);
+        toggle_piece(&mut g.pieces, PieceType::Rook, rook_from, side);
+        toggle_hash(PieceType::Rook, rook_from, side, &mut hash);
+        psqt_toggle_piece(&mut g.pieces, PieceType::Rook, rook_from, side, &mut psqt);
+        toggle_piece(&mut g.pieces, PieceType::Rook, rook_to, side);
+        toggle_hash(PieceType::Rook, rook_to, side, &mut hash);
+        psqt_toggle_piece(&mut g.pieces, PieceType::Rook, rook_to, side, &mut psqt);
     } else if let GameMoveType::Promotion(promo_piece, _) = mv.move_type {
         //If promotion, add promotion piece
-        toggle_piece(&mut pieces, promo_piece, mv.to, g.color_to_move);
-        toggle_hash(promo_piece, mv.to, g.color_to_move, &mut hash);
-        psqt_toggle_piece(
-            &mut pieces,
-            promo_piece,
-            mv.to as usize,
-            g.color_to_move,
-            &mut psqt,
-        );
-        phase.add_piece(promo_piece);
+        toggle_piece(&mut g.pieces, promo_piece, mv.to, side);
+        toggle_hash(promo_piece, mv.to, side, &mut hash);
+        psqt_toggle_piece(&mut g.pieces, promo_piece, mv.to, side, &mut psqt);
+        g.phase.add_piece(promo_piece);
     } else {
         //Add piece again at to
-        toggle_piece(&mut pieces, mv.piece_type, mv.to, g.color_to_move);
-        toggle_hash(mv.piece_type, mv.to, g.color_to_move, &mut hash);
-        psqt_toggle_piece(
-            &mut pieces,
-            mv.piece_type,
-            mv.to as usize,
-            g.color_to_move,
-            &mut psqt,
-        );
+        toggle_piece(&mut g.pieces, mv.piece_type, mv.to, side);
+        toggle_hash(mv.piece_type, mv.to, side, &mut hash);
+        toggle_pawn_hash(mv.piece_type, mv.to, side, &mut pawn_hash);
+        psqt_toggle_piece(&mut g.pieces, mv.piece_type, mv.to, side, &mut psqt);
+    }
+    //Step 2. Update mailbox
+    let color = Color::from_index(side);
+    g.mailbox[mv.from.index()] = None;
+    if captured_piece.is_some() {
+        g.mailbox[capture_square(side, mv).index()] = None;
+    }
+    match mv.move_type {
+        GameMoveType::Castle => {
+            let (king_to, rook_from, rook_to) = castling_squares(g, side, mv);
+            g.mailbox[rook_from.index()] = None;
+            g.mailbox[king_to.index()] = Some((PieceType::King, color));
+            g.mailbox[rook_to.index()] = Some((PieceType::Rook, color));
+        }
+        GameMoveType::Promotion(promo_piece, _) => {
+            g.mailbox[mv.to.index()] = Some((promo_piece, color));
+        }
+        _ => g.mailbox[mv.to.index()] = Some((mv.piece_type, color)),
     }
+    //Step 2.1. Leave the move to the network accumulators
+    #[cfg(feature = "nnue")]
+    g.nnue.play(mv, captured_piece);
     //Step 3. Update Castling Rights
     let (
         mut castle_white_kingside,
@@ -208,7 +358,7 @@ pub fn make_move(g: &GameState, mv: GameMove) -> GameState {
         g.castle_black_queenside,
     );
     if mv.move_type == GameMoveType::Castle || mv.piece_type == PieceType::King {
-        if g.color_to_move == WHITE {
+        if side == WHITE {
             castle_white_kingside = false;
             castle_white_queenside = false;
         } else {
@@ -216,26 +366,26 @@ pub fn make_move(g: &GameState, mv: GameMove) -> GameState {
             castle_black_queenside = false;
         }
     } else if mv.piece_type == PieceType::Rook {
-        if g.color_to_move == WHITE {
-            if mv.from == 0 {
+        if side == WHITE {
+            if mv.from == g.castle_rook_square(WHITE, false) {
                 castle_white_queenside = false;
-            } else if mv.from == 7 {
+            } else if mv.from == g.castle_rook_square(WHITE, true) {
                 castle_white_kingside = false;
             }
-        } else if mv.from == 56 {
+        } else if mv.from == g.castle_rook_square(BLACK, false) {
             castle_black_queenside = false;
-        } else if mv.from == 63 {
+        } else if mv.from == g.castle_rook_square(BLACK, true) {
             castle_black_kingside = false;
         }
     }
     if captured_piece.is_some() {
-        if mv.to == 0 {
+        if mv.to == g.castle_rook_square(WHITE, false) {
             castle_white_queenside = false;
-        } else if mv.to == 56 {
+        } else if mv.to == g.castle_rook_square(BLACK, false) {
             castle_black_queenside = false;
-        } else if mv.to == 7 {
+        } else if mv.to == g.castle_rook_square(WHITE, true) {
             castle_white_kingside = false;
-        } else if mv.to == 63 {
+        } else if mv.to == g.castle_rook_square(BLACK, true) {
             castle_black_kingside = false;
         }
     }
@@ -247,38 +397,30 @@ pub fn make_move(g: &GameState, mv: GameMove) -> GameState {
         castle_black_queenside,
         &mut hash,
     );
+    g.castle_white_kingside = castle_white_kingside;
+    g.castle_white_queenside = castle_white_queenside;
+    g.castle_black_kingside = castle_black_kingside;
+    g.castle_black_queenside = castle_black_queenside;
     //Step 4. Update en passant field
     let en_passant = if mv.move_type == GameMoveType::Quiet
         && mv.piece_type == PieceType::Pawn
-        && (mv.to as isize - mv.from as isize).abs() == 16
+        && (mv.to.index() as isize - mv.from.index() as isize).abs() == 16
     {
-        if g.color_to_move == WHITE {
-            1u64 << (mv.to - 8)
-        } else {
-            1u64 << (mv.to + 8)
-        }
+        Bitboard(1u64 << ((mv.from.index() + mv.to.index()) / 2))
     } else {
-        0u64
+        Bitboard::EMPTY
     };
     enpassant_hash(g.en_passant, en_passant, &mut hash);
-    //Step 5. Half moves
-    let half_moves = if mv.move_type == GameMoveType::Quiet && mv.piece_type != PieceType::Pawn {
+    g.en_passant = en_passant;
+    //Step 5. Half moves and immediate fields
+    g.half_moves = if mv.move_type == GameMoveType::Quiet && mv.piece_type != PieceType::Pawn {
         g.half_moves + 1
     } else {
         0
     };
-    GameState {
-        color_to_move,
-        pieces,
-        castle_white_kingside,
-        castle_white_queenside,
-        castle_black_kingside,
-        castle_black_queenside,
-        en_passant,
-        half_moves,
-        full_moves,
-        hash,
-        psqt,
-        phase,
-    }
+    g.color_to_move = 1 - side;
+    g.full_moves += side;
+    g.hash = hash;
+    g.pawn_hash = pawn_hash;
+    g.psqt = psqt;
 }
@@ -5,7 +5,8 @@ use crate::bitboards::bitboards::constants::{
     ROOK_RAYS,
 };
 use crate::board_representation::game_state::{
-    GameMove, GameMoveType, GameState, PieceType, BISHOP, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE,
+    GameMove, GameMoveType, GameState, PieceType, Square, BISHOP, KING, KNIGHT, PAWN, QUEEN, ROOK,
+    WHITE,
 };
 use crate::board_representation::game_state_attack_container::{
     GameStateAttackContainer, MGSA_BISHOP, MGSA_KNIGHT, MGSA_QUEEN, MGSA_ROOKS,
@@ -115,19 +116,9 @@ pub fn b_pawn_west_targets(pawns: u64) -> u64 {
 
 #[inline(always)]
 pub fn find_captured_piece_type(g: &GameState, to: usize) -> PieceType {
-    let to_board = 1u64 << to;
-    if g.pieces[PAWN][1 - g.color_to_move] & to_board != 0u64 {
-        PieceType::Pawn
-    } else if g.pieces[KNIGHT][1 - g.color_to_move] & to_board != 0u64 {
-        PieceType::Knight
-    } else if g.pieces[QUEEN][1 - g.color_to_move] & to_board != 0u64 {
-        PieceType::Queen
-    } else if g.pieces[BISHOP][1 - g.color_to_move] & to_board != 0u64 {
-        PieceType::Bishop
-    } else if g.pieces[ROOK][1 - g.color_to_move] & to_board != 0u64 {
-        PieceType::Rook
-    } else {
-        panic!("Shoudln't get here");
+    match g.mailbox[to] {
+        Some((piece, _)) => piece,
+        None => panic!("Shoudln't get here"),
     }
 }
 
@@ -421,8 +412,8 @@ pub fn add_move_to_movelist(
     move_type: GameMoveType,
 ) {
     legal_moves.add_move(GameMove {
-        from: from_square as u8,
-        to: to_square as u8,
+        from: Square::ALL[from_square],
+        to: Square::ALL[to_square],
         move_type,
         piece_type,
     });
@@ -542,7 +533,7 @@ pub fn generate_moves(
         movelist,
         only_captures,
         stm_legal_kingmoves,
-        g.king_square(side),
+        g.king_square(side).index(),
         enemy_pieces,
     );
     //----------------------------------------------------------------------
@@ -568,12 +559,12 @@ pub fn generate_moves(
             != 0u64
         {
             let checker_square = check_board.trailing_zeros() as usize;
-            if check_board & (FREEFIELD_ROOK_ATTACKS[g.king_square(side)]) != 0u64 {
+            if check_board & (FREEFIELD_ROOK_ATTACKS[g.king_square(side).index()]) != 0u64 {
                 //Checker is rook-like
-                push_mask = ROOK_RAYS[g.king_square(side)][checker_square];
+                push_mask = ROOK_RAYS[g.king_square(side).index()][checker_square];
             } else {
                 //Checker is bishop-like
-                push_mask = BISHOP_RAYS[g.king_square(side)][checker_square];
+                push_mask = BISHOP_RAYS[g.king_square(side).index()][checker_square];
             }
         } else {
             //else, we can't do push (quiet) moves
@@ -586,23 +577,23 @@ pub fn generate_moves(
     //4. Pins and pinned pieces
     let mut pinned_pieces = 0u64;
     //4.1 Rook-Like pins
-    if FREEFIELD_ROOK_ATTACKS[g.king_square(side)]
+    if FREEFIELD_ROOK_ATTACKS[g.king_square(side).index()]
         & (g.pieces[ROOK][enemy] | g.pieces[QUEEN][enemy])
         != 0u64
     {
-        let stm_rook_attacks_from_king = rook_attack(g.king_square(side), all_pieces);
+        let stm_rook_attacks_from_king = rook_attack(g.king_square(side).index(), all_pieces);
         let stm_xray_rook_attacks_from_king = xray_rook_attacks(
             stm_rook_attacks_from_king,
             all_pieces,
             side_pieces,
-            g.king_square(side),
+            g.king_square(side).index(),
         );
         let mut enemy_rooks_on_xray =
             stm_xray_rook_attacks_from_king & (g.pieces[ROOK][enemy] | g.pieces[QUEEN][enemy]);
         while enemy_rooks_on_xray != 0u64 {
             let enemy_rook_position = enemy_rooks_on_xray.trailing_zeros() as usize;
             let enemy_rook = 1u64 << enemy_rook_position;
-            let ray_to_king = ROOK_RAYS[g.king_square(side)][enemy_rook_position];
+            let ray_to_king = ROOK_RAYS[g.king_square(side).index()][enemy_rook_position];
             let pinned_piece = ray_to_king & side_pieces;
             let pinned_piece_position = pinned_piece.trailing_zeros() as usize;
             pinned_pieces |= pinned_piece;
@@ -666,23 +657,23 @@ pub fn generate_moves(
         }
     }
     //4.2 Bishop-Like pins
-    if FREEFIELD_BISHOP_ATTACKS[g.king_square(side)]
+    if FREEFIELD_BISHOP_ATTACKS[g.king_square(side).index()]
         & (g.pieces[BISHOP][enemy] | g.pieces[QUEEN][enemy])
         != 0u64
     {
-        let stm_bishop_attacks_from_king = bishop_attack(g.king_square(side), all_pieces);
+        let stm_bishop_attacks_from_king = bishop_attack(g.king_square(side).index(), all_pieces);
         let stm_xray_bishop_attacks_from_king = xray_bishop_attacks(
             stm_bishop_attacks_from_king,
             all_pieces,
             side_pieces,
-            g.king_square(side),
+            g.king_square(side).index(),
         );
         let mut enemy_bishop_on_xray =
             stm_xray_bishop_attacks_from_king & (g.pieces[BISHOP][enemy] | g.pieces[QUEEN][enemy]);
         while enemy_bishop_on_xray != 0u64 {
             let enemy_bishop_position = enemy_bishop_on_xray.trailing_zeros() as usize;
             let enemy_bishop = 1u64 << enemy_bishop_position;
-            let ray_to_king = BISHOP_RAYS[g.king_square(side)][enemy_bishop_position];
+            let ray_to_king = BISHOP_RAYS[g.king_square(side).index()][enemy_bishop_position];
             let pinned_piece = ray_to_king & side_pieces;
             let pinned_piece_position = pinned_piece.trailing_zeros() as usize;
             pinned_pieces |= pinned_piece;
@@ -760,7 +751,7 @@ pub fn generate_moves(
                 }
                 //En passants
                 let stm_pawn_pin_enpassant =
-                    stm_pawn_pin_target & g.en_passant & capture_mask & ray_to_king;
+                    stm_pawn_pin_target & g.en_passant.0 & capture_mask & ray_to_king;
                 if stm_pawn_pin_enpassant != 0u64 {
                     stm_haslegalmove = true;
                     add_move_to_movelist(
@@ -858,7 +849,7 @@ pub fn generate_moves(
     );
     //En passants
     let stm_pawn_west_enpassants = attack_container.pawn_west_attacks[side]
-        & g.en_passant
+        & g.en_passant.0
         & if stm_color_iswhite {
             capture_mask << 8
         } else {
@@ -880,8 +871,8 @@ pub fn generate_moves(
         };
         let all_pieces_without_en_passants =
             all_pieces & !(1u64 << pawn_from) & !(1u64 << removed_piece_index);
-        if rook_attack(g.king_square(side), all_pieces_without_en_passants)
-            & RANKS[g.king_square(side) / 8]
+        if rook_attack(g.king_square(side).index(), all_pieces_without_en_passants)
+            & RANKS[g.king_square(side).index() / 8]
             & (g.pieces[ROOK][enemy] | g.pieces[QUEEN][enemy])
             == 0u64
         {
@@ -923,7 +914,7 @@ pub fn generate_moves(
     );
     //En passants
     let stm_pawn_east_enpassants = attack_container.pawn_east_attacks[side]
-        & g.en_passant
+        & g.en_passant.0
         & if stm_color_iswhite {
             capture_mask << 8
         } else {
@@ -945,8 +936,8 @@ pub fn generate_moves(
         };
         let all_pieces_without_en_passants =
             all_pieces & !(1u64 << pawn_from) & !(1u64 << removed_piece_index);
-        if rook_attack(g.king_square(side), all_pieces_without_en_passants)
-            & RANKS[g.king_square(side) / 8]
+        if rook_attack(g.king_square(side).index(), all_pieces_without_en_passants)
+            & RANKS[g.king_square(side).index() / 8]
             & (g.pieces[ROOK][enemy] | g.pieces[QUEEN][enemy])
             == 0u64
         {
@@ -1025,61 +1016,13 @@ pub fn generate_moves(
     //**********************************************************************
     //7. Castling
     if (!only_captures || !stm_haslegalmove) && checkers == 0 {
-        if stm_color_iswhite {
-            if g.castle_white_kingside
-                && (all_pieces | attack_container.attacks_sum[enemy]) & (square(5) | square(6))
-                    == 0u64
-            {
-                stm_haslegalmove = true;
-                if !only_captures {
-                    movelist.add_move(GameMove {
-                        from: g.king_square(side) as u8,
-                        to: 6,
-                        move_type: GameMoveType::Castle,
-                        piece_type: PieceType::King,
-                    });
-                }
-            }
-            if g.castle_white_queenside
-                && ((all_pieces | attack_container.attacks_sum[enemy]) & (square(2) | square(3))
-                    | all_pieces & square(1))
-                    == 0u64
-            {
-                stm_haslegalmove = true;
-                if !only_captures {
-                    movelist.add_move(GameMove {
-                        from: g.king_square(side) as u8,
-                        to: 2,
-                        move_type: GameMoveType::Castle,
-                        piece_type: PieceType::King,
-                    });
-                }
-            }
-        } else {
-            if g.castle_black_kingside
-                && (all_pieces | attack_container.attacks_sum[enemy]) & (square(61) | square(62))
-                    == 0u64
-            {
-                stm_haslegalmove = true;
-                if !only_captures {
-                    movelist.add_move(GameMove {
-                        from: g.king_square(side) as u8,
-                        to: 62,
-                        move_type: GameMoveType::Castle,
-                        piece_type: PieceType::King,
-                    });
-                }
-            }
-            if g.castle_black_queenside
-                && ((all_pieces | attack_container.attacks_sum[enemy]) & (square(58) | square(59))
-                    | all_pieces & square(57))
-                    == 0u64
-            {
+        for &kingside in &[true, false] {
+            if castle_permitted(g, kingside, attack_container) {
                 stm_haslegalmove = true;
                 if !only_captures {
                     movelist.add_move(GameMove {
-                        from: g.king_square(side) as u8,
-                        to: 58,
+                        from: g.king_square(side),
+                        to: g.castle_target_square(side, kingside),
                         move_type: GameMoveType::Castle,
                         piece_type: PieceType::King,
                     });
@@ -1093,3 +1036,55 @@ pub fn generate_moves(
         stm_haslegalmove,
     }
 }
+
+//Squares from a up to and including b, both on the same rank
+#[inline(always)]
+fn rank_span(a: usize, b: usize) -> u64 {
+    let (low, high) = if a < b { (a, b) } else { (b, a) };
+    (!0u64 >> (63 - high)) & (!0u64 << low)
+}
+
+//Checks whether the side to move may castle to the given side right now.
+//Works for standard chess as well as for Chess960, where king and castling rook may start on any file
+pub fn castle_permitted(
+    g: &GameState,
+    kingside: bool,
+    attack_container: &GameStateAttackContainer,
+) -> bool {
+    let side = g.color_to_move;
+    let enemy = 1 - side;
+    let has_right = match (side == WHITE, kingside) {
+        (true, true) => g.castle_white_kingside,
+        (true, false) => g.castle_white_queenside,
+        (false, true) => g.castle_black_kingside,
+        (false, false) => g.castle_black_queenside,
+    };
+    if !has_right {
+        return false;
+    }
+    let king_from = g.king_square(side).index();
+    let rook_from = g.castle_rook_square(side, kingside).index();
+    if g.pieces[ROOK][side] & square(rook_from) == 0u64 {
+        return false;
+    }
+    let back_rank = rook_from - rook_from % 8;
+    let (king_to, rook_to) = if kingside {
+        (back_rank + 6, back_rank + 5)
+    } else {
+        (back_rank + 2, back_rank + 3)
+    };
+    let occupied = g.get_all_pieces() & !square(king_from) & !square(rook_from);
+    //Every square king and rook pass or land on has to be empty, apart from the two of them
+    if occupied & (rank_span(king_from, king_to) | rank_span(rook_from, rook_to)) != 0u64 {
+        return false;
+    }
+    //The king may not start, pass or land on an attacked square
+    if attack_container.attacks_sum[enemy] & rank_span(king_from, king_to) != 0u64 {
+        return false;
+    }
+    //In Chess960 the castling rook itself may have shielded the king's target square
+    rook_attack(king_to, occupied | square(rook_to))
+        & RANKS[king_to / 8]
+        & (g.pieces[ROOK][enemy] | g.pieces[QUEEN][enemy])
+        == 0u64
+}
@@ -1,8 +1,8 @@
 use super::super::board_representation::game_state::*;
+use super::cache::CacheEntry;
 use super::quiescence::q_search;
 use super::*;
 use super::{MATE_SCORE, MAX_SEARCH_DEPTH, STANDARD_SCORE};
-use crate::evaluation::eval_game_state;
 use crate::move_generation::makemove::{make_move, make_nullmove};
 use crate::search::moveordering::{MoveOrderer, NORMAL_STAGES};
 use crate::search::searcher::Thread;
@@ -17,6 +17,14 @@ pub const HISTORY_PRUNING_THRESHOLD: isize = 0;
 pub const SEE_PRUNING_DEPTH: i16 = 6;
 pub const SEE_PRUNING_CAPTURE_MULT: f64 = -23.;
 pub const SEE_PRUNING_QUIET_MULT: f64 = -23.;
+//Singular extensions stay off by default until the referee's SINGULAR_CONFIG.json shows a gain
+pub const DEFAULT_SINGULAR_DEPTH: i16 = 8;
+pub const MIN_SINGULAR_DEPTH: i16 = 4;
+pub const MAX_SINGULAR_DEPTH: i16 = 20;
+//Per depth left, below the TT score
+pub const DEFAULT_SINGULAR_MARGIN: i16 = 2;
+pub const MIN_SINGULAR_MARGIN: i16 = 0;
+pub const MAX_SINGULAR_MARGIN: i16 = 20;
 
 pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut Thread) -> i16 {
     //Step 0. Prepare variables
@@ -25,7 +33,10 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
     let root = p.current_depth == 0;
     let is_pv_node = p.beta - p.alpha > 1;
     //Step 1. Check timeout and if stop flag is set, if we are main thread
-    if thread.search_statistics.nodes_searched % 4096 == 0 {
+    //A node budget is looked at again once the thread searched its share of the nodes left
+    if thread.search_statistics.nodes_searched % 4096 == 0
+        || thread.search_statistics.nodes_searched >= thread.node_checkup
+    {
         checkup(thread)
     }
     if thread.search_statistics.nodes_searched % 8192 == 0 {
@@ -46,8 +57,11 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
 
     //Step 3. Check for draw or mate distance pruning if not root (need best move at root)
     if !root {
-        if let SearchInstruction::StopSearching(res) = check_for_draw(p.game_state, &thread.history)
-        {
+        if let SearchInstruction::StopSearching(res) = check_for_draw(
+            p.game_state,
+            &thread.history,
+            thread.draw_score(p.game_state.color_to_move),
+        ) {
             return res;
         }
         //Mate distance pruning
@@ -64,8 +78,9 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
         &thread.attack_container.attack_containers[p.current_depth],
     );
 
-    //Step 5. Check extensions if not at root
-    if incheck && !root {
+    //Step 5. Check extensions if not at root. A singular search keeps the depth of its node
+    let excluded_move = thread.excluded_moves[p.current_depth];
+    if incheck && !root && excluded_move.is_none() {
         p.depth_left += 1;
     }
 
@@ -79,20 +94,22 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
     //Step 7. PV-Table Lookup
     let pv_table_move = get_pvtable_move(&p, thread);
 
-    //Step 8. TT Lookup
+    //Step 8. TT Lookup. A singular search shares the entry of the node it verifies, so it skips it
     let mut static_evaluation = None;
     let mut tt_move: Option<GameMove> = None;
-    if let SearchInstruction::StopSearching(res) = thread.itcs.cache().lookup(
-        &p,
-        &mut static_evaluation,
-        &mut tt_move,
-        thread.root_plies_played,
-    ) {
-        #[cfg(feature = "search-statistics")]
-        {
-            thread.search_statistics.add_cache_hit_aj_replace_ns();
+    if excluded_move.is_none() {
+        if let SearchInstruction::StopSearching(res) = thread.itcs.cache().lookup(
+            &p,
+            &mut static_evaluation,
+            &mut tt_move,
+            thread.root_plies_played,
+        ) {
+            #[cfg(feature = "search-statistics")]
+            {
+                thread.search_statistics.add_cache_hit_aj_replace_ns();
+            }
+            return res;
         }
-        return res;
     }
     #[cfg(feature = "search-statistics")]
     {
@@ -105,7 +122,7 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
         .push(p.game_state.hash, p.game_state.half_moves == 0);
 
     //Step 9. Static Eval if needed
-    let prunable = !is_pv_node && !incheck;
+    let prunable = !is_pv_node && !incheck && excluded_move.is_none();
     make_eval(&p, thread, &mut static_evaluation, prunable);
 
     //Step 10. Prunings
@@ -136,6 +153,17 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
     //Step 12. Futil Pruning and margin preparation
     let futil_margin = prepare_futility_pruning(&p, static_evaluation);
 
+    //Step 13. Singular extension. Extend the TT move if all others fail low against a bound below
+    //its score. If even that bound fails high, more than one move beats beta (multi-cut)
+    let mut singular = false;
+    if let Some(mv) = tt_move {
+        if let SearchInstruction::StopSearching(res) =
+            singular_extension(&p, thread, mv, excluded_move, &mut singular)
+        {
+            return res;
+        }
+    }
+
     //Step 14. Iterate through all moves
     let mut current_max_score = STANDARD_SCORE;
     let mut index: usize = 0;
@@ -153,6 +181,12 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
         }
         let (mv, move_score) = mv.unwrap(); //Move score is only set for bad_capture
 
+        //Moves excluded by searchmoves or MultiPV are left out at the root. They don't count
+        //towards the move index, so the first remaining move still gets the full window
+        if root && thread.is_excluded_root_move(mv) || excluded_move == Some(mv) {
+            continue;
+        }
+
         //Step 14.4. UCI Reporting at root
         //uci_report_move(&p, su, &mv, index);
 
@@ -170,8 +204,8 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
             && current_max_score > MATED_IN_MAX
             && (futil_margin <= p.alpha
                 || p.depth_left <= HISTORY_PRUNING_DEPTH
-                    && thread.history_score[p.game_state.color_to_move][mv.from as usize]
-                        [mv.to as usize]
+                    && thread.history_score[p.game_state.color_to_move][mv.from.index()]
+                        [mv.to.index()]
                         < HISTORY_PRUNING_THRESHOLD)
             && p.game_state.has_non_pawns(p.game_state.color_to_move)
             && !gives_check
@@ -187,8 +221,7 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
             }
             //Step 14.6. History Pruning. Skip quiet moves in low depths if they are below threshold
             if p.depth_left <= HISTORY_PRUNING_DEPTH
-                && thread.history_score[p.game_state.color_to_move][mv.from as usize]
-                    [mv.to as usize]
+                && thread.history_score[p.game_state.color_to_move][mv.from.index()][mv.to.index()]
                     < HISTORY_PRUNING_THRESHOLD
             {
                 #[cfg(feature = "search-statistics")]
@@ -229,7 +262,13 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
             0
         };
 
-        let next_state = make_move(p.game_state, mv);
+        let mut next_state = make_move(p.game_state, mv);
+        thread.update_accumulator(&mut next_state);
+        let next_depth = if singular && tt_move == Some(mv) {
+            p.depth_left
+        } else {
+            p.depth_left - 1
+        };
         //Step 14.8. Search the moves
         let mut following_score: i16;
         if p.depth_left <= 2 || !is_pv_node || index == 0 {
@@ -241,7 +280,7 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
                 CombinedSearchParameters::from(
                     -p.beta,
                     -p.alpha,
-                    p.depth_left - 1 - reduction,
+                    next_depth - reduction,
                     &next_state,
                     -p.color,
                     p.current_depth + 1,
@@ -253,7 +292,7 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
                     CombinedSearchParameters::from(
                         -p.beta,
                         -p.alpha,
-                        p.depth_left - 1,
+                        next_depth,
                         &next_state,
                         -p.color,
                         p.current_depth + 1,
@@ -268,7 +307,7 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
                 CombinedSearchParameters::from(
                     -p.alpha - 1,
                     -p.alpha,
-                    p.depth_left - 1 - reduction,
+                    next_depth - reduction,
                     &next_state,
                     -p.color,
                     p.current_depth + 1,
@@ -280,7 +319,7 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
                     CombinedSearchParameters::from(
                         -p.beta,
                         -p.alpha,
-                        p.depth_left - 1,
+                        next_depth,
                         &next_state,
                         -p.color,
                         p.current_depth + 1,
@@ -323,7 +362,7 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
             //Step 14.12 Move does not cause beta cutoff, add to quiet moves tried and update butterfly heuristic
             thread.quiets_tried[p.current_depth][quiets_tried] = Some(mv);
             quiets_tried += 1;
-            thread.bf_score[p.game_state.color_to_move][mv.from as usize][mv.to as usize] +=
+            thread.bf_score[p.game_state.color_to_move][mv.from.index()][mv.to.index()] +=
                 p.depth_left as usize * p.depth_left as usize;
             //TODO: Update bf should maybe also be done in decrement history quiets
         }
@@ -333,6 +372,10 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
 
     thread.history.pop();
 
+    //Nothing but the excluded move may be legal, which makes the TT move singular
+    if excluded_move.is_some() && current_max_score == STANDARD_SCORE {
+        return p.alpha;
+    }
     debug_assert!(
         !move_orderer.has_legal_move || current_max_score > STANDARD_SCORE || thread.self_stop
     );
@@ -341,7 +384,12 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
         check_end_condition(p.game_state, current_max_score > STANDARD_SCORE, incheck);
     if game_status != GameResult::Ingame {
         clear_pv(p.current_depth, thread);
-        return leaf_score(game_status, p.color, p.current_depth as i16);
+        return leaf_score(
+            game_status,
+            p.color,
+            p.current_depth as i16,
+            thread.draw_score(p.game_state.color_to_move),
+        );
     }
     #[cfg(feature = "search-statistics")]
     {
@@ -350,8 +398,9 @@ pub fn principal_variation_search(mut p: CombinedSearchParameters, thread: &mut
         }
     }
 
-    //Step 16. Make TT Entry
-    if !thread.self_stop {
+    //Step 16. Make TT Entry. The root result of a search with excluded moves is not the real one,
+    //neither is the result of a singular search
+    if !thread.self_stop && (!root || !thread.root_moves_restricted()) && excluded_move.is_none() {
         thread.itcs.cache().insert(
             &p,
             thread.pv_table[p.current_depth].pv[0].expect("Can't unwrap move for TT"),
@@ -401,14 +450,12 @@ pub fn max_depth(p: &CombinedSearchParameters, thread: &mut Thread) -> SearchIns
     if p.current_depth >= (MAX_SEARCH_DEPTH - 1) {
         thread.attack_container.attack_containers[p.current_depth].write_state(p.game_state);
         SearchInstruction::StopSearching(
-            eval_game_state(
+            thread.evaluate(
                 p.game_state,
-                &thread.attack_container.attack_containers[p.current_depth],
+                p.current_depth,
                 p.alpha * p.color,
                 p.beta * p.color,
-            )
-            .final_eval
-                * p.color,
+            ) * p.color,
         )
     } else {
         SearchInstruction::ContinueSearching
@@ -445,13 +492,12 @@ pub fn make_eval(
             && (p.depth_left <= STATIC_NULL_MOVE_DEPTH || p.depth_left >= NULL_MOVE_PRUNING_DEPTH)
             || p.depth_left <= FUTILITY_DEPTH)
     {
-        let eval_res = eval_game_state(
+        *static_evaluation = Some(thread.evaluate(
             p.game_state,
-            &thread.attack_container.attack_containers[p.current_depth],
+            p.current_depth,
             p.alpha * p.color,
             p.beta * p.color,
-        );
-        *static_evaluation = Some(eval_res.final_eval);
+        ));
         #[cfg(feature = "search-statistics")]
         {
             thread.search_statistics.add_static_eval_node();
@@ -550,6 +596,71 @@ pub fn internal_iterative_deepening(
     SearchInstruction::ContinueSearching
 }
 
+#[inline(always)]
+pub fn singular_extension(
+    p: &CombinedSearchParameters,
+    thread: &mut Thread,
+    tt_move: GameMove,
+    excluded_move: Option<GameMove>,
+    singular: &mut bool,
+) -> SearchInstruction {
+    if !thread.singular_extensions
+        || p.current_depth == 0
+        || excluded_move.is_some()
+        || p.depth_left < thread.singular_depth
+    {
+        return SearchInstruction::ContinueSearching;
+    }
+    //Only a lower bound or exact score from a search not much shallower than this one is trusted
+    let ce = match thread
+        .itcs
+        .cache()
+        .get(p.game_state.hash)
+        .probe(p.game_state.hash)
+    {
+        Some(ce) => ce,
+        None => return SearchInstruction::ContinueSearching,
+    };
+    if ce.alpha
+        || i16::from(ce.depth) < p.depth_left - 3
+        || ce.score.abs() >= -MATED_IN_MAX
+        || CacheEntry::u16_to_mv(ce.mv, p.game_state) != tt_move
+    {
+        return SearchInstruction::ContinueSearching;
+    }
+    let singular_beta = ce.score - thread.singular_margin * p.depth_left;
+    thread.history.pop();
+    thread.excluded_moves[p.current_depth] = Some(tt_move);
+    let score = principal_variation_search(
+        CombinedSearchParameters::from(
+            singular_beta - 1,
+            singular_beta,
+            (p.depth_left - 1) / 2,
+            p.game_state,
+            p.color,
+            p.current_depth,
+        ),
+        thread,
+    );
+    thread.excluded_moves[p.current_depth] = None;
+    #[cfg(feature = "search-statistics")]
+    {
+        thread.search_statistics.add_singular_search();
+    }
+    if thread.self_stop {
+        return SearchInstruction::StopSearching(STANDARD_SCORE);
+    }
+    if score < singular_beta {
+        *singular = true;
+    } else if singular_beta >= p.beta {
+        return SearchInstruction::StopSearching(singular_beta);
+    }
+    thread
+        .history
+        .push(p.game_state.hash, p.game_state.half_moves == 0);
+    SearchInstruction::ContinueSearching
+}
+
 #[inline(always)]
 pub fn prepare_futility_pruning(
     p: &CombinedSearchParameters,
@@ -583,7 +694,7 @@ pub fn compute_lmr_reduction(
     if gives_check {
         reduction -= 1;
     }
-    if thread.history_score[p.game_state.color_to_move][mv.from as usize][mv.to as usize] > 0 {
+    if thread.history_score[p.game_state.color_to_move][mv.from.index()][mv.to.index()] > 0 {
         reduction -= 1;
     }
     reduction = reduction.min(p.depth_left - 1);
@@ -617,9 +728,9 @@ pub fn update_quiet_cutoff(
     mv: GameMove,
     quiets_tried: usize,
 ) {
-    thread.hh_score[p.game_state.color_to_move][mv.from as usize][mv.to as usize] +=
+    thread.hh_score[p.game_state.color_to_move][mv.from.index()][mv.to.index()] +=
         p.depth_left as usize * p.depth_left as usize;
-    thread.history_score[p.game_state.color_to_move][mv.from as usize][mv.to as usize] +=
+    thread.history_score[p.game_state.color_to_move][mv.from.index()][mv.to.index()] +=
         p.depth_left as isize * p.depth_left as isize;
     decrement_history_quiets(
         thread,
@@ -653,7 +764,7 @@ pub fn decrement_history_quiets(
 ) {
     for i in 0..quiets_tried {
         let mv = thread.quiets_tried[current_depth][i].as_ref().unwrap();
-        thread.history_score[side_to_move][mv.from as usize][mv.to as usize] -=
+        thread.history_score[side_to_move][mv.from.index()][mv.to.index()] -=
             depth_left * depth_left;
     }
 }
@@ -0,0 +1,203 @@
+use super::cache::Cache;
+use super::observer::{SearchObserver, SearchResult};
+use super::searcher::{search_move, CancellationToken, InterThreadCommunicationSystem};
+use super::timecontrol::{SearchLimits, TimeControl};
+use super::MAX_SEARCH_DEPTH;
+use crate::board_representation::game_state::GameState;
+use crate::UCIOptions;
+use std::io;
+use std::mem;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+
+//Entry point for using the search as a library:
+//Search::new(state).depth(10).observer(observer).run()
+//Without a depth, node, mate or time limit the search only ends once it is stopped, which needs
+//it to be started instead of run
+pub struct Search {
+    state: GameState,
+    history: Vec<GameState>,
+    tc: TimeControl,
+    max_depth: usize,
+    limits: SearchLimits,
+    options: Option<UCIOptions>,
+    observer: Option<Arc<dyn SearchObserver>>,
+}
+
+impl Search {
+    pub fn new(state: GameState) -> Self {
+        Search {
+            state,
+            history: Vec::new(),
+            tc: TimeControl::Infinite,
+            max_depth: MAX_SEARCH_DEPTH,
+            limits: SearchLimits::default(),
+            options: None,
+            observer: None,
+        }
+    }
+
+    //The positions played before the one searched, oldest first. Needed to detect repetitions
+    pub fn history(mut self, history: Vec<GameState>) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn time_control(mut self, tc: TimeControl) -> Self {
+        self.tc = tc;
+        self
+    }
+
+    pub fn depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth.min(MAX_SEARCH_DEPTH);
+        self
+    }
+
+    pub fn limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn options(mut self, options: UCIOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    //Replaces the UCI output of the engine state for this search
+    pub fn observer(mut self, observer: Arc<dyn SearchObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    //Searches on a new engine state, which is dropped afterwards
+    pub fn run(self) -> SearchResult {
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            io::sink(),
+        ));
+        self.search_on_new_state(&itcs, CancellationToken::default())
+    }
+
+    //Searches on an existing engine state, keeping its cache and the time saved on earlier moves
+    pub fn run_on(self, itcs: &Arc<InterThreadCommunicationSystem>) -> SearchResult {
+        self.search(itcs, CancellationToken::default())
+    }
+
+    //Like run, but the search goes on in the background
+    pub fn start(self) -> SearchHandle {
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            io::sink(),
+        ));
+        let search_itcs = Arc::clone(&itcs);
+        SearchHandle::spawn(itcs, move |cancellation| {
+            self.search_on_new_state(&search_itcs, cancellation)
+        })
+    }
+
+    //Like run_on, but the search goes on in the background. No other search may be started on the
+    //engine state until this one is over
+    pub fn start_on(self, itcs: &Arc<InterThreadCommunicationSystem>) -> SearchHandle {
+        let search_itcs = Arc::clone(itcs);
+        SearchHandle::spawn(Arc::clone(itcs), move |cancellation| {
+            self.search(&search_itcs, cancellation)
+        })
+    }
+
+    fn search_on_new_state(
+        self,
+        itcs: &Arc<InterThreadCommunicationSystem>,
+        cancellation: CancellationToken,
+    ) -> SearchResult {
+        let options = self.options.unwrap_or_default();
+        *itcs.uci_options() = options;
+        *itcs.cache() = Cache::with_size_threaded(options.hash_size, options.threads);
+        let result = self.search(itcs, cancellation);
+        //The helper threads hold on to the engine state until they exit
+        InterThreadCommunicationSystem::update_thread_count(itcs, 1);
+        result
+    }
+
+    fn search(
+        self,
+        itcs: &Arc<InterThreadCommunicationSystem>,
+        cancellation: CancellationToken,
+    ) -> SearchResult {
+        if let Some(options) = self.options {
+            let old_options = mem::replace(itcs.uci_options(), options);
+            if old_options.hash_size != options.hash_size {
+                *itcs.cache() = Cache::with_size_threaded(options.hash_size, options.threads);
+            }
+        }
+        if itcs.nodes_searched().len() != itcs.uci_options().threads {
+            let threads = itcs.uci_options().threads;
+            InterThreadCommunicationSystem::update_thread_count(itcs, threads);
+        }
+        let previous_observer = self
+            .observer
+            .map(|observer| mem::replace(&mut *itcs.observer.write().unwrap(), observer));
+        let result = search_move(
+            Arc::clone(itcs),
+            self.max_depth as i16,
+            self.state,
+            self.history,
+            self.tc,
+            self.limits,
+            cancellation,
+        );
+        if let Some(observer) = previous_observer {
+            *itcs.observer.write().unwrap() = observer;
+        }
+        result
+    }
+}
+
+//A search running in the background
+pub struct SearchHandle {
+    itcs: Arc<InterThreadCommunicationSystem>,
+    cancellation: CancellationToken,
+    search: Option<thread::JoinHandle<SearchResult>>,
+    //wasm32 has no threads, so there the search is over before the handle is returned
+    result: Option<SearchResult>,
+}
+
+impl SearchHandle {
+    fn spawn<F: FnOnce(CancellationToken) -> SearchResult + Send + 'static>(
+        itcs: Arc<InterThreadCommunicationSystem>,
+        search: F,
+    ) -> Self {
+        let cancellation = CancellationToken::default();
+        let search_cancellation = cancellation.clone();
+        #[cfg(not(target_arch = "wasm32"))]
+        let (search, result) = (
+            Some(thread::spawn(move || search(search_cancellation))),
+            None,
+        );
+        #[cfg(target_arch = "wasm32")]
+        let (search, result) = (None, Some(search(search_cancellation)));
+        SearchHandle {
+            itcs,
+            cancellation,
+            search,
+            result,
+        }
+    }
+
+    //Ends the search, pondering included. The bestmove is reported as usual
+    pub fn stop(&self) {
+        self.itcs.ponder.store(false, Ordering::Relaxed);
+        self.cancellation.cancel();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.search
+            .as_ref()
+            .is_some_and(|search| !search.is_finished())
+    }
+
+    pub fn wait(self) -> SearchResult {
+        match self.search {
+            Some(search) => search.join().expect("search thread panicked"),
+            None => self.result.expect("search without result"),
+        }
+    }
+}
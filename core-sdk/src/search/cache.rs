@@ -1,5 +1,5 @@
 use crate::board_representation::game_state::{
-    GameMove, GameMoveType, GameState, PieceType, BISHOP, KNIGHT, PAWN, QUEEN, ROOK,
+    GameMove, GameMoveType, GameState, PieceType, Square, BISHOP, KNIGHT, PAWN, QUEEN, ROOK,
 };
 use crate::search::{CombinedSearchParameters, SearchInstruction};
 use std::cell::UnsafeCell;
@@ -39,29 +39,39 @@ impl Cache {
             let chunksize = (buckets + num_threads - 1) / num_threads;
 
             let mut ptr = cache_vec.as_mut_ptr();
-            // let mut handles = Vec::new();
+            #[cfg(not(target_arch = "wasm32"))]
+            let mut handles = Vec::new();
 
             for t in 0..num_threads {
-                // The last chunk may be shorter.
-                let this_chunk = chunksize.min(buckets - t * chunksize);
+                // The last chunk may be shorter, or even empty if there are more threads than buckets.
+                let this_chunk = chunksize.min(buckets.saturating_sub(t * chunksize));
+                if this_chunk == 0 {
+                    break;
+                }
 
                 // circumvent the fact that raw pointers are not Send
-                let w = PtrWrapper { p: ptr.clone() };
-                // handles.push(std::thread::spawn(move || {
+                let w = PtrWrapper { p: ptr };
+                let init_chunk = move || {
                     let mut inner_ptr = w.p;
                     for _ in 0..this_chunk {
-                        *inner_ptr = CacheBucket::default();
+                        inner_ptr.write(CacheBucket::default());
                         inner_ptr = inner_ptr.offset(1);
                     }
-                // }));
-                ptr = ptr.offset(chunksize as isize);
+                };
+                // wasm32 has no threads, so the chunks are initialized one after another there
+                #[cfg(not(target_arch = "wasm32"))]
+                handles.push(std::thread::spawn(init_chunk));
+                #[cfg(target_arch = "wasm32")]
+                init_chunk();
+                ptr = ptr.add(chunksize);
             }
 
-            // for handle in handles {
-            //     handle
-            //         .join()
-            //         .expect("Could not unwrap handle while initializing the cache!");
-            // }
+            #[cfg(not(target_arch = "wasm32"))]
+            for handle in handles {
+                handle
+                    .join()
+                    .expect("Could not unwrap handle while initializing the cache!");
+            }
         }
         cache_vec
     }
@@ -369,8 +379,8 @@ impl CacheEntry {
     #[inline(always)]
     pub fn mv_to_u16(mv: GameMove) -> u16 {
         let mut res = 0;
-        res |= (mv.from as usize) << 10;
-        res |= (mv.to as usize) << 4;
+        res |= mv.from.index() << 10;
+        res |= mv.to.index() << 4;
         res |= match &mv.move_type {
             GameMoveType::Quiet => 1,
             GameMoveType::Castle => 2,
@@ -390,10 +400,10 @@ impl CacheEntry {
     #[inline(always)]
     pub fn u16_to_mv(mv: u16, game_state: &GameState) -> GameMove {
         let typ = mv & 15;
-        let from = ((mv & 0xFC00) >> 10) as u8;
-        let from_board = 1u64 << from;
-        let to = ((mv & 0x03F0) >> 4) as u8;
-        let to_board = 1u64 << to;
+        let from = Square::ALL[((mv & 0xFC00) >> 10) as usize];
+        let from_board = from.bitboard().0;
+        let to = Square::ALL[((mv & 0x03F0) >> 4) as usize];
+        let to_board = to.bitboard().0;
         let color_to_move = game_state.color_to_move;
         let enemy_color = 1 - color_to_move;
         let piece_type = if (game_state.pieces[PAWN][color_to_move] & from_board) != 0u64 {
@@ -522,7 +532,9 @@ unsafe impl<T> Send for PtrWrapper<T> {}
 #[cfg(test)]
 mod tests {
     use super::CacheEntry;
-    use crate::board_representation::game_state::{GameMove, GameMoveType, GameState, PieceType};
+    use crate::board_representation::game_state::{
+        GameMove, GameMoveType, GameState, PieceType, Square,
+    };
     use crate::move_generation::makemove::make_move;
 
     #[test]
@@ -530,8 +542,8 @@ mod tests {
         let mut game_state = GameState::from_fen("k4b2/2p1P3/8/3P4/6b1/7P/8/R3K2R w KQ -");
         {
             let h3h4 = GameMove {
-                from: 23,
-                to: 31,
+                from: Square::H3,
+                to: Square::H4,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Quiet,
             };
@@ -544,8 +556,8 @@ mod tests {
         }
         {
             let h3g4 = GameMove {
-                from: 23,
-                to: 30,
+                from: Square::H3,
+                to: Square::G4,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Capture(PieceType::Bishop),
             };
@@ -558,8 +570,8 @@ mod tests {
         }
         {
             let e1c1 = GameMove {
-                from: 4,
-                to: 2,
+                from: Square::E1,
+                to: Square::C1,
                 piece_type: PieceType::King,
                 move_type: GameMoveType::Castle,
             };
@@ -572,8 +584,8 @@ mod tests {
         }
         {
             let e1g1 = GameMove {
-                from: 4,
-                to: 6,
+                from: Square::E1,
+                to: Square::G1,
                 piece_type: PieceType::King,
                 move_type: GameMoveType::Castle,
             };
@@ -586,8 +598,8 @@ mod tests {
         }
         {
             let e7e8q = GameMove {
-                from: 52,
-                to: 60,
+                from: Square::E7,
+                to: Square::E8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Queen, None),
             };
@@ -600,8 +612,8 @@ mod tests {
         }
         {
             let e7e8r = GameMove {
-                from: 52,
-                to: 60,
+                from: Square::E7,
+                to: Square::E8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Rook, None),
             };
@@ -614,8 +626,8 @@ mod tests {
         }
         {
             let e7e8b = GameMove {
-                from: 52,
-                to: 60,
+                from: Square::E7,
+                to: Square::E8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Bishop, None),
             };
@@ -628,8 +640,8 @@ mod tests {
         }
         {
             let e7e8n = GameMove {
-                from: 52,
-                to: 60,
+                from: Square::E7,
+                to: Square::E8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Knight, None),
             };
@@ -643,8 +655,8 @@ mod tests {
 
         {
             let e7e8q = GameMove {
-                from: 52,
-                to: 61,
+                from: Square::E7,
+                to: Square::F8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Queen, Some(PieceType::Bishop)),
             };
@@ -657,8 +669,8 @@ mod tests {
         }
         {
             let e7e8r = GameMove {
-                from: 52,
-                to: 61,
+                from: Square::E7,
+                to: Square::F8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Rook, Some(PieceType::Bishop)),
             };
@@ -671,8 +683,8 @@ mod tests {
         }
         {
             let e7e8b = GameMove {
-                from: 52,
-                to: 61,
+                from: Square::E7,
+                to: Square::F8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Bishop, Some(PieceType::Bishop)),
             };
@@ -685,8 +697,8 @@ mod tests {
         }
         {
             let e7e8n = GameMove {
-                from: 52,
-                to: 61,
+                from: Square::E7,
+                to: Square::F8,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Promotion(PieceType::Knight, Some(PieceType::Bishop)),
             };
@@ -700,8 +712,8 @@ mod tests {
         game_state = make_move(
             &game_state,
             GameMove {
-                from: 23,
-                to: 31,
+                from: Square::H3,
+                to: Square::H4,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Quiet,
             },
@@ -709,16 +721,16 @@ mod tests {
         game_state = make_move(
             &game_state,
             GameMove {
-                from: 50,
-                to: 34,
+                from: Square::C7,
+                to: Square::C5,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::Quiet,
             },
         );
         {
             let d5d6 = GameMove {
-                from: 35,
-                to: 42,
+                from: Square::D5,
+                to: Square::C6,
                 piece_type: PieceType::Pawn,
                 move_type: GameMoveType::EnPassant,
             };
@@ -1,10 +1,13 @@
 pub mod alphabeta;
+pub mod builder;
 pub mod cache;
 pub mod history;
 pub mod moveordering;
+pub mod observer;
 pub mod quiescence;
 pub mod reserved_memory;
 pub mod searcher;
+pub mod skill;
 pub mod statistics;
 pub mod timecontrol;
 
@@ -14,11 +17,14 @@ use crate::search::searcher::Thread;
 use crate::search::timecontrol::TimeControlInformation;
 use history::History;
 use std::fmt::{Display, Formatter, Result};
+use std::sync::atomic::Ordering;
 
 pub const MAX_SEARCH_DEPTH: usize = 100;
 pub const MATE_SCORE: i16 = 15000;
 pub const MATED_IN_MAX: i16 = -14000;
 pub const STANDARD_SCORE: i16 = -32767;
+//Most nodes a thread searches between two looks at the shared node counters under a node budget
+pub const NODE_CHECKUP_INTERVAL: u64 = 1024;
 
 pub fn init_constants() {
     quiescence::PIECE_VALUES.len();
@@ -83,6 +89,10 @@ impl PrincipalVariation {
             pv: vec![None; depth_left + 1],
         }
     }
+
+    pub fn moves(&self) -> Vec<GameMove> {
+        self.pv.iter().map_while(|mv| *mv).collect()
+    }
 }
 
 impl Display for PrincipalVariation {
@@ -100,10 +110,11 @@ impl Display for PrincipalVariation {
 #[derive(Copy, Clone)]
 pub struct GradedMove(pub GameMove, pub Option<f64>);
 
+//draw_score is seen from the side to move, see Thread::draw_score
 #[inline(always)]
-pub fn leaf_score(game_status: GameResult, color: i16, current_depth: i16) -> i16 {
+pub fn leaf_score(game_status: GameResult, color: i16, current_depth: i16, draw_score: i16) -> i16 {
     if game_status == GameResult::Draw {
-        return 0;
+        return draw_score;
     } else if game_status == GameResult::WhiteWin {
         return (MATE_SCORE - current_depth) * color;
     } else if game_status == GameResult::BlackWin {
@@ -114,26 +125,34 @@ pub fn leaf_score(game_status: GameResult, color: i16, current_depth: i16) -> i1
 
 //Doesn't actually check for stalemate
 #[inline(always)]
-pub fn check_for_draw(game_state: &GameState, history: &History) -> SearchInstruction {
-    if game_state.pieces[PAWN][WHITE]
-        | game_state.pieces[ROOK][WHITE]
-        | game_state.pieces[QUEEN][WHITE]
-        | game_state.pieces[PAWN][BLACK]
-        | game_state.pieces[ROOK][BLACK]
-        | game_state.pieces[QUEEN][BLACK]
-        == 0u64
-        && (game_state.pieces[KNIGHT][WHITE] | game_state.pieces[BISHOP][WHITE]).count_ones() <= 1
-        && (game_state.pieces[KNIGHT][BLACK] | game_state.pieces[BISHOP][BLACK]).count_ones() <= 1
+pub fn check_for_draw(
+    game_state: &GameState,
+    history: &History,
+    draw_score: i16,
+) -> SearchInstruction {
+    //Besides dead positions, at most one minor piece per side can't be won by force either
+    if game_state.has_insufficient_material()
+        || game_state.pieces[PAWN][WHITE]
+            | game_state.pieces[ROOK][WHITE]
+            | game_state.pieces[QUEEN][WHITE]
+            | game_state.pieces[PAWN][BLACK]
+            | game_state.pieces[ROOK][BLACK]
+            | game_state.pieces[QUEEN][BLACK]
+            == 0u64
+            && (game_state.pieces[KNIGHT][WHITE] | game_state.pieces[BISHOP][WHITE]).count_ones()
+                <= 1
+            && (game_state.pieces[KNIGHT][BLACK] | game_state.pieces[BISHOP][BLACK]).count_ones()
+                <= 1
     {
-        return SearchInstruction::StopSearching(0);
+        return SearchInstruction::StopSearching(draw_score);
     }
 
     if game_state.half_moves >= 100 {
-        return SearchInstruction::StopSearching(0);
+        return SearchInstruction::StopSearching(draw_score);
     }
 
     if history.get_occurences(game_state) >= 1 {
-        return SearchInstruction::StopSearching(0);
+        return SearchInstruction::StopSearching(draw_score);
     }
     SearchInstruction::ContinueSearching
 }
@@ -188,31 +207,42 @@ pub fn in_check(game_state: &GameState, attack_container: &GameStateAttackContai
 
 #[inline(always)]
 pub fn checkup(thread: &mut Thread) {
-    if (thread.id == 0
-        && thread.tc.time_over(
-            thread.itcs.get_time_elapsed(),
-            &TimeControlInformation {
-                high_score_diff: false,
-                time_saved: thread.time_saved,
-                stable_pv: thread
-                    .itcs
-                    .stable_pv
-                    .load(std::sync::atomic::Ordering::Relaxed),
-            },
-            thread.itcs.uci_options().move_overhead,
-        ))
-        || *thread
-            .itcs
-            .timeout_flag
-            .read()
-            .expect("Reading posioned timeoutflag")
+    //Only stop on the node budget or the clock once there is a move to play. Until the next look
+    //each thread may search its share of the nodes left, so the budget is met without every node
+    //touching the shared counters
+    let has_move = thread.current_pv.pv.pv[0].is_some();
+    let node_limit_reached = match thread.limits.nodes {
+        Some(nodes) if thread.search_statistics.nodes_searched >= thread.node_checkup => {
+            thread.itcs.update(
+                thread.id,
+                thread.search_statistics.nodes_searched,
+                thread.search_statistics.seldepth,
+            );
+            let nodes_left = nodes.saturating_sub(thread.itcs.get_nodes_sum());
+            let share = nodes_left / thread.itcs.nodes_searched().len() as u64;
+            thread.node_checkup =
+                thread.search_statistics.nodes_searched + share.clamp(1, NODE_CHECKUP_INTERVAL);
+            nodes_left == 0 && has_move
+        }
+        _ => false,
+    };
+    if node_limit_reached
+        || (thread.id == 0
+            && has_move
+            && !thread.itcs.ponder.load(Ordering::Relaxed)
+            && thread.tc.time_over(
+                thread.itcs.get_time_elapsed(),
+                &TimeControlInformation {
+                    high_score_diff: false,
+                    time_saved: thread.time_saved,
+                    stable_pv: thread.itcs.stable_pv.load(Ordering::Relaxed),
+                },
+                thread.itcs.uci_options().move_overhead,
+            ))
+        || thread.cancellation.is_cancelled()
     {
-        if thread.id == 0 {
-            *thread
-                .itcs
-                .timeout_flag
-                .write()
-                .expect("Writing poisoned timeoutflag") = true;
+        if thread.id == 0 || node_limit_reached {
+            thread.cancellation.cancel();
         }
         thread.self_stop = true;
     }
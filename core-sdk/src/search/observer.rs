@@ -0,0 +1,125 @@
+use super::searcher::Output;
+use super::MATE_SCORE;
+use crate::board_representation::game_state::GameMove;
+use std::fmt::{Display, Formatter, Result};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Score {
+    //From the point of view of the side to move
+    Centipawns(i16),
+    //Moves until mate, negative if the side to move gets mated
+    Mate(i16),
+}
+
+impl From<i16> for Score {
+    fn from(score: i16) -> Self {
+        if score.abs() > MATE_SCORE - 200 {
+            Score::Mate(if score > 0 {
+                (MATE_SCORE - score) / 2 + 1
+            } else {
+                (-MATE_SCORE - score) / 2
+            })
+        } else {
+            Score::Centipawns(score)
+        }
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        match self {
+            Score::Centipawns(cp) => write!(formatter, "cp {}", cp),
+            Score::Mate(moves) => write!(formatter, "mate {}", moves),
+        }
+    }
+}
+
+//State of one line at the time it improved
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    pub depth: usize,
+    pub seldepth: usize,
+    //0 is the best line, the others follow when MultiPV is set
+    pub line: usize,
+    pub lines: usize,
+    pub nodes: u64,
+    pub nps: u64,
+    //Permill of the cache in use
+    pub hashfull: usize,
+    //In milliseconds
+    pub time: u64,
+    pub score: Score,
+    pub pv: Vec<GameMove>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: GameMove,
+    //The reply the search expects, if the principal variation reaches that far
+    pub ponder_move: Option<GameMove>,
+    pub score: Score,
+    pub pv: Vec<GameMove>,
+    pub depth: usize,
+    pub nodes: u64,
+    //In milliseconds
+    pub time: u64,
+}
+
+//Callbacks are made from the search threads, so they should return quickly
+pub trait SearchObserver: Send + Sync {
+    //Called whenever a line improves, which usually happens once per iteration
+    fn on_iteration(&self, _info: &SearchInfo) {}
+    //Called once the search is over. While pondering, that is only after ponderhit or stop
+    fn on_bestmove(&self, _result: &SearchResult) {}
+}
+
+//Reports the search as UCI info and bestmove lines. This is what every engine state starts with
+pub struct UciObserver {
+    output: Arc<Mutex<Output>>,
+}
+
+impl UciObserver {
+    pub(crate) fn new(output: Arc<Mutex<Output>>) -> Self {
+        UciObserver { output }
+    }
+}
+
+impl SearchObserver for UciObserver {
+    fn on_iteration(&self, info: &SearchInfo) {
+        let mut output = self.output.lock().unwrap();
+        //Only name the line if the GUI asked for more than one
+        let multi_pv_string = if info.lines > 1 {
+            format!(" multipv {}", info.line + 1)
+        } else {
+            String::new()
+        };
+        let pv_string: String = info.pv.iter().map(|mv| format!("{:?} ", mv)).collect();
+        writeln!(
+            output,
+            "info depth {} seldepth {}{} nodes {} nps {} hashfull {} time {} score {} pv {}",
+            info.depth,
+            info.seldepth,
+            multi_pv_string,
+            info.nodes,
+            info.nps,
+            info.hashfull,
+            info.time,
+            info.score,
+            pv_string
+        )
+        .expect("engine output write failed");
+        output.flush().expect("engine output write failed");
+    }
+
+    fn on_bestmove(&self, result: &SearchResult) {
+        let mut output = self.output.lock().unwrap();
+        write!(output, "bestmove {:?}", result.best_move).expect("engine output write failed");
+        if let Some(ponder_move) = result.ponder_move {
+            write!(output, " ponder {:?}", ponder_move).expect("engine output write failed");
+        }
+        writeln!(output).expect("engine output write failed");
+        output.flush().expect("engine output write failed");
+    }
+}
@@ -2,7 +2,6 @@ use super::super::board_representation::game_state::{
     GameMove, GameMoveType, GameResult, GameState, PieceType, BISHOP, BLACK, KING, KNIGHT, PAWN,
     QUEEN, ROOK, WHITE,
 };
-use super::super::evaluation::eval_game_state;
 use super::super::move_generation::movegen;
 use super::alphabeta::*;
 use super::*;
@@ -29,7 +28,11 @@ pub fn q_search(mut p: CombinedSearchParameters, thread: &mut Thread) -> i16 {
     }
 
     //Step 3. Check for draw
-    if let SearchInstruction::StopSearching(res) = check_for_draw(p.game_state, &thread.history) {
+    if let SearchInstruction::StopSearching(res) = check_for_draw(
+        p.game_state,
+        &thread.history,
+        thread.draw_score(p.game_state.color_to_move),
+    ) {
         return res;
     }
 
@@ -46,14 +49,12 @@ pub fn q_search(mut p: CombinedSearchParameters, thread: &mut Thread) -> i16 {
     //Step 5. Get standing pat when not in check
     let stand_pat = if !incheck {
         Some(
-            eval_game_state(
+            thread.evaluate(
                 &p.game_state,
-                &thread.attack_container.attack_containers[p.current_depth],
+                p.current_depth,
                 p.alpha * p.color,
                 p.beta * p.color,
-            )
-            .final_eval
-                * p.color,
+            ) * p.color,
         )
     } else {
         None
@@ -118,6 +119,8 @@ pub fn q_search(mut p: CombinedSearchParameters, thread: &mut Thread) -> i16 {
         gen_only_captures: !incheck,
         has_legal_move: false,
     };
+    #[cfg(feature = "search-statistics")]
+    let mut index: usize = 0;
 
     loop {
         let mv = move_orderer.next(thread, &p, None, tt_move);
@@ -136,7 +139,8 @@ pub fn q_search(mut p: CombinedSearchParameters, thread: &mut Thread) -> i16 {
             continue;
         }
         debug_assert!(incheck || capture_move.is_capture());
-        let next_g = make_move(p.game_state, capture_move);
+        let mut next_g = make_move(p.game_state, capture_move);
+        thread.update_accumulator(&mut next_g);
         //Step 8.4. Search move
         let score = -q_search(
             CombinedSearchParameters::from(
@@ -170,6 +174,10 @@ pub fn q_search(mut p: CombinedSearchParameters, thread: &mut Thread) -> i16 {
         if score > p.alpha {
             p.alpha = score;
         }
+        #[cfg(feature = "search-statistics")]
+        {
+            index += 1;
+        }
     }
 
     thread.history.pop();
@@ -183,7 +191,12 @@ pub fn q_search(mut p: CombinedSearchParameters, thread: &mut Thread) -> i16 {
     let game_status = check_end_condition(p.game_state, move_orderer.has_legal_move, incheck);
     if game_status != GameResult::Ingame {
         clear_pv(p.current_depth, thread);
-        return leaf_score(game_status, p.color, p.current_depth as i16);
+        return leaf_score(
+            game_status,
+            p.color,
+            p.current_depth as i16,
+            thread.draw_score(p.game_state.color_to_move),
+        );
     }
 
     //Step 10. Make TT entry
@@ -274,19 +287,17 @@ pub fn see(game_state: &GameState, mv: GameMove, exact: bool, gain: &mut Vec<i16
         | game_state.pieces[ROOK][BLACK]
         | game_state.pieces[QUEEN][WHITE]
         | game_state.pieces[QUEEN][BLACK];
-    let mut from_set = 1u64 << mv.from;
+    let mut from_set = 1u64 << mv.from.index();
     let mut occ = game_state.get_all_pieces();
-    let mut attadef = attacks_to(&game_state, mv.to as usize, occ);
-    gain[0] = capture_value(mv);
-    let mut color_to_move = game_state.color_to_move;
-    let mut attacked_piece = match mv.piece_type {
-        PieceType::Pawn => PAWN,
-        PieceType::Knight => KNIGHT,
-        PieceType::Bishop => BISHOP,
-        PieceType::Rook => ROOK,
-        PieceType::Queen => QUEEN,
-        PieceType::King => KING,
+    let mut attadef = attacks_to(&game_state, mv.to.index(), occ);
+    //Looking up the target square instead of the move type also covers quiet moves
+    gain[0] = match game_state.piece_on(mv.to) {
+        Some((piece, _)) => piece_value(piece),
+        None if mv.move_type == GameMoveType::EnPassant => PIECE_VALUES[PAWN],
+        None => 0,
     };
+    let mut color_to_move = game_state.color_to_move;
+    let mut attacked_piece = mv.piece_type.to_index();
     let mut index = 0;
     let mut deleted_pieces = 0u64;
     while from_set != 0u64 {
@@ -300,7 +311,7 @@ pub fn see(game_state: &GameState, mv: GameMove, exact: bool, gain: &mut Vec<i16
         occ ^= from_set;
         if from_set & may_xray != 0u64 {
             //Recalculate rays
-            attadef |= recalculate_sliders(&game_state, color_to_move, mv.to as usize, occ)
+            attadef |= recalculate_sliders(&game_state, color_to_move, mv.to.index(), occ)
                 & (!deleted_pieces);
         }
         color_to_move = 1 - color_to_move;
@@ -400,6 +411,7 @@ mod tests {
     use super::GameMoveType;
     use super::GameState;
     use super::PieceType;
+    use crate::board_representation::game_state::Square;
 
     #[test]
     fn see_test() {
@@ -408,8 +420,8 @@ mod tests {
             see(
                 &GameState::from_fen("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - -"),
                 GameMove {
-                    from: 4,
-                    to: 36,
+                    from: Square::E1,
+                    to: Square::E5,
                     move_type: GameMoveType::Capture(PieceType::Pawn),
                     piece_type: PieceType::Rook,
                 },
@@ -422,8 +434,8 @@ mod tests {
             see(
                 &GameState::from_fen("1k2r3/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - -"),
                 GameMove {
-                    from: 4,
-                    to: 36,
+                    from: Square::E1,
+                    to: Square::E5,
                     move_type: GameMoveType::Capture(PieceType::Pawn),
                     piece_type: PieceType::Rook,
                 },
@@ -436,8 +448,8 @@ mod tests {
             see(
                 &GameState::from_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - -"),
                 GameMove {
-                    from: 19,
-                    to: 36,
+                    from: Square::D3,
+                    to: Square::E5,
                     move_type: GameMoveType::Capture(PieceType::Pawn),
                     piece_type: PieceType::Knight,
                 },
@@ -450,8 +462,8 @@ mod tests {
             see(
                 &GameState::from_fen("1k1r3q/1ppn3p/p4b2/4n3/8/P2N2P1/1PP1R1BP/2K1Q3 w - -"),
                 GameMove {
-                    from: 19,
-                    to: 36,
+                    from: Square::D3,
+                    to: Square::E5,
                     move_type: GameMoveType::Capture(PieceType::Knight),
                     piece_type: PieceType::Knight,
                 },
@@ -464,8 +476,8 @@ mod tests {
             see(
                 &GameState::from_fen("1k1r2q1/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - -"),
                 GameMove {
-                    from: 19,
-                    to: 36,
+                    from: Square::D3,
+                    to: Square::E5,
                     move_type: GameMoveType::Capture(PieceType::Pawn),
                     piece_type: PieceType::Knight,
                 },
@@ -478,8 +490,8 @@ mod tests {
             see(
                 &GameState::from_fen("8/8/3p4/4r3/2RKP3/5k2/8/8 b - -"),
                 GameMove {
-                    from: 36,
-                    to: 28,
+                    from: Square::E5,
+                    to: Square::E4,
                     move_type: GameMoveType::Capture(PieceType::Pawn),
                     piece_type: PieceType::Rook,
                 },
@@ -492,8 +504,8 @@ mod tests {
             see(
                 &GameState::from_fen("k7/8/5q2/8/3r4/2KQ4/8/8 w - -"),
                 GameMove {
-                    from: 19,
-                    to: 27,
+                    from: Square::D3,
+                    to: Square::D4,
                     move_type: GameMoveType::Capture(PieceType::Rook),
                     piece_type: PieceType::Queen,
                 },
@@ -506,8 +518,8 @@ mod tests {
             see(
                 &GameState::from_fen("8/8/5q2/2k5/3r4/2KQ4/8/8 w - -"),
                 GameMove {
-                    from: 19,
-                    to: 27,
+                    from: Square::D3,
+                    to: Square::D4,
                     move_type: GameMoveType::Capture(PieceType::Rook),
                     piece_type: PieceType::Queen,
                 },
@@ -520,8 +532,8 @@ mod tests {
             see(
                 &GameState::from_fen("4pq2/3P4/8/8/8/8/8/k1K5 w - -"),
                 GameMove {
-                    from: 51,
-                    to: 60,
+                    from: Square::D7,
+                    to: Square::E8,
                     move_type: GameMoveType::Promotion(PieceType::Queen, Some(PieceType::Pawn)),
                     piece_type: PieceType::Pawn,
                 },
@@ -534,8 +546,8 @@ mod tests {
             see(
                 &GameState::from_fen("4pq2/3P4/2B5/8/8/8/8/k1K5 w - -"),
                 GameMove {
-                    from: 51,
-                    to: 60,
+                    from: Square::D7,
+                    to: Square::E8,
                     move_type: GameMoveType::Promotion(PieceType::Queen, Some(PieceType::Pawn)),
                     piece_type: PieceType::Pawn,
                 },
@@ -1,8 +1,12 @@
-use super::alphabeta::principal_variation_search;
+use super::alphabeta::{
+    principal_variation_search, DEFAULT_SINGULAR_DEPTH, DEFAULT_SINGULAR_MARGIN,
+};
 use super::cache::Cache;
 use super::history::History;
+use super::observer::{SearchInfo, SearchObserver, SearchResult, UciObserver};
+use super::skill::{Skill, SKILL_CANDIDATES};
 use super::statistics::SearchStatistics;
-use super::timecontrol::TimeControl;
+use super::timecontrol::{SearchLimits, TimeControl};
 use super::GameMove;
 use super::PrincipalVariation;
 use super::MATED_IN_MAX;
@@ -10,6 +14,11 @@ use super::MAX_SEARCH_DEPTH;
 use crate::board_representation::game_state::{GameState, WHITE};
 //use crate::logging::log;
 use crate::board_representation::game_state_attack_container::GameStateAttackContainer;
+use crate::evaluation::eval_game_state_with_pawn_hash;
+use crate::evaluation::eval_params::EvalParams;
+#[cfg(feature = "nnue")]
+use crate::evaluation::nnue::Network;
+use crate::evaluation::pawn_hash::PawnHashTable;
 use crate::move_generation::makemove::make_move;
 use crate::move_generation::movegen::{generate_moves, MoveList};
 use crate::search::reserved_memory::{ReservedAttackContainer, ReservedMoveList};
@@ -20,11 +29,10 @@ use std::io::{self, Write, BufWriter};
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
-#[allow(unused)]
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex, MutexGuard, RwLock};
-#[allow(unused)]
 use std::thread;
+use std::time::Duration;
 
 // FIXME expects used for error handling of writeln
 #[cfg(not(target_arch = "wasm32"))]
@@ -47,7 +55,15 @@ pub const DEFAULT_THREADS: usize = 1;
 pub const MAX_THREADS: usize = 65536;
 pub const MIN_THREADS: usize = 1;
 
-pub(crate) type Output = BufWriter<Box<dyn io::Write>>;
+pub const DEFAULT_CONTEMPT: i16 = 0;
+pub const MIN_CONTEMPT: i16 = -100;
+pub const MAX_CONTEMPT: i16 = 100;
+
+pub const DEFAULT_MULTI_PV: usize = 1;
+pub const MIN_MULTI_PV: usize = 1;
+pub const MAX_MULTI_PV: usize = 256;
+
+pub(crate) type Output = BufWriter<Box<dyn io::Write + Send>>;
 
 
 #[derive(Copy, Clone)]
@@ -58,8 +74,11 @@ pub enum DepthInformation {
 }
 pub struct InterThreadCommunicationSystem {
     pub uci_options: UnsafeCell<UCIOptions>,
-    pub best_pv: Mutex<ScoredPrincipalVariation>,
+    //Best line of the search first, followed by the best lines excluding the moves found before
+    pub multi_pv: Mutex<Vec<ScoredPrincipalVariation>>,
     pub stable_pv: AtomicBool,
+    //Set while searching on the opponent's time. The clock only counts from ponderhit on
+    pub ponder: AtomicBool,
     pub depth_info: Mutex<[DepthInformation; MAX_SEARCH_DEPTH]>,
     pub start_time: RwLock<Instant>, //Only used for reporting
     pub nodes_searched: UnsafeCell<Vec<AtomicU64>>, // Only used for reporting
@@ -67,40 +86,40 @@ pub struct InterThreadCommunicationSystem {
     pub cache: UnsafeCell<Cache>,    //Only used for reporting
     pub cache_status: AtomicUsize,
     pub last_cache_status: Mutex<Option<Instant>>,
-    pub timeout_flag: RwLock<bool>,
     pub saved_time: AtomicU64,
+    //One channel per helper thread, the main thread is the one calling search_move
     pub tx: RwLock<Vec<Sender<ThreadInstruction>>>,
-    pub output: Mutex<Output>,
+    //Helpers signal here whenever they finished a search or exited
+    pub tx_f: Mutex<Sender<()>>,
+    pub rx_f: Mutex<Receiver<()>>,
+    pub output: Arc<Mutex<Output>>,
+    pub observer: RwLock<Arc<dyn SearchObserver>>,
+    //Loaded evaluation parameters, without the compiled-in ones are used. Threads take them over
+    //when a search starts
+    pub eval_params: RwLock<Option<Arc<EvalParams>>>,
+    //The network evaluating instead of the classical evaluation, taken over the same way
+    #[cfg(feature = "nnue")]
+    pub network: RwLock<Option<Arc<Network>>>,
+    //The pawn hash table of the main thread, kept from one search to the next like the ones of
+    //the helpers. Counting the clears lets the helpers notice them when the next search starts
+    pub pawn_hash: Mutex<Option<PawnHashTable>>,
+    pub pawn_hash_clears: AtomicUsize,
 }
 
 impl Default for InterThreadCommunicationSystem {
     fn default() -> Self {
-        // let (tx_f, rx_f) = channel();
-        let output = Box::new(io::stdout());
-        InterThreadCommunicationSystem {
-            uci_options: UnsafeCell::new(UCIOptions::default()),
-            best_pv: Mutex::new(ScoredPrincipalVariation::default()),
-            stable_pv: AtomicBool::new(false),
-            depth_info: Mutex::new([DepthInformation::UnSearched; MAX_SEARCH_DEPTH]),
-            nodes_searched: UnsafeCell::new(Vec::new()),
-            seldepth: AtomicUsize::new(0),
-            start_time: RwLock::new(Instant::now()),
-            last_cache_status: Mutex::new(None),
-            cache_status: AtomicUsize::new(0),
-            cache: UnsafeCell::new(Cache::with_size_threaded(0, 1)),
-            timeout_flag: RwLock::new(false),
-            saved_time: AtomicU64::new(0u64),
-            tx: RwLock::new(Vec::new()),
-            output: Mutex::new(BufWriter::new(output)),
-        }
+        InterThreadCommunicationSystem::default_with_output(io::stdout())
     }
 }
 impl InterThreadCommunicationSystem {
-    pub fn default_with_output<O: Write + 'static>(output: O) -> Self {
+    pub fn default_with_output<O: Write + Send + 'static>(output: O) -> Self {
+        let (tx_f, rx_f) = channel();
+        let output: Arc<Mutex<Output>> = Arc::new(Mutex::new(BufWriter::new(Box::new(output))));
         InterThreadCommunicationSystem {
             uci_options: UnsafeCell::new(UCIOptions::default()),
-            best_pv: Mutex::new(ScoredPrincipalVariation::default()),
+            multi_pv: Mutex::new(vec![ScoredPrincipalVariation::default()]),
             stable_pv: AtomicBool::new(false),
+            ponder: AtomicBool::new(false),
             depth_info: Mutex::new([DepthInformation::UnSearched; MAX_SEARCH_DEPTH]),
             nodes_searched: UnsafeCell::new(Vec::new()),
             seldepth: AtomicUsize::new(0),
@@ -108,10 +127,17 @@ impl InterThreadCommunicationSystem {
             last_cache_status: Mutex::new(None),
             cache_status: AtomicUsize::new(0),
             cache: UnsafeCell::new(Cache::with_size_threaded(0, 1)),
-            timeout_flag: RwLock::new(false),
             saved_time: AtomicU64::new(0u64),
             tx: RwLock::new(Vec::new()),
-            output: Mutex::new(BufWriter::new(Box::new(output))),
+            tx_f: Mutex::new(tx_f),
+            rx_f: Mutex::new(rx_f),
+            observer: RwLock::new(Arc::new(UciObserver::new(Arc::clone(&output)))),
+            eval_params: RwLock::new(None),
+            #[cfg(feature = "nnue")]
+            network: RwLock::new(None),
+            pawn_hash: Mutex::new(None),
+            pawn_hash_clears: AtomicUsize::new(0),
+            output,
         }
     }
 
@@ -129,36 +155,49 @@ impl InterThreadCommunicationSystem {
         itcs: &Arc<InterThreadCommunicationSystem>,
         new_thread_count: usize,
     ) {
-        // for tx in itcs.tx.read().unwrap().iter() {
-        //     tx.send(ThreadInstruction::Exit)
-        //         .expect("couldn't send exit flag");
-        // }
-        // for _ in 0..itcs.tx.read().unwrap().len() {
-        //     itcs.rx_f.recv().expect("Couldn't receive exit flag!")
-        // }
-        itcs.uci_options().threads = new_thread_count;
+        //wasm32 can't spawn threads, so everything is searched by the main thread there
+        let new_thread_count = if cfg!(target_arch = "wasm32") {
+            1
+        } else {
+            new_thread_count
+        };
+
         let itcs_tx = &mut *itcs.tx.write().unwrap();
+        for tx in itcs_tx.iter() {
+            tx.send(ThreadInstruction::Exit)
+                .expect("couldn't send exit flag");
+        }
+        let rx_f = itcs.rx_f.lock().unwrap();
+        for _ in 0..itcs_tx.len() {
+            rx_f.recv().expect("Couldn't receive exit flag!")
+        }
+        itcs.uci_options().threads = new_thread_count;
         let itcs_nodes_searched = itcs.nodes_searched();
         *itcs_tx = Vec::with_capacity(new_thread_count);
         *itcs_nodes_searched = Vec::with_capacity(new_thread_count);
-        for _ in 0..new_thread_count {
+        for id in 0..new_thread_count {
             itcs_nodes_searched.push(AtomicU64::new(0));
-            //     let (tx, rx) = channel();
-            //     itcs_tx.push(tx);
-            //     let tx_f = itcs.tx_f.clone();
-            //     let self_arc = Arc::clone(&itcs);
-            //     thread::spawn(move ||
-            //     {
-            //         let mut thread = Thread::new(id, self_arc, rx, tx_f);
-            //         thread.run();
-            //     };
+            if id == 0 {
+                continue;
+            }
+            let (tx, rx) = channel();
+            itcs_tx.push(tx);
+            let tx_f = itcs.tx_f.lock().unwrap().clone();
+            let self_arc = Arc::clone(itcs);
+            thread::Builder::new()
+                .name(format!("search thread {}", id))
+                .spawn(move || {
+                    let mut thread = Thread::new(id, self_arc, PawnHashTable::default());
+                    thread.run(rx, tx_f);
+                })
+                .expect("Couldn't spawn search thread!");
         }
     }
 
     pub fn get_time_elapsed(&self) -> u64 {
         let now = Instant::now();
         let dur = now.duration_since(*self.start_time.read().unwrap());
-        (dur.as_secs() as u64) * 1000
+        dur.as_millis() as u64
     }
 
     pub fn update(&self, thread_id: usize, nodes_searched: u64, seldepth: usize) {
@@ -179,13 +218,24 @@ impl InterThreadCommunicationSystem {
         self.output.lock().unwrap()
     }
 
-    pub fn register_pv(&self, scored_pv: &ScoredPrincipalVariation, no_fail: bool) {
-        let mut curr_best = self.best_pv.lock().unwrap();
-        self.stable_pv.store(false, Ordering::Relaxed);
-        //Update pv stability
-        if let Some(other_mv) = curr_best.pv.pv[0] {
-            if other_mv == scored_pv.pv.pv[0].unwrap() && no_fail {
-                self.stable_pv.store(true, Ordering::Relaxed);
+    pub fn register_pv(
+        &self,
+        scored_pv: &ScoredPrincipalVariation,
+        no_fail: bool,
+        pv_index: usize,
+    ) {
+        let mut lines = self.multi_pv.lock().unwrap();
+        if lines.len() <= pv_index {
+            lines.resize(pv_index + 1, ScoredPrincipalVariation::default());
+        }
+        let curr_best = &mut lines[pv_index];
+        if pv_index == 0 {
+            self.stable_pv.store(false, Ordering::Relaxed);
+            //Update pv stability
+            if let Some(other_mv) = curr_best.pv.pv[0] {
+                if other_mv == scored_pv.pv.pv[0].unwrap() && no_fail {
+                    self.stable_pv.store(true, Ordering::Relaxed);
+                }
             }
         }
         if curr_best.depth < scored_pv.depth
@@ -194,58 +244,98 @@ impl InterThreadCommunicationSystem {
             if no_fail {
                 *curr_best = scored_pv.clone();
             }
-            //Report to UCI
+            //Candidates of a weakened search beyond the MultiPV lines stay hidden
+            if pv_index >= self.uci_options().multi_pv {
+                return;
+            }
+            //Report to the observer
             let searched_nodes: u64 = self.get_nodes_sum();
             let elapsed_time = self.get_time_elapsed();
-            let fill_status = self.cache().fill_status();
-            //if cache_status.is_none()
-                // || Instant::now()
-                //     .duration_since(cache_status.unwrap())
-                //     .as_millis()
-                //     > 200
-            // {
-            //     *cache_status = Some(Instant::now());
-            //     self.cache_status
-            //         .store(, Ordering::Relaxed);
-            //     self.cache_status.load(Ordering::Relaxed)
-            // } else {
-            //     self.cache_status.load(Ordering::Relaxed)
-            // };
-            let score_string = if scored_pv.score.abs() > MATE_SCORE - 200 {
-                let dtm = if scored_pv.score > 0 {
-                    (MATE_SCORE - scored_pv.score) / 2 + 1
-                } else {
-                    (-MATE_SCORE - scored_pv.score) / 2
-                };
-                format!("score mate {}", dtm)
-            } else {
-                format!("score cp {}", scored_pv.score)
+            let info = SearchInfo {
+                depth: scored_pv.depth,
+                seldepth: self.seldepth.load(Ordering::Relaxed),
+                line: pv_index,
+                lines: self.uci_options().multi_pv,
+                nodes: searched_nodes,
+                nps: (searched_nodes as f64 / (elapsed_time.max(1) as f64 / 1000.0)) as u64,
+                hashfull: self.cache().fill_status(),
+                time: elapsed_time,
+                score: scored_pv.score.into(),
+                pv: scored_pv.pv.moves(),
             };
-            writeln!(
-                self.output(),
-                "info depth {} seldepth {} nodes {} nps {} hashfull {:.0} time {} {} pv {}",
-                scored_pv.depth,
-                self.seldepth.load(Ordering::Relaxed),
-                searched_nodes,
-                (searched_nodes as f64 / (elapsed_time.max(1) as f64 / 1000.0)) as u64,
-                fill_status,
-                self.get_time_elapsed(),
-                score_string,
-                scored_pv.pv
-            )
-            .expect("engine output write failed");
+            //The lines are released first, as observers may look at them
+            drop(lines);
+            self.observer().on_iteration(&info);
+        }
+    }
+
+    pub fn observer(&self) -> Arc<dyn SearchObserver> {
+        Arc::clone(&self.observer.read().unwrap())
+    }
+
+    pub fn eval_params(&self) -> Option<Arc<EvalParams>> {
+        self.eval_params.read().unwrap().clone()
+    }
+
+    //Only takes effect from the next search on
+    pub fn set_eval_params(&self, params: Option<EvalParams>) {
+        *self.eval_params.write().unwrap() = params.map(Arc::new);
+        //The stored pawn structures were scored with the old parameters
+        self.clear_pawn_hash();
+    }
+
+    //Also takes effect from the next search on
+    pub fn clear_pawn_hash(&self) {
+        *self.pawn_hash.lock().unwrap() = None;
+        self.pawn_hash_clears.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "nnue")]
+    pub fn network(&self) -> Option<Arc<Network>> {
+        self.network.read().unwrap().clone()
+    }
+
+    //Only takes effect from the next search on
+    #[cfg(feature = "nnue")]
+    pub fn set_network(&self, network: Option<Network>) {
+        *self.network.write().unwrap() = network.map(Arc::new);
+    }
+
+    //The MultiPV lines, plus the candidates to pick from if the strength is limited
+    pub fn searched_lines(&self) -> usize {
+        let multi_pv = self.uci_options().multi_pv;
+        if Skill::from_options(self.uci_options()).is_some() {
+            multi_pv.max(SKILL_CANDIDATES)
+        } else {
+            multi_pv
         }
     }
 
-    pub fn report_bestmove(&self) {
-        writeln!(
-            self.output(),
-            "bestmove {:?}",
-            self.best_pv.lock().unwrap().pv.pv[0]
-                .as_ref()
-                .expect("Could not unwrap pv for bestmove!")
-        )
-        .expect("engine output write failed");
+    pub fn search_result(&self, line: usize) -> SearchResult {
+        let lines = self.multi_pv.lock().unwrap();
+        let pv = lines[line].pv.moves();
+        SearchResult {
+            best_move: *pv.first().expect("Could not unwrap pv for bestmove!"),
+            ponder_move: pv.get(1).copied(),
+            score: lines[line].score.into(),
+            depth: lines[line].depth,
+            pv,
+            nodes: self.get_nodes_sum(),
+            time: self.get_time_elapsed(),
+        }
+    }
+
+    //The opponent played the move we pondered on, so the search goes on with the real clock
+    pub fn ponderhit(&self) {
+        *self.start_time.write().unwrap() = Instant::now();
+        self.ponder.store(false, Ordering::Relaxed);
+    }
+
+    //While pondering, the bestmove may only be sent after ponderhit or stop
+    pub fn wait_while_pondering(&self) {
+        while self.ponder.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(1));
+        }
     }
 
     pub fn get_next_depth(&self, mut from_depth: usize) -> (usize, bool) {
@@ -285,9 +375,37 @@ impl InterThreadCommunicationSystem {
     }
 }
 unsafe impl std::marker::Sync for InterThreadCommunicationSystem {}
+
+//Stops one search. Every thread taking part in it holds a clone, so searches on other engine
+//states are not affected
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+//Everything a helper thread needs to join a search
+pub struct SearchInstruction {
+    pub max_depth: i16,
+    pub state: GameState,
+    pub tc: TimeControl,
+    pub limits: SearchLimits,
+    pub history: History,
+    pub time_saved: u64,
+    pub cancellation: CancellationToken,
+}
+
 pub enum ThreadInstruction {
     Exit,
-    StartSearch(i16, GameState, TimeControl, History, u64),
+    //Boxed, the instruction is far larger than Exit
+    StartSearch(Box<SearchInstruction>),
 }
 
 pub struct Thread {
@@ -304,13 +422,33 @@ pub struct Thread {
     pub bf_score: [[[usize; 64]; 64]; 2],
     pub history_score: [[[isize; 64]; 64]; 2],
     pub see_buffer: Vec<i16>,
+    pub pawn_hash: PawnHashTable,
+    pub pawn_hash_clears: usize, //Clears of the pawn hash tables seen so far
+    pub eval_params: Option<Arc<EvalParams>>,
+    #[cfg(feature = "nnue")]
+    pub network: Option<Arc<Network>>,
     pub search_statistics: SearchStatistics,
     pub tc: TimeControl, //Only thread 0 takes care of Timecontrol though
+    pub limits: SearchLimits,
+    pub node_checkup: u64, //Own node count at which the node budget is looked at next
     pub time_saved: u64,
-    pub self_stop: bool, //This is set when timeout_stop is set(timeout_stop isn't always polled)
+    pub cancellation: CancellationToken,
+    pub self_stop: bool, //This is set once the search is cancelled(cancellation isn't always polled)
     pub current_pv: ScoredPrincipalVariation,
+    //Index of the MultiPV line searched right now and the lines found by this thread
+    pub pv_index: usize,
+    pub pv_lines: Vec<ScoredPrincipalVariation>,
+    //Root moves leading the lines before pv_index, which the root search skips
+    pub excluded_root_moves: Vec<GameMove>,
     pub pv_applicable: Vec<u64>, //Hashes of gamestates the pv plays along
     pub main_thread_in_depth: bool,
+    //Score of a draw for each side to move. With contempt the root side avoids draws
+    pub draw_scores: [i16; 2],
+    //The TT move a singular search leaves out, by ply
+    pub excluded_moves: [Option<GameMove>; MAX_SEARCH_DEPTH],
+    pub singular_extensions: bool,
+    pub singular_depth: i16,
+    pub singular_margin: i16,
 }
 
 impl Thread {
@@ -324,7 +462,13 @@ impl Thread {
         scored_pv: ScoredPrincipalVariation,
         no_fail: bool,
     ) {
-        self.itcs.register_pv(&scored_pv, no_fail);
+        self.itcs.register_pv(&scored_pv, no_fail, self.pv_index);
+        if self.pv_index > 0 {
+            //Move ordering only follows the best line
+            self.pv_lines[self.pv_index] = scored_pv;
+            return;
+        }
+        self.pv_lines[0] = scored_pv.clone();
         self.current_pv = scored_pv;
         self.pv_applicable.clear();
         self.pv_applicable.push(root.hash);
@@ -342,7 +486,69 @@ impl Thread {
             }
         }
     }
-    fn new(id: usize, itcs: Arc<InterThreadCommunicationSystem>) -> Self {
+    //Static evaluation from white's point of view, with the attacks written for the given ply
+    #[inline(always)]
+    pub fn evaluate(&mut self, g: &GameState, current_depth: usize, alpha: i16, beta: i16) -> i16 {
+        //The network replaces the classical evaluation once one is loaded
+        #[cfg(feature = "nnue")]
+        {
+            if let Some(network) = &self.network {
+                let eval = network.evaluate_state(g);
+                return if g.color_to_move == WHITE {
+                    eval
+                } else {
+                    -eval
+                };
+            }
+        }
+        #[cfg_attr(not(feature = "search-statistics"), allow(unused_variables))]
+        let (result, pawn_hash_hit) = eval_game_state_with_pawn_hash(
+            g,
+            &self.attack_container.attack_containers[current_depth],
+            self.eval_params.as_deref(),
+            &mut self.pawn_hash,
+            alpha,
+            beta,
+        );
+        #[cfg(feature = "search-statistics")]
+        self.search_statistics.add_pawn_hash_probe(pawn_hash_hit);
+        result.final_eval
+    }
+
+    //Has to be called on positions the search goes on from, so that the network accumulators of
+    //the positions after them are updated incrementally
+    #[inline(always)]
+    pub fn update_accumulator(&self, _g: &mut GameState) {
+        #[cfg(feature = "nnue")]
+        {
+            if let Some(network) = &self.network {
+                network.update(_g);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn draw_score(&self, color_to_move: usize) -> i16 {
+        self.draw_scores[color_to_move]
+    }
+
+    //Root moves which are not searched, because they aren't among the searchmoves or lead a
+    //better MultiPV line
+    pub fn is_excluded_root_move(&self, mv: GameMove) -> bool {
+        self.excluded_root_moves.contains(&mv)
+            || !self.limits.search_moves.is_empty() && !self.limits.search_moves.contains(&mv)
+    }
+
+    pub fn root_moves_restricted(&self) -> bool {
+        !self.excluded_root_moves.is_empty() || !self.limits.search_moves.is_empty()
+    }
+
+    pub fn new(
+        id: usize,
+        itcs: Arc<InterThreadCommunicationSystem>,
+        pawn_hash: PawnHashTable,
+    ) -> Self {
+        let pawn_hash_clears = itcs.pawn_hash_clears.load(Ordering::Relaxed);
         let mut pv_table = Vec::with_capacity(MAX_SEARCH_DEPTH);
         for i in 0..MAX_SEARCH_DEPTH {
             pv_table.push(PrincipalVariation::new(MAX_SEARCH_DEPTH - i));
@@ -361,13 +567,29 @@ impl Thread {
             bf_score: [[[1; 64]; 64]; 2],
             history_score: [[[0; 64]; 64]; 2],
             see_buffer: vec![0i16; MAX_SEARCH_DEPTH],
+            pawn_hash,
+            pawn_hash_clears,
+            eval_params: None,
+            #[cfg(feature = "nnue")]
+            network: None,
             search_statistics: SearchStatistics::default(),
             tc: TimeControl::MoveTime(0u64),
+            limits: SearchLimits::default(),
+            node_checkup: u64::MAX,
             time_saved: 0u64,
+            cancellation: CancellationToken::default(),
             self_stop: false,
             current_pv: ScoredPrincipalVariation::default(),
+            pv_index: 0,
+            pv_lines: vec![ScoredPrincipalVariation::default()],
+            excluded_root_moves: Vec::new(),
             pv_applicable: Vec::with_capacity(MAX_SEARCH_DEPTH),
             main_thread_in_depth: false,
+            draw_scores: [0; 2],
+            excluded_moves: [None; MAX_SEARCH_DEPTH],
+            singular_extensions: false,
+            singular_depth: DEFAULT_SINGULAR_DEPTH,
+            singular_margin: DEFAULT_SINGULAR_MARGIN,
         }
     }
 
@@ -376,6 +598,7 @@ impl Thread {
         max_depth: i16,
         state: GameState,
         tc: TimeControl,
+        limits: SearchLimits,
         history: History,
         time_saved: u64,
     ) {
@@ -389,27 +612,135 @@ impl Thread {
         self.hh_score = [[[0; 64]; 64]; 2];
         self.bf_score = [[[1; 64]; 64]; 2];
         self.history_score = [[[0; 64]; 64]; 2];
+        //The evaluation parameters may have changed since the last search
+        self.eval_params = self.itcs.eval_params();
+        #[cfg(feature = "nnue")]
+        {
+            self.network = self.itcs.network();
+        }
+        let pawn_hash_clears = self.itcs.pawn_hash_clears.load(Ordering::Relaxed);
+        if pawn_hash_clears != self.pawn_hash_clears {
+            self.pawn_hash.clear();
+            self.pawn_hash_clears = pawn_hash_clears;
+        }
         self.search_statistics = SearchStatistics::default();
         self.tc = tc;
+        self.node_checkup = if limits.nodes.is_some() { 0 } else { u64::MAX };
+        self.limits = limits;
         self.self_stop = false;
+        //Analysis has to treat both sides alike
+        let contempt = if self.itcs.uci_options().analyse_mode {
+            0
+        } else {
+            self.itcs.uci_options().contempt
+        };
+        self.draw_scores[state.color_to_move] = -contempt;
+        self.draw_scores[1 - state.color_to_move] = contempt;
+        self.singular_extensions = self.itcs.uci_options().singular_extensions;
+        self.singular_depth = self.itcs.uci_options().singular_depth;
+        self.singular_margin = self.itcs.uci_options().singular_margin;
         self.search(max_depth, state);
-        // self.tx.send(()).expect("Error sending finish flag!");
-    }
-
-    // fn run(&mut self) {
-    //     loop {
-    //         let msg: ThreadInstruction = self.rx.try_recv().unwrap();
-    //         match msg {
-    //             ThreadInstruction::Exit => {
-    //                 self.tx.send(()).expect("Error sending exit flag!");
-    //                 break;
-    //             }
-    //             ThreadInstruction::StartSearch(max_depth, state, tc, history, time_saved) => {
-    //                 self.start_search(max_depth, state, tc, history, time_saved)
-    //             }
-    //         }
-    //     }
-    // }
+    }
+
+    //Loop of a helper thread, waiting for instructions sent by search_move
+    fn run(&mut self, rx: Receiver<ThreadInstruction>, tx_f: Sender<()>) {
+        loop {
+            let msg: ThreadInstruction = rx.recv().expect("Couldn't receive thread instruction!");
+            match msg {
+                ThreadInstruction::Exit => {
+                    tx_f.send(()).expect("Error sending exit flag!");
+                    break;
+                }
+                ThreadInstruction::StartSearch(instruction) => {
+                    let SearchInstruction {
+                        max_depth,
+                        state,
+                        tc,
+                        limits,
+                        history,
+                        time_saved,
+                        cancellation,
+                    } = *instruction;
+                    self.cancellation = cancellation;
+                    self.start_search(max_depth, state, tc, limits, history, time_saved);
+                    tx_f.send(()).expect("Error sending finish flag!");
+                }
+            }
+        }
+    }
+
+    //Searches the root in a window around the last score of the line, widening it until the
+    //score falls inside
+    fn aspiration_window(
+        &mut self,
+        curr_depth: usize,
+        state: &GameState,
+        previous_score: Option<i16>,
+    ) {
+        if self.itcs.uci_options().debug_print {
+            writeln!(
+                self.output(),
+                "info String Thread {} starting aspiration window with depth {}",
+                self.id,
+                curr_depth
+            )
+            .expect("engine output write failed");
+        }
+        let mut delta = if let Some(ps) = previous_score {
+            ps.abs() / 50
+        } else {
+            0
+        } + 14;
+        let mut alpha = if curr_depth == 1 {
+            -16000
+        } else {
+            self.pv_lines[self.pv_index].score - delta
+        };
+        let mut beta = if curr_depth == 1 {
+            16000
+        } else {
+            self.pv_lines[self.pv_index].score + delta
+        };
+        loop {
+            principal_variation_search(
+                CombinedSearchParameters::from(
+                    alpha,
+                    beta,
+                    curr_depth as i16,
+                    state,
+                    if state.color_to_move == WHITE { 1 } else { -1 },
+                    0,
+                ),
+                self,
+            );
+            let score = self.pv_lines[self.pv_index].score;
+            if self.self_stop {
+                break;
+            }
+            if score > alpha && score < beta {
+                break;
+            }
+
+            if score <= alpha {
+                if alpha < -10000 || score < MATED_IN_MAX {
+                    alpha = -16000;
+                    beta = 16000;
+                } else {
+                    beta = (alpha + beta) / 2;
+                    alpha -= delta;
+                }
+            }
+            if score >= beta {
+                if beta > 10000 || score > -MATED_IN_MAX {
+                    beta = 16000;
+                    alpha = -16000;
+                } else {
+                    beta += delta;
+                }
+            }
+            delta = (f64::from(delta) * 1.5) as i16;
+        }
+    }
 
     fn search(&mut self, max_depth: i16, state: GameState) {
         if self.itcs.uci_options().debug_print {
@@ -421,7 +752,22 @@ impl Thread {
             .expect("engine output write failed");
         }
         let mut curr_depth = 0;
-        let mut previous_score: Option<i16> = None;
+        //There can't be more lines than legal moves at the root
+        let mut root_moves = MoveList::default();
+        generate_moves(
+            &state,
+            false,
+            &mut root_moves,
+            &GameStateAttackContainer::from_state(&state),
+        );
+        let searchable_moves = if self.limits.search_moves.is_empty() {
+            root_moves.move_list.len()
+        } else {
+            self.limits.search_moves.len()
+        };
+        let lines = self.itcs.searched_lines().min(searchable_moves).max(1);
+        self.pv_lines = vec![ScoredPrincipalVariation::default(); lines];
+        let mut previous_scores: Vec<Option<i16>> = vec![None; lines];
         loop {
             let temp = self.itcs.get_next_depth(curr_depth);
             curr_depth = temp.0;
@@ -429,74 +775,32 @@ impl Thread {
             if curr_depth as i16 > max_depth {
                 break;
             }
-            //Start Aspiration Window
-            if self.itcs.uci_options().debug_print {
-                writeln!(
-                    self.output(),
-                    "info String Thread {} starting aspiration window with depth {}",
-                    self.id,
-                    curr_depth
-                )
-                .expect("engine output write failed");
-            }
-            let mut delta = if let Some(ps) = previous_score {
-                ps.abs() / 50
-            } else {
-                0
-            } + 14;
-            let mut alpha = if curr_depth == 1 {
-                -16000
-            } else {
-                self.current_pv.score - delta
-            };
-            let mut beta = if curr_depth == 1 {
-                16000
-            } else {
-                self.current_pv.score + delta
-            };
-            loop {
-                principal_variation_search(
-                    CombinedSearchParameters::from(
-                        alpha,
-                        beta,
-                        curr_depth as i16,
-                        &state,
-                        if state.color_to_move == WHITE { 1 } else { -1 },
-                        0,
-                    ),
-                    self,
-                );
+            for (pv_index, previous_score) in previous_scores.iter_mut().enumerate() {
+                self.pv_index = pv_index;
+                self.excluded_root_moves = self.pv_lines[..pv_index]
+                    .iter()
+                    .filter_map(|line| line.pv.pv[0])
+                    .collect();
+                self.aspiration_window(curr_depth, &state, *previous_score);
+                *previous_score = Some(self.pv_lines[pv_index].score);
                 if self.self_stop {
                     break;
                 }
-                if self.current_pv.score > alpha && self.current_pv.score < beta {
-                    break;
-                }
-
-                if self.current_pv.score <= alpha {
-                    if alpha < -10000 || self.current_pv.score < MATED_IN_MAX {
-                        alpha = -16000;
-                        beta = 16000;
-                    } else {
-                        beta = (alpha + beta) / 2;
-                        alpha -= delta;
-                    }
-                }
-                if self.current_pv.score >= beta {
-                    if beta > 10000 || self.current_pv.score > -MATED_IN_MAX {
-                        beta = 16000;
-                        alpha = -16000;
-                    } else {
-                        beta += delta;
-                    }
-                }
-                delta = (f64::from(delta) * 1.5) as i16;
             }
-            previous_score = Some(self.current_pv.score);
             if self.self_stop {
                 break;
             }
+            if let Some(mate) = self.limits.mate {
+                //A mate in n moves is delivered on ply 2n - 1, no deeper than the search goes
+                let mate_ply = (2 * mate.min(MAX_SEARCH_DEPTH)).saturating_sub(1) as i16;
+                if self.pv_lines[0].score >= MATE_SCORE - mate_ply {
+                    self.cancellation.cancel();
+                    break;
+                }
+            }
         }
+        self.pv_index = 0;
+        self.excluded_root_moves.clear();
         if self.itcs.uci_options().debug_print {
             writeln!(
                 self.output(),
@@ -512,11 +816,7 @@ impl Thread {
             self.search_statistics.seldepth,
         );
         if self.id == 0 {
-            *self
-                .itcs
-                .timeout_flag
-                .write()
-                .expect("Couldn't write to timeout flag") = true;
+            self.cancellation.cancel();
         }
     }
 }
@@ -527,9 +827,20 @@ pub fn search_move(
     game_state: GameState,
     history: Vec<GameState>,
     tc: TimeControl,
-) -> Option<i16> {
+    mut limits: SearchLimits,
+    cancellation: CancellationToken,
+) -> SearchResult {
+    //A weakened search looks less deep and picks among several root moves in the end
+    let skill = Skill::from_options(itcs.uci_options());
+    let max_depth = match skill {
+        Some(skill) => max_depth.min(skill.max_depth() as i16),
+        None => max_depth,
+    };
+    if let Some(skill) = skill {
+        limits.nodes = Some(limits.nodes.unwrap_or(u64::MAX).min(skill.max_nodes()));
+    }
     //1. Prepare itcs (reset things from previous search)
-    *itcs.best_pv.lock().unwrap() = ScoredPrincipalVariation::default();
+    *itcs.multi_pv.lock().unwrap() = vec![ScoredPrincipalVariation::default()];
     itcs.stable_pv.store(false, Ordering::Relaxed);
     *itcs.depth_info.lock().unwrap() = [DepthInformation::UnSearched; MAX_SEARCH_DEPTH];
     itcs.nodes_searched()
@@ -539,9 +850,10 @@ pub fn search_move(
     *itcs.start_time.write().unwrap() = Instant::now();
     *itcs.last_cache_status.lock().unwrap() = None;
     itcs.cache_status.store(0, Ordering::Relaxed);
-    *itcs.timeout_flag.write().unwrap() = false;
 
-    let mut thread = Thread::new(0, Arc::clone(&itcs));
+    let pawn_hash = itcs.pawn_hash.lock().unwrap().take().unwrap_or_default();
+    let mut thread = Thread::new(0, Arc::clone(&itcs), pawn_hash);
+    thread.cancellation = cancellation.clone();
 
     let time_saved_before = itcs.saved_time.load(Ordering::Relaxed);
     //Step 1. Check how many legal moves there are
@@ -554,17 +866,30 @@ pub fn search_move(
     );
 
     //Step2. Check legal moves
+    limits
+        .search_moves
+        .retain(|mv| movelist.move_list.iter().any(|gmv| gmv.0 == *mv));
     if movelist.move_list.is_empty() {
         panic!("The root position given does not have any legal move!");
     } else if movelist.move_list.len() == 1 {
-        writeln!(itcs.output(), "bestmove {:?}", movelist.move_list[0].0)
-            .expect("engine output write failed");
+        itcs.wait_while_pondering();
+        let only_move = movelist.move_list[0].0;
+        let result = SearchResult {
+            best_move: only_move,
+            ponder_move: None,
+            score: 0.into(),
+            pv: vec![only_move],
+            depth: 0,
+            nodes: 0,
+            time: itcs.get_time_elapsed(),
+        };
+        itcs.observer().on_bestmove(&result);
 
         let new_timesaved: u64 = (time_saved_before as i64
             + tc.time_saved(0, time_saved_before, itcs.uci_options().move_overhead))
         .max(0) as u64;
         itcs.saved_time.store(new_timesaved, Ordering::Relaxed);
-        return None;
+        return result;
     }
 
     //Step3. Prepare history
@@ -580,38 +905,49 @@ pub fn search_move(
         hist.push(*hashes, false);
     }
 
-    //Step 4. Send search command
+    //Step 4. Send search command to the helpers, then search on this thread
+    for tx in itcs.tx.read().unwrap().iter() {
+        tx.send(ThreadInstruction::StartSearch(Box::new(
+            SearchInstruction {
+                max_depth,
+                state: game_state.clone(),
+                tc,
+                limits: limits.clone(),
+                history: hist.clone(),
+                time_saved: time_saved_before,
+                cancellation: cancellation.clone(),
+            },
+        )))
+        .expect("Couldn't send search command!");
+    }
     thread.start_search(
         max_depth,
         game_state.clone(),
         tc,
-        hist.clone(),
+        limits,
+        hist,
         time_saved_before,
     );
-    /*for tx in itcs.tx.read().unwrap().iter() {
-        tx.send(ThreadInstruction::StartSearch(
-            max_depth,
-            game_state.clone(),
-            tc,
-            hist.clone(),
-            time_saved_before,
-        ))
-        .expect("Couldn't send search command!");
-    }*/
-
-    // //Step 5. Wait until every thread finished up
-    // for _ in 0..itcs.uci_options().threads {
-    //     std::thread::sleep_ms(10000000);
-    //     // loop
-    //     // match itcs.rx_f
-    //     //     .try_recv() {
-
-    //     //     }
-    //     //     .expect("Could not receive finish flag from channel");
-    // }
-
-    //Step 6. Report to UCI
-    itcs.report_bestmove();
+    *itcs.pawn_hash.lock().unwrap() = Some(thread.pawn_hash);
+
+    //Step 5. Wait until every helper finished up. The main thread has cancelled the search when
+    //it returned, so they stop on their next checkup
+    let rx_f = itcs.rx_f.lock().unwrap();
+    for _ in 0..itcs.tx.read().unwrap().len() {
+        rx_f
+            .recv()
+            .expect("Could not receive finish flag from channel");
+    }
+    drop(rx_f);
+
+    //Step 6. Report to the observer
+    itcs.wait_while_pondering();
+    let line = match skill {
+        Some(skill) => skill.pick_line(&itcs.multi_pv.lock().unwrap(), &mut rand::thread_rng()),
+        None => 0,
+    };
+    let result = itcs.search_result(line);
+    itcs.observer().on_bestmove(&result);
     //Store new saved time
     let elapsed_time = itcs.get_time_elapsed();
     let new_timesaved: u64 = (time_saved_before as i64
@@ -623,6 +959,5 @@ pub fn search_move(
     .max(0) as u64;
     itcs.saved_time.store(new_timesaved, Ordering::Relaxed);
     //And return
-    let best_score = itcs.best_pv.lock().unwrap().score;
-    Some(best_score)
+    result
 }
@@ -0,0 +1,103 @@
+use super::ScoredPrincipalVariation;
+use crate::UCIOptions;
+use rand::Rng;
+
+pub const DEFAULT_SKILL_LEVEL: usize = 20;
+pub const MIN_SKILL_LEVEL: usize = 0;
+pub const MAX_SKILL_LEVEL: usize = 20;
+
+//Rating of skill levels, measured with the referee at 4s+40ms (STRENGTH_CONFIG.json). Levels 15
+//and 19 played full strength, the others the next stronger level in the table, 100 games each.
+//Full strength is set to 2800, the other ratings follow from the scores, so they are self-play
+//ratings and uncertain by about 100 Elo per match
+const ELO_LEVELS: [(u32, f64); 8] = [
+    (470, 0.),
+    (610, 1.),
+    (980, 3.),
+    (1260, 5.),
+    (1500, 7.),
+    (1910, 10.),
+    (2270, 15.),
+    (2410, 19.),
+];
+
+pub const MIN_ELO: u32 = ELO_LEVELS[0].0;
+pub const MAX_ELO: u32 = ELO_LEVELS[ELO_LEVELS.len() - 1].0;
+pub const DEFAULT_ELO: u32 = MAX_ELO;
+
+//Number of root moves a weakened search picks from
+pub const SKILL_CANDIDATES: usize = 4;
+
+//How much a weakened search holds back, from 0 (random among the candidates) up to just below
+//MAX_SKILL_LEVEL. Fractional levels let every Elo step make a difference
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Skill {
+    pub level: f64,
+}
+
+impl Skill {
+    //None at full strength
+    pub fn from_options(options: &UCIOptions) -> Option<Skill> {
+        if options.limit_strength {
+            Some(Skill::from_elo(options.elo))
+        } else if options.skill_level < MAX_SKILL_LEVEL {
+            Some(Skill {
+                level: options.skill_level as f64,
+            })
+        } else {
+            None
+        }
+    }
+
+    //Interpolates linearly between the measured levels
+    pub fn from_elo(elo: u32) -> Skill {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        let upper = ELO_LEVELS
+            .iter()
+            .position(|(rating, _)| *rating >= elo)
+            .unwrap_or(ELO_LEVELS.len() - 1)
+            .max(1);
+        let (low_elo, low_level) = ELO_LEVELS[upper - 1];
+        let (high_elo, high_level) = ELO_LEVELS[upper];
+        Skill {
+            level: low_level
+                + (high_level - low_level) * f64::from(elo - low_elo)
+                    / f64::from(high_elo - low_elo),
+        }
+    }
+
+    pub fn max_depth(&self) -> usize {
+        1 + self.level as usize
+    }
+
+    //From 1000 nodes on level 0, doubling every second level
+    pub fn max_nodes(&self) -> u64 {
+        (1000. * 2f64.powf(self.level / 2.)) as u64
+    }
+
+    //Index of the line to play. Every candidate gets noise added to its score which grows with the
+    //weakness and its distance to the best line, so weak levels mostly make small mistakes, but
+    //sometimes blunder
+    pub fn pick_line<R: Rng>(&self, lines: &[ScoredPrincipalVariation], rng: &mut R) -> usize {
+        let candidates = lines
+            .iter()
+            .take(SKILL_CANDIDATES)
+            .take_while(|line| matches!(line.pv.pv.first(), Some(Some(_))))
+            .count();
+        if candidates <= 1 {
+            return 0;
+        }
+        let top_score = i32::from(lines[0].score);
+        let delta = (top_score - i32::from(lines[candidates - 1].score)).min(100);
+        let weakness = (120. - 2. * self.level) as i32;
+        let mut best = (0, i32::MIN);
+        for (index, line) in lines.iter().take(candidates).enumerate() {
+            let score = i32::from(line.score);
+            let push = (weakness * (top_score - score) + delta * rng.gen_range(0, weakness)) / 128;
+            if score + push > best.1 {
+                best = (index, score + push);
+            }
+        }
+        best.0
+    }
+}
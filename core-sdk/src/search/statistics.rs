@@ -21,8 +21,11 @@ pub struct SearchStatistics {
     pub static_eval_nodes: u64,
     pub cache_replace_eval: u64,
     pub iid_nodes: u64,
+    pub singular_nodes: u64,
     pub futil_nodes: u64,
     pub history_pruned: u64,
+    pub pawn_hash_probes: u64,
+    pub pawn_hash_hits: u64,
 }
 
 impl Default for SearchStatistics {
@@ -48,8 +51,11 @@ impl Default for SearchStatistics {
             static_eval_nodes: 0,
             cache_replace_eval: 0,
             iid_nodes: 0,
+            singular_nodes: 0,
             futil_nodes: 0,
             history_pruned: 0,
+            pawn_hash_probes: 0,
+            pawn_hash_hits: 0,
         }
     }
 }
@@ -88,6 +94,10 @@ impl SearchStatistics {
         self.iid_nodes += 1;
     }
     #[inline(always)]
+    pub fn add_singular_search(&mut self) {
+        self.singular_nodes += 1;
+    }
+    #[inline(always)]
     pub fn add_static_eval_node(&mut self) {
         self.static_eval_nodes += 1;
     }
@@ -143,6 +153,13 @@ impl SearchStatistics {
     pub fn add_nm_pruning(&mut self) {
         self.nm_pruned += 1;
     }
+    #[inline(always)]
+    pub fn add_pawn_hash_probe(&mut self, hit: bool) {
+        self.pawn_hash_probes += 1;
+        if hit {
+            self.pawn_hash_hits += 1;
+        }
+    }
 }
 
 impl Display for SearchStatistics {
@@ -193,6 +210,11 @@ impl Display for SearchStatistics {
             self.iid_nodes,
             (self.iid_nodes as f64 / self.normal_nodes_searched as f64 * 100.0)
         ));
+        res_str.push_str(&format!(
+            "Normal-Search Singular Nodes : {} ({}%)\n",
+            self.singular_nodes,
+            (self.singular_nodes as f64 / self.normal_nodes_searched as f64 * 100.0)
+        ));
         res_str.push_str(&format!(
             "Normal-Search Static Eval Nodes : {} ({}%)\n",
             self.static_eval_nodes,
@@ -218,6 +240,11 @@ impl Display for SearchStatistics {
             self.history_pruned,
             (self.history_pruned as f64 / self.normal_nodes_searched as f64 * 100.0)
         ));
+        res_str.push_str(&format!(
+            "Pawn-Hash-Hits: {} ({}%)\n",
+            self.pawn_hash_hits,
+            (self.pawn_hash_hits as f64 / self.pawn_hash_probes as f64 * 100.0)
+        ));
 
         res_str.push_str("\n");
         res_str.push_str(&format!(
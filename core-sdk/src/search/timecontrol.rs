@@ -1,3 +1,5 @@
+use crate::board_representation::game_state::GameMove;
+
 pub const DEFAULT_MOVE_OVERHEAD: u64 = 25;
 pub const MIN_MOVE_OVERHEAD: u64 = 0;
 pub const MAX_MOVE_OVERHEAD: u64 = 20000;
@@ -18,6 +20,17 @@ impl TimeControlInformation {
     }
 }
 
+//Limits of a search apart from the clock
+#[derive(Clone, Default)]
+pub struct SearchLimits {
+    //Root moves to consider, all of them if empty
+    pub search_moves: Vec<GameMove>,
+    //Node budget, summed across all threads
+    pub nodes: Option<u64>,
+    //Stop as soon as a mate in this many moves is found
+    pub mate: Option<usize>,
+}
+
 #[derive(Clone, Copy)]
 pub enum TimeControl {
     Incremental(u64, u64),
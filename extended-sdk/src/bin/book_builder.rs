@@ -0,0 +1,65 @@
+use core_sdk::board_representation::game_state::GameResult;
+use extended_sdk::book_builder::{BookBuilder, BookFilter};
+use std::env;
+
+const USAGE: &str = "Usage: book_builder pgn FILE [pgn FILE ...] out FILE [minelo ELO] [maxply PLY] [mingames GAMES] [results 1-0,0-1,1/2-1/2]";
+
+fn parse_result(result: &str) -> GameResult {
+    match result {
+        "1-0" => GameResult::WhiteWin,
+        "0-1" => GameResult::BlackWin,
+        "1/2-1/2" => GameResult::Draw,
+        _ => panic!("Invalid result {}\n{}", result, USAGE),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut pgn_files: Vec<&str> = Vec::new();
+    let mut out_path: Option<&str> = None;
+    let mut filter = BookFilter::default();
+    let mut index: usize = 1;
+    while index + 1 < args.len() {
+        let value = &args[index + 1];
+        match &args[index][..] {
+            "pgn" => pgn_files.push(value),
+            "out" => out_path = Some(value),
+            "minelo" => filter.min_elo = Some(value.parse().expect(USAGE)),
+            "maxply" => filter.max_ply = value.parse().expect(USAGE),
+            "mingames" => filter.min_games = value.parse().expect(USAGE),
+            "results" => filter.results = value.split(',').map(parse_result).collect(),
+            _ => panic!("Invalid argument {}\n{}", args[index], USAGE),
+        }
+        index += 2;
+    }
+    if index < args.len() {
+        panic!("Missing value for {}\n{}", args[index], USAGE);
+    }
+    let out_path = out_path.expect(USAGE);
+    if pgn_files.is_empty() {
+        panic!("{}", USAGE);
+    }
+
+    let mut builder = BookBuilder::new(filter);
+    for path in pgn_files {
+        let summary = builder
+            .add_pgn(path)
+            .unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e));
+        println!("Added {} games from {}", summary.added, path);
+        if summary.skipped > 0 {
+            println!(
+                "Warning: skipped {} games of {} with an invalid FEN tag or move",
+                summary.skipped, path
+            );
+        }
+    }
+    let entries = builder
+        .write(out_path)
+        .unwrap_or_else(|e| panic!("Unable to write {}: {}", out_path, e));
+    println!(
+        "Wrote {} entries for {} positions to {}",
+        entries,
+        builder.positions.len(),
+        out_path
+    );
+}
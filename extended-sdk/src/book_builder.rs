@@ -0,0 +1,270 @@
+use crate::pgn::pgn_reader::PGNParser;
+use core_sdk::board_representation::game_state::{
+    FenError, GameMove, GameResult, GameState, WHITE,
+};
+use core_sdk::board_representation::san::SanError;
+use core_sdk::book::polyglot::{encode_move, BookEntry};
+use core_sdk::move_generation::makemove::make_move;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+
+pub struct BookFilter {
+    //Games are only added if their result is listed
+    pub results: Vec<GameResult>,
+    //Games in which a player is rated below this or has no Elo tag are skipped
+    pub min_elo: Option<u32>,
+    //Only the first max_ply moves of every game make it into the book
+    pub max_ply: usize,
+    //Moves played less often than this are left out
+    pub min_games: u32,
+}
+
+impl Default for BookFilter {
+    fn default() -> Self {
+        BookFilter {
+            results: vec![GameResult::WhiteWin, GameResult::BlackWin, GameResult::Draw],
+            min_elo: None,
+            max_ply: 20,
+            min_games: 1,
+        }
+    }
+}
+
+//Results of the games a move was played in, seen from the side making the move
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct MoveStats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MoveStats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    //Polyglot's usual weighting, a win counting twice as much as a draw
+    pub fn score(&self) -> u32 {
+        2 * self.wins + self.draws
+    }
+}
+
+//Why a game could not be added to the book
+#[derive(PartialEq, Debug, Clone)]
+pub enum GameError {
+    Fen(FenError),
+    Move(SanError),
+}
+impl Display for GameError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            GameError::Fen(e) => write!(formatter, "invalid FEN tag: {}", e),
+            GameError::Move(e) => write!(formatter, "{}", e),
+        }
+    }
+}
+impl std::error::Error for GameError {}
+
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct PgnSummary {
+    //Games that passed the filter and made it into the book
+    pub added: usize,
+    //Games whose FEN tag or moves could not be read
+    pub skipped: usize,
+}
+
+pub struct BookBuilder {
+    pub filter: BookFilter,
+    //Statistics of every move, grouped by the polyglot key of the position it was played in
+    pub positions: HashMap<u64, HashMap<u16, MoveStats>>,
+}
+
+impl BookBuilder {
+    pub fn new(filter: BookFilter) -> Self {
+        BookBuilder {
+            filter,
+            positions: HashMap::new(),
+        }
+    }
+
+    //Streams all games of a PGN file into the book. Games which can not be read are skipped
+    //instead of ending the whole file
+    pub fn add_pgn(&mut self, path: &str) -> io::Result<PgnSummary> {
+        let mut pgn_parser = PGNParser {
+            reader: BufReader::new(File::open(path)?),
+            tags: Vec::new(),
+        };
+        let mut summary = PgnSummary::default();
+        while let Some(game) = pgn_parser.next() {
+            let tokens = movetext_tokens(&game);
+            let result = match tokens.last().map(|token| token.as_str()) {
+                Some("1-0") => GameResult::WhiteWin,
+                Some("0-1") => GameResult::BlackWin,
+                Some("1/2-1/2") => GameResult::Draw,
+                _ => GameResult::Ingame,
+            };
+            if !self.filter.results.contains(&result) || !self.passes_elo(&pgn_parser) {
+                continue;
+            }
+            match self.read_moves(&pgn_parser, &tokens) {
+                Ok((moves, states)) => {
+                    self.add_game(&moves, &states, &result);
+                    summary.added += 1;
+                }
+                Err(_) => summary.skipped += 1,
+            }
+        }
+        Ok(summary)
+    }
+
+    //Plays the moves up to the ply limit from the position of the FEN tag, returning them along
+    //with the positions they were played in
+    fn read_moves(
+        &self,
+        pgn_parser: &PGNParser,
+        tokens: &[String],
+    ) -> Result<(Vec<GameMove>, Vec<GameState>), GameError> {
+        let mut state = match pgn_parser.tag("FEN") {
+            Some(fen) => GameState::try_from_fen(fen).map_err(GameError::Fen)?,
+            None => GameState::standard(),
+        };
+        let mut moves = Vec::new();
+        let mut states = Vec::new();
+        for token in tokens.iter().take(self.filter.max_ply) {
+            if is_result(token) {
+                break;
+            }
+            let mv = state.parse_san(token).map_err(GameError::Move)?;
+            let next = make_move(&state, mv);
+            moves.push(mv);
+            states.push(state);
+            state = next;
+        }
+        Ok((moves, states))
+    }
+
+    fn passes_elo(&self, pgn_parser: &PGNParser) -> bool {
+        match self.filter.min_elo {
+            None => true,
+            Some(min_elo) => ["WhiteElo", "BlackElo"].iter().all(|tag| {
+                pgn_parser
+                    .tag(tag)
+                    .and_then(|elo| elo.parse::<u32>().ok())
+                    .filter(|elo| *elo >= min_elo)
+                    .is_some()
+            }),
+        }
+    }
+
+    //states[i] has to be the position moves[i] was played in
+    pub fn add_game(&mut self, moves: &[GameMove], states: &[GameState], result: &GameResult) {
+        for (mv, state) in moves.iter().zip(states).take(self.filter.max_ply) {
+            let stats = self
+                .positions
+                .entry(state.polyglot_key())
+                .or_insert_with(HashMap::new)
+                .entry(encode_move(state, *mv))
+                .or_insert_with(MoveStats::default);
+            match (result, state.color_to_move == WHITE) {
+                (GameResult::Draw, _) => stats.draws += 1,
+                (GameResult::WhiteWin, true) | (GameResult::BlackWin, false) => stats.wins += 1,
+                (GameResult::WhiteWin, false) | (GameResult::BlackWin, true) => stats.losses += 1,
+                (GameResult::Ingame, _) => {}
+            }
+        }
+    }
+
+    //Book entries sorted by key. Within a position weights are scaled down together if the best
+    //score does not fit into 16 bits, and moves without any weight are dropped
+    pub fn entries(&self) -> Vec<BookEntry> {
+        let mut res = Vec::new();
+        for (key, moves) in self.positions.iter() {
+            let candidates: Vec<(u16, u32)> = moves
+                .iter()
+                .filter(|(_, stats)| stats.games() >= self.filter.min_games)
+                .map(|(mv, stats)| (*mv, stats.score()))
+                .collect();
+            let max_score = candidates
+                .iter()
+                .map(|(_, score)| *score)
+                .max()
+                .unwrap_or(0);
+            for (mv, score) in candidates {
+                let weight = if max_score > u32::from(u16::MAX) {
+                    (u64::from(score) * u64::from(u16::MAX) / u64::from(max_score)) as u16
+                } else {
+                    score as u16
+                };
+                if weight > 0 {
+                    res.push(BookEntry {
+                        key: *key,
+                        mv,
+                        weight,
+                        learn: 0,
+                    });
+                }
+            }
+        }
+        res.sort_by(|a, b| {
+            a.key
+                .cmp(&b.key)
+                .then(b.weight.cmp(&a.weight))
+                .then(a.mv.cmp(&b.mv))
+        });
+        res
+    }
+
+    pub fn write(&self, path: &str) -> io::Result<usize> {
+        let entries = self.entries();
+        let mut writer = BufWriter::new(File::create(path)?);
+        for entry in entries.iter() {
+            writer.write_all(&entry.to_bytes())?;
+        }
+        writer.flush()?;
+        Ok(entries.len())
+    }
+}
+
+fn is_result(token: &str) -> bool {
+    ["1-0", "0-1", "1/2-1/2", "*"].contains(&token)
+}
+
+//Moves and the game termination marker of PGN move text. Move numbers, comments, variations and
+//numeric annotation glyphs are dropped
+fn movetext_tokens(movetext: &str) -> Vec<String> {
+    let mut text = String::with_capacity(movetext.len());
+    let mut brace_comment = false;
+    let mut line_comment = false;
+    let mut variation_depth = 0;
+    for c in movetext.chars() {
+        match c {
+            '}' if brace_comment => brace_comment = false,
+            '\n' if line_comment => line_comment = false,
+            _ if brace_comment || line_comment => continue,
+            '{' => brace_comment = true,
+            ';' => line_comment = true,
+            '(' => variation_depth += 1,
+            ')' if variation_depth > 0 => variation_depth -= 1,
+            _ if variation_depth > 0 => continue,
+            _ => {
+                text.push(c);
+                continue;
+            }
+        }
+        text.push(' ');
+    }
+    text.split_whitespace()
+        .filter(|token| !token.starts_with('$'))
+        .map(|token| {
+            if is_result(token) {
+                token
+            } else {
+                token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.')
+            }
+        })
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+        .collect()
+}
@@ -1,3 +1,4 @@
+pub mod book_builder;
 pub mod misc;
 pub mod openings;
 pub mod pgn;
@@ -14,7 +14,10 @@ pub fn load_db_until(db: &str, until: usize) -> (Vec<GameState>, Vec<Vec<GameMov
     let res_file = File::open(db).expect("Unable to open opening database");
     let reader = BufReader::new(res_file);
     let parser = GameParser {
-        pgn_parser: PGNParser { reader },
+        pgn_parser: PGNParser {
+            reader,
+            tags: Vec::new(),
+        },
         is_opening: true,
         opening_load_untilply: until,
         move_list: movelist,
@@ -77,9 +77,7 @@ pub fn find_castle(
 ) -> Result<(GameMove, GameState), ()> {
     for gmv in movelist.move_list.iter() {
         let mv = gmv.0;
-        if mv.move_type == GameMoveType::Castle
-            && mv.to as isize - mv.from as isize == 2 * if king_side { 1 } else { -1 }
-        {
+        if mv.move_type == GameMoveType::Castle && (mv.to > mv.from) == king_side {
             let state = make_move(g, mv);
             return Ok((mv, state));
         }
@@ -175,10 +173,10 @@ impl MoveSpecification {
     }
 
     pub fn matches(&self, mv: &GameMove) -> bool {
-        mv.to as usize == self.target_square
-            && (self.from_square.is_none() || self.from_square.unwrap() == mv.from as usize)
-            && (self.from_file.is_none() || self.from_file.unwrap() == mv.from as usize % 8)
-            && (self.from_rank.is_none() || self.from_rank.unwrap() == mv.from as usize / 8)
+        mv.to.index() == self.target_square
+            && (self.from_square.is_none() || self.from_square.unwrap() == mv.from.index())
+            && (self.from_file.is_none() || self.from_file.unwrap() == mv.from.index() % 8)
+            && (self.from_rank.is_none() || self.from_rank.unwrap() == mv.from.index() / 8)
             && (mv.piece_type == self.moving_piece_type || self.from_square.is_some())
             && (self.promotion_piece
                 == match mv.move_type {
@@ -283,6 +281,25 @@ pub fn match_rank(c: Option<char>) -> usize {
 
 pub struct PGNParser {
     pub reader: BufReader<File>,
+    //Tag pairs of the game returned last, in the order they appear
+    pub tags: Vec<(String, String)>,
+}
+
+impl PGNParser {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+//Splits a tag pair line like [White "Carlsen, Magnus"] into name and value
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_at(inner.find(' ')?);
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.to_string()))
 }
 
 impl Iterator for PGNParser {
@@ -290,13 +307,20 @@ impl Iterator for PGNParser {
 
     fn next(&mut self) -> Option<String> {
         let mut res_str = String::new();
+        self.tags.clear();
         let mut line = String::new();
         let mut res = self.reader.read_line(&mut line);
         while match res {
             Err(_e) => false,
             Ok(_e) => true,
         } {
-            if line.contains("1.") && !line.contains('[') {
+            if let Some(tag) = parse_tag(&line) {
+                self.tags.push(tag);
+            }
+            //Games set up from a FEN tag may start with any move number
+            if (line.contains("1.") || self.tag("FEN").is_some() && !line.trim().is_empty())
+                && !line.contains('[')
+            {
                 loop {
                     res_str.push_str(&line);
                     if res_str.contains("1-0")
@@ -307,9 +331,11 @@ impl Iterator for PGNParser {
                         break;
                     }
                     line = String::new();
-                    self.reader
-                        .read_line(&mut line)
-                        .expect("Reader had an error reading moves of game!");
+                    //A game cut off at the end of the file is returned as it is
+                    match self.reader.read_line(&mut line) {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => {}
+                    }
                 }
                 break;
             }
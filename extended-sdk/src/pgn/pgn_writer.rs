@@ -43,6 +43,24 @@ pub fn get_pgn_string(
     moves: Vec<GameMove>,
     opening_comment: Option<usize>,
 ) -> String {
+    //Games without a given result are scored by the rules from their final position
+    let mut history = Vec::with_capacity(moves.len());
+    let mut final_state = GameState::from_fen(&metadata.starting_position);
+    for mv in moves.iter() {
+        let next_state = make_move(&final_state, *mv);
+        history.push(final_state);
+        final_state = next_state;
+    }
+    let (final_result, reason) = final_state.game_status(&history);
+    let result = match &metadata.result {
+        Some(s) => s.clone(),
+        None => final_result.to_string(),
+    };
+    let termination = match &metadata.termination {
+        Some(s) => Some(s.clone()),
+        None => reason.map(|_| "normal".to_owned()),
+    };
+
     let mut res_str = String::new();
     let s = if metadata.event_name.is_some() {
         metadata.event_name.clone().unwrap()
@@ -80,14 +98,9 @@ pub fn get_pgn_string(
         String::from("?")
     };
     res_str.push_str(&format!("[Black \"{}\"]\n", s));
-    let s = if metadata.result.is_some() {
-        metadata.result.clone().unwrap()
-    } else {
-        String::from("*")
-    };
-    res_str.push_str(&format!("[Result \"{}\"]\n", s));
+    res_str.push_str(&format!("[Result \"{}\"]\n", result));
 
-    if let Some(s) = &metadata.termination {
+    if let Some(s) = &termination {
         res_str.push_str(&format!("[Termination \"{}\"]\n", s));
     }
     if metadata.starting_position != crate::misc::STD_FEN {
@@ -111,8 +124,9 @@ pub fn get_pgn_string(
         move_text.push_str("{Opening book has ended} ");
     }
     let mut current_color = start_pos.color_to_move;
+    let sans = start_pos.moves_to_san(&moves);
     for (index, mv) in moves.iter().enumerate() {
-        move_text.push_str(&format!("{} ", mv.to_san(&start_pos)));
+        move_text.push_str(&format!("{} ", sans[index]));
         if opening_comment.is_some() && (index + 1) == opening_comment.unwrap() {
             move_text.push_str("{Opening book has ended} ");
         }
@@ -122,11 +136,7 @@ pub fn get_pgn_string(
         }
         current_color = 1 - current_color;
     }
-    move_text.push_str(if metadata.result.is_some() {
-        metadata.result.as_ref().unwrap()
-    } else {
-        "*"
-    });
+    move_text.push_str(&result);
     let contents: Vec<&str> = move_text.split_whitespace().collect();
     //Make sure that every line is only 80 long at maximum
     let mut move_text = String::new();
@@ -163,37 +173,28 @@ mod tests {
         let mut attack_container = GameStateAttackContainer::default();
         let mut rng = rand::thread_rng();
         let mut g = GameState::from_fen("rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq -");
+        let mut history = Vec::with_capacity(100);
         let mut moves = Vec::with_capacity(100);
-        let mut res = GameResult::Ingame;
-        loop {
-            attack_container.write_state(&g);
-            let agsi = movegen::generate_moves(&g, false, &mut movelist, &attack_container);
-            if !agsi.stm_haslegalmove || g.half_moves >= 100 {
-                if g.half_moves >= 100 {
-                    res = GameResult::Draw;
-                }
-                if !agsi.stm_haslegalmove {
-                    if agsi.stm_incheck {
-                        if g.color_to_move == WHITE {
-                            res = GameResult::BlackWin;
-                        } else {
-                            res = GameResult::WhiteWin;
-                        }
-                    } else {
-                        res = GameResult::Draw;
-                    }
-                }
-                break;
+        let res = loop {
+            let (res, _) = g.game_status(&history);
+            if res != GameResult::Ingame {
+                break res;
             }
+            attack_container.write_state(&g);
+            movegen::generate_moves(&g, false, &mut movelist, &attack_container);
             let mv = movelist.move_list[rng.gen_range(0, movelist.move_list.len())];
-            g = make_move(&g, mv.0);
+            let next = make_move(&g, mv.0);
+            history.push(g);
+            g = next;
             moves.push(mv.0);
-        }
+        };
         let mut metadata = PGNMetadata::default();
         metadata.fill_systemdata();
-        metadata.result = Some(res.to_string());
         metadata.starting_position =
             "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq -".to_owned();
-        print!("{}", super::get_pgn_string(&metadata, moves, Some(0)))
+        let pgn = super::get_pgn_string(&metadata, moves, Some(0));
+        assert!(pgn.contains(&format!("[Result \"{}\"]", res)));
+        assert!(pgn.contains("[Termination \"normal\"]"));
+        print!("{}", pgn)
     }
 }
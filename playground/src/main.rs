@@ -1,7 +1,7 @@
 use core_sdk::board_representation::game_state::GameState;
 use core_sdk::search::cache::Cache;
-use core_sdk::search::searcher::{search_move, InterThreadCommunicationSystem};
-use core_sdk::search::timecontrol::TimeControl;
+use core_sdk::search::searcher::{search_move, CancellationToken, InterThreadCommunicationSystem};
+use core_sdk::search::timecontrol::{SearchLimits, TimeControl};
 use std::sync::Arc;
 
 fn main() {
@@ -18,5 +18,7 @@ fn go_infinite_from_startpos() {
         GameState::standard(),
         Vec::new(),
         TimeControl::Infinite,
+        SearchLimits::default(),
+        CancellationToken::default(),
     );
 }
@@ -2,7 +2,7 @@ use crate::async_communication::{
     expect_output, expect_output_and_listen_for_info, stderr_listener, write_all,
 };
 use core_sdk::board_representation::game_state::*;
-use core_sdk::move_generation::movegen::MoveList;
+use core_sdk::board_representation::game_status::Reason;
 use core_sdk::search::timecontrol::TimeControl;
 use log::{info, warn};
 use std::collections::HashMap;
@@ -27,12 +27,24 @@ pub enum EndConditionInformation {
     HundredMoveDraw,
     ThreeFoldRepetition,
     DrawByadjudication,
-    DrawByMissingPieces,
+    DrawByInsufficientMaterial,
     StaleMate,
     Mate,
     MateByadjudication,
 }
 
+impl From<Reason> for EndConditionInformation {
+    fn from(reason: Reason) -> Self {
+        match reason {
+            Reason::Checkmate => EndConditionInformation::Mate,
+            Reason::Stalemate => EndConditionInformation::StaleMate,
+            Reason::FiftyMoveRule => EndConditionInformation::HundredMoveDraw,
+            Reason::ThreefoldRepetition => EndConditionInformation::ThreeFoldRepetition,
+            Reason::InsufficientMaterial => EndConditionInformation::DrawByInsufficientMaterial,
+        }
+    }
+}
+
 impl Display for EndConditionInformation {
     fn fmt(&self, formatter: &mut Formatter) -> Result {
         let mut res_str: String = String::new();
@@ -40,7 +52,7 @@ impl Display for EndConditionInformation {
             EndConditionInformation::HundredMoveDraw => "Hundred Move Draw",
             EndConditionInformation::ThreeFoldRepetition => "Draw by Three Fold Repetition",
             EndConditionInformation::DrawByadjudication => "Draw by adjudication",
-            EndConditionInformation::DrawByMissingPieces => "Draw by missing pieces",
+            EndConditionInformation::DrawByInsufficientMaterial => "Draw by insufficient material",
             EndConditionInformation::StaleMate => "Draw by Stalemate",
             EndConditionInformation::Mate => "Win by Mate",
             EndConditionInformation::MateByadjudication => "Win by adjudication",
@@ -204,7 +216,7 @@ impl Engine {
         stdin: &mut BufWriter<ChildStdin>,
         stdout: &mut BufReader<ChildStdout>,
         task_id: usize,
-        movelist: &MoveList,
+        game_state: &GameState,
     ) -> EngineReaction<(GameMove, EngineStatus)> {
         write_all(stdin, position_description).await;
         let reaction = self.valid_isready_reaction(stdin, stdout, task_id).await;
@@ -238,16 +250,16 @@ impl Engine {
         let line = output.0.unwrap();
         let split_line: Vec<&str> = line.split_whitespace().collect();
         let game_move: GameMove = if split_line[0] == "bestmove" {
-            let mv = GameMove::string_to_move(split_line[1]);
-            let found_move = find_move(mv.0, mv.1, mv.2, &movelist);
-            if found_move.is_none() {
-                info!(
-                    "Engine {} sent illegal move ({}) in game {}\n",
-                    self.name, line, task_id
-                );
-                return EngineReaction::DisqualifyEngine;
+            match game_state.parse_uci_move(split_line.get(1).copied().unwrap_or("")) {
+                Ok(mv) => mv,
+                Err(e) => {
+                    info!(
+                        "Engine {} sent illegal move ({}) in game {}: {}\n",
+                        self.name, line, task_id, e
+                    );
+                    return EngineReaction::DisqualifyEngine;
+                }
             }
-            found_move.unwrap()
         } else {
             info!(
                 "Bestmove wasn't first argument after bestmove keyword! Disqualifiying engine {} in game {}\n",
@@ -340,33 +352,6 @@ impl Engine {
     }
 }
 
-pub fn find_move(
-    from: usize,
-    to: usize,
-    promo_pieces: Option<PieceType>,
-    move_list: &MoveList,
-) -> Option<GameMove> {
-    for gmv in move_list.move_list.iter() {
-        let mv = gmv.0;
-        if mv.from as usize == from && mv.to as usize == to {
-            if let GameMoveType::Promotion(ps, _) = mv.move_type {
-                match promo_pieces {
-                    Some(piece) => {
-                        if piece != ps {
-                            continue;
-                        }
-                    }
-                    None => {
-                        continue;
-                    }
-                }
-            }
-            return Some(mv);
-        }
-    }
-    None
-}
-
 pub fn fetch_info(info: &str) -> UCIInfo {
     let split_line: Vec<&str> = info.split_whitespace().collect();
     let mut depth = None;
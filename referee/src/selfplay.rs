@@ -1,9 +1,7 @@
 use crate::async_communication::{stderr_listener, write_all};
 use crate::engine::{EndConditionInformation, EngineReaction, EngineStatus, PlayTask, TaskResult};
 use core_sdk::board_representation::game_state::*;
-use core_sdk::board_representation::game_state_attack_container::GameStateAttackContainer;
 use core_sdk::move_generation::makemove::make_move;
-use core_sdk::move_generation::movegen;
 use log::warn;
 use std::time::Duration;
 use tokio::process::Child;
@@ -21,21 +19,11 @@ pub async fn cleanup(mut e1: Child, mut e2: Child, e1_err: JoinHandle<()>, e2_er
         .unwrap_or_else(|msg| warn!("Could not join e2_err task: {}", msg));
 }
 pub async fn play_game(mut task: PlayTask) -> TaskResult {
-    let mut movelist = movegen::MoveList::default();
-    let mut attack_container = GameStateAttackContainer::default();
     //-------------------------------------------------------------
     //Set game up
     let opening_fen = task.opening.to_fen();
-    attack_container.write_state(&task.opening);
-    let agsi = movegen::generate_moves(&task.opening, false, &mut movelist, &attack_container);
     let mut history: Vec<GameState> = Vec::with_capacity(100);
-    let mut status = check_end_condition(
-        &task.opening,
-        agsi.stm_haslegalmove,
-        agsi.stm_incheck,
-        &history,
-    )
-    .0;
+    let mut status = task.opening.game_status(&history).0;
     assert_eq!(status, GameResult::Ingame);
     history.push(task.opening.clone());
     let mut move_history: Vec<GameMove> = Vec::with_capacity(100);
@@ -116,7 +104,7 @@ pub async fn play_game(mut task: PlayTask) -> TaskResult {
                     &mut e1_input,
                     &mut e1_output,
                     task.id,
-                    &movelist,
+                    latest_state,
                 )
                 .await;
             let engine_status;
@@ -162,7 +150,7 @@ pub async fn play_game(mut task: PlayTask) -> TaskResult {
                     &mut e2_input,
                     &mut e2_output,
                     task.id,
-                    &movelist,
+                    latest_state,
                 )
                 .await;
             let engine_status;
@@ -207,12 +195,10 @@ pub async fn play_game(mut task: PlayTask) -> TaskResult {
         if state.full_moves < 35 {
             draw_adjudication = 0;
         }
-        attack_container.write_state(&state);
-        let agsi = movegen::generate_moves(&state, false, &mut movelist, &attack_container);
-        let check = check_end_condition(&state, agsi.stm_haslegalmove, agsi.stm_incheck, &history);
+        let check = state.game_status(&history);
         history.push(state);
         status = check.0;
-        endcondition = check.1;
+        endcondition = check.1.map(EndConditionInformation::from);
         //Check for adjudication
         if let GameResult::Ingame = status {
             //Check adjudication values
@@ -268,65 +254,3 @@ pub async fn play_game(mut task: PlayTask) -> TaskResult {
         final_status: status,
     }
 }
-
-pub fn check_end_condition(
-    game_state: &GameState,
-    has_legal_moves: bool,
-    in_check: bool,
-    history: &[GameState],
-) -> (GameResult, Option<EndConditionInformation>) {
-    let enemy_win = if game_state.color_to_move == 0 {
-        GameResult::BlackWin
-    } else {
-        GameResult::WhiteWin
-    };
-    if in_check && !has_legal_moves {
-        return (enemy_win, Some(EndConditionInformation::Mate));
-    }
-    if !in_check && !has_legal_moves {
-        return (GameResult::Draw, Some(EndConditionInformation::StaleMate));
-    }
-
-    //Missing pieces
-    if game_state.pieces[PAWN][WHITE]
-        | game_state.pieces[KNIGHT][WHITE]
-        | game_state.pieces[BISHOP][WHITE]
-        | game_state.pieces[ROOK][WHITE]
-        | game_state.pieces[QUEEN][WHITE]
-        | game_state.pieces[PAWN][BLACK]
-        | game_state.pieces[KNIGHT][BLACK]
-        | game_state.pieces[BISHOP][BLACK]
-        | game_state.pieces[ROOK][BLACK]
-        | game_state.pieces[QUEEN][BLACK]
-        == 0u64
-    {
-        return (
-            GameResult::Draw,
-            Some(EndConditionInformation::DrawByMissingPieces),
-        );
-    }
-    if game_state.half_moves >= 100 {
-        return (
-            GameResult::Draw,
-            Some(EndConditionInformation::HundredMoveDraw),
-        );
-    }
-    if get_occurences(history, game_state) >= 2 {
-        return (
-            GameResult::Draw,
-            Some(EndConditionInformation::ThreeFoldRepetition),
-        );
-    }
-
-    (GameResult::Ingame, None)
-}
-
-pub fn get_occurences(history: &[GameState], state: &GameState) -> usize {
-    let mut occ = 0;
-    for other in history {
-        if other.hash == state.hash {
-            occ += 1;
-        }
-    }
-    occ
-}
@@ -41,9 +41,20 @@ pub async fn start_self_play(config: Config) {
     let mut db: Vec<GameState> = Vec::with_capacity(100_000);
     let mut db_sequences: Vec<Vec<GameMove>> = Vec::with_capacity(100_000);
     for database in config.opening_databases {
-        let mut database_loaded = load_db_until(&database, config.opening_load_untilply);
-        db.append(&mut database_loaded.0);
-        db_sequences.append(&mut database_loaded.1);
+        let (states, sequences) = load_db_until(&database, config.opening_load_untilply);
+        for (state, sequence) in states.into_iter().zip(sequences) {
+            if let Err(errors) = state.validate() {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                println!(
+                    "Skipping corrupt opening {}: {}",
+                    state.to_fen(),
+                    errors.join(", ")
+                );
+                continue;
+            }
+            db.push(state);
+            db_sequences.push(sequence);
+        }
     }
     println!(
         "{}",
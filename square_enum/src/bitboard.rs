@@ -0,0 +1,224 @@
+use crate::square::{File, Rank, Square};
+use std::fmt::{Display, Formatter, Result};
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, ShlAssign, Shr,
+    ShrAssign,
+};
+
+//A set of squares, bit i is set if Square::ALL[i] is in the set
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0u64);
+    pub const FULL: Bitboard = Bitboard(!0u64);
+
+    #[inline(always)]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0u64
+    }
+
+    #[inline(always)]
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    #[inline(always)]
+    pub fn contains(self, square: Square) -> bool {
+        self.0 & (1u64 << square.index()) != 0u64
+    }
+
+    #[inline(always)]
+    pub fn set(&mut self, square: Square) {
+        self.0 |= 1u64 << square.index();
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self, square: Square) {
+        self.0 &= !(1u64 << square.index());
+    }
+
+    #[inline(always)]
+    pub fn toggle(&mut self, square: Square) {
+        self.0 ^= 1u64 << square.index();
+    }
+
+    //The square with the lowest index
+    #[inline(always)]
+    pub fn lsb(self) -> Option<Square> {
+        Square::from_index(self.0.trailing_zeros() as usize)
+    }
+
+    #[inline(always)]
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        let square = self.lsb()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+//Iterates over the squares of the set, from a1 to h8
+impl Iterator for Bitboard {
+    type Item = Square;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Square> {
+        self.pop_lsb()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count() as usize, Some(self.count() as usize))
+    }
+}
+
+impl From<u64> for Bitboard {
+    fn from(bb: u64) -> Self {
+        Bitboard(bb)
+    }
+}
+
+impl From<Bitboard> for u64 {
+    fn from(bb: Bitboard) -> Self {
+        bb.0
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Self {
+        square.bitboard()
+    }
+}
+
+impl From<File> for Bitboard {
+    fn from(file: File) -> Self {
+        file.bitboard()
+    }
+}
+
+impl From<Rank> for Bitboard {
+    fn from(rank: Rank) -> Self {
+        rank.bitboard()
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $fn:ident, $assign_op:ident, $assign_fn:ident) => {
+        impl<T: Into<Bitboard>> $op<T> for Bitboard {
+            type Output = Bitboard;
+
+            #[inline(always)]
+            fn $fn(self, other: T) -> Bitboard {
+                Bitboard((self.0).$fn(other.into().0))
+            }
+        }
+
+        impl<T: Into<Bitboard>> $assign_op<T> for Bitboard {
+            #[inline(always)]
+            fn $assign_fn(&mut self, other: T) {
+                (self.0).$assign_fn(other.into().0)
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    #[inline(always)]
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl Shl<usize> for Bitboard {
+    type Output = Bitboard;
+
+    #[inline(always)]
+    fn shl(self, shift: usize) -> Bitboard {
+        Bitboard(self.0 << shift)
+    }
+}
+
+impl ShlAssign<usize> for Bitboard {
+    #[inline(always)]
+    fn shl_assign(&mut self, shift: usize) {
+        self.0 <<= shift;
+    }
+}
+
+impl Shr<usize> for Bitboard {
+    type Output = Bitboard;
+
+    #[inline(always)]
+    fn shr(self, shift: usize) -> Bitboard {
+        Bitboard(self.0 >> shift)
+    }
+}
+
+impl ShrAssign<usize> for Bitboard {
+    #[inline(always)]
+    fn shr_assign(&mut self, shift: usize) {
+        self.0 >>= shift;
+    }
+}
+
+//Prints the board from white's point of view, X marks a square in the set
+impl Display for Bitboard {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let mut res_str = String::new();
+        for rank in Rank::ALL.iter().rev() {
+            res_str.push(rank.to_char());
+            for file in File::ALL.iter() {
+                res_str.push_str(if self.contains(Square::new(*file, *rank)) {
+                    " X"
+                } else {
+                    " ."
+                });
+            }
+            res_str.push('\n');
+        }
+        res_str.push_str("  a b c d e f g h\n");
+        write!(formatter, "{}", res_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bitboard;
+    use crate::square::{File, Rank, Square};
+
+    #[test]
+    fn bitboard_test() {
+        let mut bb = Bitboard::from(Square::E4) | Square::A1 | Square::H8;
+        assert_eq!(bb.count(), 3);
+        assert!(bb.contains(Square::E4));
+        assert!(!bb.contains(Square::E5));
+        assert_eq!(
+            bb.collect::<Vec<Square>>(),
+            vec![Square::A1, Square::E4, Square::H8]
+        );
+        bb.toggle(Square::E4);
+        assert_eq!(bb, Bitboard((1u64 << 63) | 1u64));
+        bb.clear(Square::A1);
+        bb.set(Square::B2);
+        assert_eq!(bb.lsb(), Some(Square::B2));
+        assert_eq!(Bitboard::EMPTY.lsb(), None);
+        assert_eq!(!Bitboard::EMPTY, Bitboard::FULL);
+        assert_eq!(Bitboard::from(Square::E2) << 8, Bitboard::from(Square::E3));
+        assert_eq!(
+            Bitboard::from(File::E) & Rank::Fourth,
+            Bitboard::from(Square::E4)
+        );
+        assert_eq!(Bitboard::from(Rank::Eighth).count(), 8);
+        assert_eq!(
+            Bitboard::from(Square::A8).to_string(),
+            "8 X . . . . . . .\n7 . . . . . . . .\n6 . . . . . . . .\n5 . . . . . . . .\n\
+             4 . . . . . . . .\n3 . . . . . . . .\n2 . . . . . . . .\n1 . . . . . . . .\n  \
+             a b c d e f g h\n"
+        );
+    }
+}
@@ -0,0 +1,7 @@
+//Typed squares, files, ranks and bitboards.
+//Squares are numbered little-endian rank-file, a1 = 0, h1 = 7, a8 = 56, h8 = 63 (see notes.txt)
+pub mod bitboard;
+pub mod square;
+
+pub use bitboard::Bitboard;
+pub use square::{File, Rank, Square};
@@ -0,0 +1,252 @@
+use crate::bitboard::Bitboard;
+use std::fmt::{Display, Formatter, Result};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+impl File {
+    pub const ALL: [File; 8] = [
+        File::A,
+        File::B,
+        File::C,
+        File::D,
+        File::E,
+        File::F,
+        File::G,
+        File::H,
+    ];
+
+    #[inline(always)]
+    pub fn from_index(index: usize) -> Option<File> {
+        File::ALL.get(index).copied()
+    }
+
+    #[inline(always)]
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_char(c: char) -> Option<File> {
+        match c {
+            'a'..='h' => File::from_index(c as usize - 'a' as usize),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        (b'a' + self as u8) as char
+    }
+
+    //All squares on this file
+    #[inline(always)]
+    pub fn bitboard(self) -> Bitboard {
+        Bitboard(0x0101_0101_0101_0101u64 << self.index())
+    }
+}
+
+impl Display for File {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        write!(formatter, "{}", self.to_char())
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eighth,
+    ];
+
+    #[inline(always)]
+    pub fn from_index(index: usize) -> Option<Rank> {
+        Rank::ALL.get(index).copied()
+    }
+
+    #[inline(always)]
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_char(c: char) -> Option<Rank> {
+        match c {
+            '1'..='8' => Rank::from_index(c as usize - '1' as usize),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        (b'1' + self as u8) as char
+    }
+
+    //The same rank seen from the other side of the board
+    #[inline(always)]
+    pub fn flip(self) -> Rank {
+        Rank::ALL[7 - self.index()]
+    }
+
+    //All squares on this rank
+    #[inline(always)]
+    pub fn bitboard(self) -> Bitboard {
+        Bitboard(0xFFu64 << (8 * self.index()))
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        write!(formatter, "{}", self.to_char())
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = {
+        use Square::*;
+        [
+            A1, B1, C1, D1, E1, F1, G1, H1,
+            A2, B2, C2, D2, E2, F2, G2, H2,
+            A3, B3, C3, D3, E3, F3, G3, H3,
+            A4, B4, C4, D4, E4, F4, G4, H4,
+            A5, B5, C5, D5, E5, F5, G5, H5,
+            A6, B6, C6, D6, E6, F6, G6, H6,
+            A7, B7, C7, D7, E7, F7, G7, H7,
+            A8, B8, C8, D8, E8, F8, G8, H8,
+        ]
+    };
+
+    #[inline(always)]
+    pub fn new(file: File, rank: Rank) -> Square {
+        Square::ALL[rank.index() * 8 + file.index()]
+    }
+
+    #[inline(always)]
+    pub fn from_index(index: usize) -> Option<Square> {
+        Square::ALL.get(index).copied()
+    }
+
+    #[inline(always)]
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    #[inline(always)]
+    pub fn file(self) -> File {
+        File::ALL[self.index() % 8]
+    }
+
+    #[inline(always)]
+    pub fn rank(self) -> Rank {
+        Rank::ALL[self.index() / 8]
+    }
+
+    //Mirrors the square vertically, a1 <-> a8
+    #[inline(always)]
+    pub fn flip(self) -> Square {
+        Square::ALL[self.index() ^ 56]
+    }
+
+    //The square reached by going the given amount of files east and ranks north,
+    //None if that leaves the board
+    pub fn offset(self, files: isize, ranks: isize) -> Option<Square> {
+        let file = self.file().index() as isize + files;
+        let rank = self.rank().index() as isize + ranks;
+        if (0..8).contains(&file) && (0..8).contains(&rank) {
+            Some(Square::ALL[(rank * 8 + file) as usize])
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    pub fn bitboard(self) -> Bitboard {
+        Bitboard(1u64 << self.index())
+    }
+}
+
+impl Display for Square {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        write!(formatter, "{}{}", self.file(), self.rank())
+    }
+}
+
+impl FromStr for Square {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Square, String> {
+        let mut chars = s.chars();
+        match (
+            chars.next().and_then(File::from_char),
+            chars.next().and_then(Rank::from_char),
+            chars.next(),
+        ) {
+            (Some(file), Some(rank), None) => Ok(Square::new(file, rank)),
+            _ => Err(format!("Invalid square {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{File, Rank, Square};
+
+    #[test]
+    fn square_test() {
+        for (index, square) in Square::ALL.iter().enumerate() {
+            assert_eq!(square.index(), index);
+            assert_eq!(Square::from_index(index), Some(*square));
+            assert_eq!(Square::new(square.file(), square.rank()), *square);
+            assert_eq!(square.to_string().parse::<Square>(), Ok(*square));
+            assert_eq!(square.flip().flip(), *square);
+        }
+        assert_eq!(Square::from_index(64), None);
+        assert_eq!(Square::E4.to_string(), "e4");
+        assert_eq!(Square::E4.file(), File::E);
+        assert_eq!(Square::E4.rank(), Rank::Fourth);
+        assert_eq!(Square::A1.flip(), Square::A8);
+        assert_eq!(Square::E4.offset(1, 2), Some(Square::F6));
+        assert_eq!(Square::H4.offset(1, 0), None);
+        assert_eq!(Square::A1.offset(0, -1), None);
+        assert!("i1".parse::<Square>().is_err());
+        assert!("a9".parse::<Square>().is_err());
+        assert!("a1a".parse::<Square>().is_err());
+    }
+}
@@ -1,21 +1,61 @@
 extern crate rand;
 #[cfg(test)]
 mod tests {
-    use core_sdk::board_representation::game_state::GameState;
+    use core_sdk::board_representation::game_state::{
+        Bitboard, Color, FenError, GameMoveType, GameResult, GameState, MoveError, PieceType,
+        PositionError, Square, BLACK, KINGSIDE, QUEENSIDE, WHITE,
+    };
     use core_sdk::board_representation::game_state_attack_container::GameStateAttackContainer;
+    use core_sdk::board_representation::game_status::Reason;
+    use core_sdk::board_representation::san::SanError;
+    use core_sdk::book::polyglot::{encode_move, BookEntry, BookSelection, PolyglotBook};
+    use core_sdk::evaluation::eval_params::{EvalParams, DEFAULT_EVAL_PARAMS};
+    use core_sdk::evaluation::nnue::{Network, EVAL_SCALE};
+    #[cfg(feature = "nnue")]
+    use core_sdk::evaluation::nnue::{FEATURES, FILE_SIZE, HIDDEN_SIZE};
+    use core_sdk::evaluation::parameters::Parameters;
+    use core_sdk::evaluation::pawn_hash::PawnHashTable;
     use core_sdk::evaluation::phase::Phase;
     use core_sdk::evaluation::psqt_evaluation::psqt;
-    use core_sdk::move_generation::makemove::make_move;
+    use core_sdk::evaluation::EvaluationScore;
+    use core_sdk::evaluation::{
+        eval_game_state, eval_game_state_from_null, eval_game_state_with_params,
+        eval_game_state_with_pawn_hash, trace_game_state, trace_sides,
+    };
+    use core_sdk::move_generation::makemove::{
+        make_move, make_move_mut, make_nullmove, unmake_move,
+    };
     use core_sdk::move_generation::movegen;
     use core_sdk::move_generation::movegen::MoveList;
-    use core_sdk::perft;
+    use core_sdk::search::alphabeta::{
+        principal_variation_search, singular_extension, MIN_SINGULAR_DEPTH,
+    };
+    use core_sdk::search::builder::Search;
+    use core_sdk::search::cache::{Cache, CacheEntry};
+    use core_sdk::search::observer::{Score, SearchInfo, SearchObserver, SearchResult};
     use core_sdk::search::reserved_memory::ReservedAttackContainer;
     use core_sdk::search::reserved_memory::ReservedMoveList;
+    use core_sdk::search::searcher::{
+        search_move, CancellationToken, InterThreadCommunicationSystem, Thread,
+    };
+    use core_sdk::search::skill::{Skill, MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO};
+    use core_sdk::search::timecontrol::{SearchLimits, TimeControl};
+    use core_sdk::search::{
+        CombinedSearchParameters, PrincipalVariation, ScoredPrincipalVariation, SearchInstruction,
+        MATE_SCORE, MAX_SEARCH_DEPTH,
+    };
+    use core_sdk::{perft, perft_mut, UCIOptions};
+    use extended_sdk::book_builder::{BookBuilder, BookFilter};
     use extended_sdk::misc::KING_BASE_PATH;
     use extended_sdk::pgn::pgn_reader::{parse_move, GameParser, PGNParser};
     use rand::Rng;
     use std::fs::File;
-    use std::io::BufReader;
+    use std::io::{BufReader, Write};
+    use std::sync::atomic::Ordering;
+    use std::sync::{Arc, Mutex};
+    use tuning::loading::LabelledGameState;
+    use tuning::nnue_training::{NetworkTrainer, TrainingPosition};
+    use tuning::q_search::QSearch;
 
     #[test]
     fn fen_test() {
@@ -162,6 +202,1338 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fen_error_test() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+        assert!(GameState::try_from_fen(&format!("{} w KQkq - 0 1", start)).is_ok());
+        assert!(GameState::try_from_fen(&format!("{}  w  KQkq -", start)).is_ok());
+        let cases = vec![
+            ("", FenError::MissingFields(0)),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq -",
+                FenError::BadRankCount(7),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4X3/8/PPPPPPPP/RNBQKBNR w KQkq -",
+                FenError::BadPieceChar('X', 4),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/9/8/PPPPPPPP/RNBQKBNR w KQkq -",
+                FenError::BadPieceChar('9', 4),
+            ),
+            (
+                "rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+                FenError::BadRankLength(7, 7),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq -",
+                FenError::BadRankLength(1, 9),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR w kq -",
+                FenError::MissingKing(0),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/3k4/PPPPPPPP/RNBQKBNR w KQ -",
+                FenError::TooManyKings(1),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq -",
+                FenError::BadSideToMove("x".to_owned()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx -",
+                FenError::IllegalCastling("KQkx".to_owned()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKkq -",
+                FenError::IllegalCastling("KKkq".to_owned()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Bkq -",
+                FenError::IllegalCastling("Bkq".to_owned()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3",
+                FenError::BadEnPassant("e3".to_owned()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq i6",
+                FenError::BadEnPassant("i6".to_owned()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1",
+                FenError::BadMoveCounter("x".to_owned()),
+            ),
+        ];
+        for (fen, error) in cases {
+            assert_eq!(GameState::try_from_fen(fen).err(), Some(error), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        assert_eq!(GameState::standard().validate(), Ok(()));
+        assert_eq!(
+            GameState::from_fen("rk2r2r/8/8/8/8/8/8/RK2R2R w EQe - 0 1").validate(),
+            Ok(())
+        );
+        let cases = vec![
+            (
+                "P3k3/8/8/8/8/8/8/4K2p w - - 0 1",
+                vec![PositionError::PawnsOnBackRank(
+                    Bitboard::from(Square::H1) | Square::A8,
+                )],
+            ),
+            (
+                "4k3/pppppppp/8/8/8/8/PPPPPPPP/NNN1K3 w - - 0 1",
+                vec![PositionError::TooManyPieces(WHITE)],
+            ),
+            (
+                "4k3/8/8/8/8/8/8/r3K3 b - - 0 1",
+                vec![PositionError::OpponentInCheck],
+            ),
+            (
+                "4k3/8/8/8/8/8/2n5/4K3 b - - 0 1",
+                vec![PositionError::OpponentInCheck],
+            ),
+            (
+                "4k3/8/8/8/7b/3n4/8/r3K3 w - - 0 1",
+                vec![PositionError::TooManyCheckers(3)],
+            ),
+            (
+                "r3k3/8/8/8/8/8/8/4K2R w Kkq - 0 1",
+                vec![PositionError::CastlingRights(BLACK, KINGSIDE)],
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/4K3 w KQ - 0 1",
+                vec![
+                    PositionError::CastlingRights(WHITE, KINGSIDE),
+                    PositionError::CastlingRights(WHITE, QUEENSIDE),
+                ],
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+                vec![PositionError::InvalidEnPassant(Square::E6.into())],
+            ),
+            (
+                "4k3/4p3/8/4p3/8/8/8/4K3 w - e6 0 1",
+                vec![PositionError::InvalidEnPassant(Square::E6.into())],
+            ),
+        ];
+        for (fen, errors) in cases {
+            assert_eq!(
+                GameState::from_fen(fen).validate(),
+                Err(errors.clone()),
+                "{}",
+                fen
+            );
+            assert_eq!(
+                GameState::try_from_fen(fen).err(),
+                Some(FenError::IllegalPosition(errors))
+            );
+        }
+        let mut g = GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        g.pieces[5][WHITE] |= 1u64;
+        assert_eq!(g.validate(), Err(vec![PositionError::KingCount(WHITE, 2)]));
+        g.pieces[5][WHITE] = 0u64;
+        assert_eq!(g.validate(), Err(vec![PositionError::KingCount(WHITE, 0)]));
+        assert!(GameState::try_from_fen("4k3/8/8/4pP2/8/8/8/4K3 w - e6 0 2").is_ok());
+    }
+
+    #[test]
+    fn chess960_fen_test() {
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9";
+        let g = GameState::from_fen(fen);
+        assert!(g.chess960);
+        assert_eq!(&g.to_fen(), fen);
+        assert_eq!(
+            &g.to_shredder_fen(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+        );
+        assert_eq!(
+            g.hash,
+            GameState::from_fen(
+                "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+            )
+            .hash
+        );
+        //Inner rooks need their file in X-FEN
+        let fen = "rk2r2r/8/8/8/8/8/8/RK2R2R w EQe - 0 1";
+        assert_eq!(&GameState::from_fen(fen).to_fen(), fen);
+        assert_eq!(
+            &GameState::from_fen("rkr5/8/8/8/8/8/8/RKR5 w Ca - 0 1").to_fen(),
+            "rkr5/8/8/8/8/8/8/RKR5 w Kq - 0 1"
+        );
+        //Standard positions keep KQkq
+        let g = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1");
+        assert_eq!(&g.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let g = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert!(!g.chess960);
+    }
+
+    #[test]
+    fn chess960_perft_test() {
+        let mut movelist = ReservedMoveList::default();
+        let mut attack_container = ReservedAttackContainer::default();
+        #[rustfmt::skip]
+        let cases = [
+            //https://www.chessprogramming.org/Chess960_Perft_Results
+            (21, 1, "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"),
+            (528, 2, "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"),
+            (12189, 3, "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"),
+            (326_672, 4, "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"),
+            (8_146_062, 5, "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"),
+            (21, 1, "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9"),
+            (807, 2, "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9"),
+            (18002, 3, "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9"),
+            (667_366, 4, "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9"),
+            (20, 1, "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9"),
+            (479, 2, "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9"),
+            (10471, 3, "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9"),
+            (273_318, 4, "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9"),
+            (22, 1, "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9"),
+            (593, 2, "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9"),
+            (13440, 3, "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9"),
+            (382_958, 4, "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9"),
+            (28, 1, "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9"),
+            (1120, 2, "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9"),
+            (31058, 3, "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9"),
+            (1_171_749, 4, "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9"),
+            //The castling rook shields the king's target square from the queen on a1
+            (18, 1, "1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1"),
+            (638, 2, "1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1"),
+            (12062, 3, "1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1"),
+            (439_358, 4, "1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1"),
+            //King and rook swap squares
+            (23, 1, "rk4r1/8/8/8/8/8/8/RK4R1 w GAga - 0 1"),
+            (442, 2, "rk4r1/8/8/8/8/8/8/RK4R1 w GAga - 0 1"),
+            (10065, 3, "rk4r1/8/8/8/8/8/8/RK4R1 w GAga - 0 1"),
+            (218_225, 4, "rk4r1/8/8/8/8/8/8/RK4R1 w GAga - 0 1"),
+            //King already stands on its target square
+            (24, 1, "1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1"),
+            (479, 2, "1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1"),
+            (10954, 3, "1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1"),
+            (237_166, 4, "1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1"),
+            (20, 1, "rkr5/8/8/8/8/8/8/RKR5 w CAca - 0 1"),
+            (328, 2, "rkr5/8/8/8/8/8/8/RKR5 w CAca - 0 1"),
+            (6855, 3, "rkr5/8/8/8/8/8/8/RKR5 w CAca - 0 1"),
+            (138_584, 4, "rkr5/8/8/8/8/8/8/RKR5 w CAca - 0 1"),
+        ];
+
+        for case in cases.iter() {
+            assert_eq!(
+                case.0,
+                perft(
+                    &GameState::from_fen(case.2),
+                    case.1,
+                    &mut movelist,
+                    &mut attack_container,
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn make_unmake_test() {
+        let mut movelist = ReservedMoveList::default();
+        let mut attack_container = ReservedAttackContainer::default();
+        let positions = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+            "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1",
+        ];
+        for fen in positions.iter() {
+            let mut g = GameState::from_fen(fen);
+            let expected = perft(&g, 3, &mut movelist, &mut attack_container);
+            assert_eq!(
+                perft_mut(&mut g, 3, &mut movelist, &mut attack_container),
+                expected
+            );
+            assert_eq!(&g.to_fen(), &GameState::from_fen(fen).to_fen());
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut movelist = movegen::MoveList::default();
+        let mut attack_container = GameStateAttackContainer::default();
+        for _i in 0..1000 {
+            let mut g = GameState::standard();
+            for _j in 0..200 {
+                attack_container.write_state(&g);
+                let agsi = movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+                if !agsi.stm_haslegalmove {
+                    break;
+                }
+                let mv = movelist.move_list[rng.gen_range(0, movelist.move_list.len())].0;
+                let before = g.clone();
+                let copied = make_move(&g, mv);
+                let undo = make_move_mut(&mut g, mv);
+                assert_eq!(g.to_fen(), copied.to_fen());
+                assert_eq!(g.hash, copied.hash);
+                assert_eq!(g.psqt, copied.psqt);
+                assert_eq!(g.phase.material_score, copied.phase.material_score);
+                assert_eq!(g.mailbox, copied.mailbox);
+                assert_eq!(g.mailbox, GameState::mailbox_from_pieces(&g.pieces));
+                unmake_move(&mut g, mv, undo);
+                assert_eq!(g.to_fen(), before.to_fen());
+                assert_eq!(g.hash, before.hash);
+                assert_eq!(g.psqt, before.psqt);
+                assert_eq!(g.phase.material_score, before.phase.material_score);
+                assert_eq!(g.mailbox, before.mailbox);
+                g = copied;
+            }
+        }
+    }
+
+    #[test]
+    fn mailbox_test() {
+        let g =
+            GameState::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
+        assert_eq!(
+            g.piece_on(Square::E1),
+            Some((PieceType::King, Color::White))
+        );
+        assert_eq!(
+            g.piece_on(Square::E7),
+            Some((PieceType::Queen, Color::Black))
+        );
+        assert_eq!(
+            g.piece_on(Square::D5),
+            Some((PieceType::Pawn, Color::White))
+        );
+        assert_eq!(
+            g.piece_on(Square::E4),
+            Some((PieceType::Pawn, Color::White))
+        );
+        assert_eq!(g.piece_on(Square::D4), None);
+        assert_eq!(g.mailbox, GameState::mailbox_from_pieces(&g.pieces));
+
+        let mut movelist = movegen::MoveList::default();
+        let mut attack_container = GameStateAttackContainer::default();
+        attack_container.write_state(&g);
+        movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+        let mv = movelist
+            .move_list
+            .iter()
+            .map(|gm| gm.0)
+            .find(|mv| mv.from == Square::E2 && mv.to == Square::A6)
+            .unwrap();
+        let g = make_move(&g, mv);
+        assert_eq!(g.piece_on(Square::E2), None);
+        assert_eq!(
+            g.piece_on(Square::A6),
+            Some((PieceType::Bishop, Color::White))
+        );
+        let g = make_nullmove(&g);
+        assert_eq!(g.mailbox, GameState::mailbox_from_pieces(&g.pieces));
+    }
+
+    #[test]
+    fn san_test() {
+        let g =
+            GameState::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
+        let cases = [
+            ("O-O", "e1g1"),
+            ("0-0-0", "e1c1"),
+            ("Bxa6!?", "e2a6"),
+            ("Nxf7", "e5f7"),
+            ("Qxf6+", "f3f6"),
+            ("d6", "d5d6"),
+            ("dxe6", "d5e6"),
+            ("a4", "a2a4"),
+            ("Rb1", "a1b1"),
+        ];
+        for (san, uci) in cases.iter() {
+            assert_eq!(format!("{:?}", g.parse_san(san).unwrap()), *uci);
+        }
+        assert_eq!(g.parse_san("  "), Err(SanError::Empty));
+        assert_eq!(
+            g.parse_san("Nxe9"),
+            Err(SanError::InvalidSyntax("Nxe9".to_owned()))
+        );
+        assert_eq!(
+            g.parse_san("Ke3"),
+            Err(SanError::IllegalMove("Ke3".to_owned()))
+        );
+        assert_eq!(
+            g.parse_san("Nh5"),
+            Err(SanError::IllegalMove("Nh5".to_owned()))
+        );
+
+        let g = GameState::from_fen("1n2k3/P1P5/8/8/1N3N2/8/8/4K3 w - - 0 1");
+        assert_eq!(format!("{:?}", g.parse_san("cxb8=N").unwrap()), "c7b8n");
+        assert_eq!(format!("{:?}", g.parse_san("axb8Q+").unwrap()), "a7b8q");
+        assert_eq!(format!("{:?}", g.parse_san("c8=Q+").unwrap()), "c7c8q");
+        assert_eq!(format!("{:?}", g.parse_san("Nfd5").unwrap()), "f4d5");
+        assert_eq!(
+            g.parse_san("Nd5"),
+            Err(SanError::AmbiguousMove("Nd5".to_owned()))
+        );
+        assert_eq!(
+            g.parse_san("c8"),
+            Err(SanError::IllegalMove("c8".to_owned()))
+        );
+
+        let g = GameState::from_fen("1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1");
+        assert_eq!(format!("{:?}", g.parse_san("O-O").unwrap()), "d1h1");
+        assert_eq!(g.parse_san("O-O").unwrap().to_san(&g), "O-O");
+        //The king would be in check from the queen once the rook has left b1
+        assert_eq!(
+            g.parse_san("O-O-O"),
+            Err(SanError::IllegalMove("O-O-O".to_owned()))
+        );
+
+        let g = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R2R2K1 w - - 0 1");
+        let mv = g.parse_san("Rd8#").unwrap();
+        assert_eq!(format!("{:?}", mv), "d1d8");
+        assert_eq!(mv.to_san(&g), "Rd8#");
+
+        //Round trip over random games
+        let mut rng = rand::thread_rng();
+        let mut movelist = movegen::MoveList::default();
+        let mut attack_container = GameStateAttackContainer::default();
+        for _i in 0..100 {
+            let start = GameState::standard();
+            let mut g = start.clone();
+            let mut line = Vec::new();
+            for _j in 0..200 {
+                attack_container.write_state(&g);
+                let agsi = movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+                if !agsi.stm_haslegalmove {
+                    break;
+                }
+                for gmv in movelist.move_list.iter() {
+                    assert_eq!(g.parse_san(&gmv.0.to_san(&g)), Ok(gmv.0));
+                }
+                let mv = movelist.move_list[rng.gen_range(0, movelist.move_list.len())].0;
+                line.push(mv);
+                g = make_move(&g, mv);
+            }
+            let sans = start.moves_to_san(&line);
+            let mut g = start;
+            for (mv, san) in line.iter().zip(sans.iter()) {
+                assert_eq!(&mv.to_san(&g), san);
+                g = make_move(&g, g.parse_san(san).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn uci_move_test() {
+        let g = GameState::from_fen("1n2k3/P1P5/8/8/1N3N2/8/8/4K3 w - - 0 1");
+        assert_eq!(format!("{:?}", g.parse_uci_move("c7b8n").unwrap()), "c7b8n");
+        assert_eq!(format!("{:?}", g.parse_uci_move("f4d5").unwrap()), "f4d5");
+        assert_eq!(
+            g.parse_uci_move("c7c8"),
+            Err(MoveError::IllegalMove("c7c8".to_owned()))
+        );
+        assert_eq!(
+            g.parse_uci_move("f4f5"),
+            Err(MoveError::IllegalMove("f4f5".to_owned()))
+        );
+        for desc in ["", "e2", "e2e4e", "c7c8k", "i1a1", "e2e44"].iter() {
+            assert_eq!(
+                g.parse_uci_move(desc),
+                Err(MoveError::InvalidSyntax(desc.to_string()))
+            );
+        }
+        let g = GameState::from_fen("1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1");
+        let castle = g.parse_uci_move("d1h1").unwrap();
+        assert_eq!(castle.move_type, GameMoveType::Castle);
+        assert!(g.is_legal(castle));
+        assert!(g.parse_uci_move("d1b1").is_err());
+
+        let mut rng = rand::thread_rng();
+        let mut movelist = movegen::MoveList::default();
+        let mut attack_container = GameStateAttackContainer::default();
+        for _i in 0..100 {
+            let mut g = GameState::standard();
+            let mut last_move = None;
+            for _j in 0..200 {
+                attack_container.write_state(&g);
+                let agsi = movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+                if !agsi.stm_haslegalmove {
+                    break;
+                }
+                for gmv in movelist.move_list.iter() {
+                    assert!(g.is_legal(gmv.0));
+                    assert_eq!(g.parse_uci_move(&format!("{:?}", gmv.0)), Ok(gmv.0));
+                }
+                //The opponent's last move can never be played by the side to move
+                if let Some(mv) = last_move {
+                    assert!(!g.is_legal(mv));
+                }
+                let mv = movelist.move_list[rng.gen_range(0, movelist.move_list.len())].0;
+                last_move = Some(mv);
+                g = make_move(&g, mv);
+            }
+        }
+    }
+
+    #[test]
+    fn game_status_test() {
+        let status = |fen: &str| GameState::from_fen(fen).game_status(&[]);
+        assert_eq!(
+            status("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"),
+            (GameResult::BlackWin, Some(Reason::Checkmate))
+        );
+        assert_eq!(
+            status("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+            (GameResult::Draw, Some(Reason::Stalemate))
+        );
+        assert_eq!(
+            status("7k/8/6K1/8/8/8/8/R7 b - - 100 80"),
+            (GameResult::Draw, Some(Reason::FiftyMoveRule))
+        );
+        //Mate takes precedence over the fifty move rule
+        assert_eq!(
+            status("R6k/8/6K1/8/8/8/8/8 b - - 100 80"),
+            (GameResult::WhiteWin, Some(Reason::Checkmate))
+        );
+        let dead = [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1K3 b - - 0 1",
+            "4k3/8/8/2b5/8/8/8/2B1K3 w - - 0 1",
+            "4kb2/8/8/8/8/4B3/8/2B1K3 w - - 0 1",
+        ];
+        for fen in dead.iter() {
+            assert_eq!(
+                status(fen),
+                (GameResult::Draw, Some(Reason::InsufficientMaterial))
+            );
+        }
+        let alive = [
+            "4k3/8/8/1b6/8/8/8/2B1K3 w - - 0 1",
+            "4k1n1/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/7p/4K3 w - - 0 1",
+        ];
+        for fen in alive.iter() {
+            assert_eq!(status(fen), (GameResult::Ingame, None));
+        }
+
+        let mut movelist = movegen::MoveList::default();
+        let mut attack_container = GameStateAttackContainer::default();
+        let mut history = Vec::new();
+        let mut g = GameState::standard();
+        for (i, mv) in [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ]
+        .iter()
+        .enumerate()
+        {
+            assert_eq!(g.game_status(&history), (GameResult::Ingame, None));
+            attack_container.write_state(&g);
+            let next = make_move(&g, parse_move(&g, mv, &mut movelist, &attack_container).0);
+            history.push(g);
+            g = next;
+            if i == 7 {
+                assert_eq!(
+                    g.game_status(&history),
+                    (GameResult::Draw, Some(Reason::ThreefoldRepetition))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn polyglot_key_test() {
+        //Reference keys from the Polyglot book format specification
+        let lines: [(&[&str], u64); 9] = [
+            (&[], 0x463b96181691fc9c),
+            (&["e2e4"], 0x823c9b50fd114196),
+            (&["e2e4", "d7d5"], 0x0756b94461c50fb0),
+            (&["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+            (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+            (
+                &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"],
+                0x652a607ca3f242c1,
+            ),
+            (
+                &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"],
+                0x00fdd303c946bdd9,
+            ),
+            (
+                &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"],
+                0x3c8123ea7b067637,
+            ),
+            (
+                &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"],
+                0x5c3f9b829b279560,
+            ),
+        ];
+        for (moves, key) in lines.iter() {
+            let mut g = GameState::standard();
+            for mv in moves.iter() {
+                g = make_move(&g, g.parse_uci_move(mv).unwrap());
+            }
+            assert_eq!(g.polyglot_key(), *key);
+        }
+    }
+
+    #[test]
+    fn polyglot_book_test() {
+        let start = GameState::standard();
+        let e4 = start.parse_uci_move("e2e4").unwrap();
+        let d4 = start.parse_uci_move("d2d4").unwrap();
+        let nf3 = start.parse_uci_move("g1f3").unwrap();
+        let after_e4 = make_move(&start, e4);
+        let c5 = after_e4.parse_uci_move("c7c5").unwrap();
+        let castle_pos =
+            GameState::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
+        let castle = castle_pos.parse_uci_move("e1g1").unwrap();
+        let entry = |g: &GameState, mv, weight| BookEntry {
+            key: g.polyglot_key(),
+            mv: encode_move(g, mv),
+            weight,
+            learn: 0,
+        };
+        let entries = [
+            entry(&after_e4, c5, 7),
+            entry(&start, e4, 10),
+            entry(&start, d4, 5),
+            entry(&start, nf3, 0),
+            //Illegal in the start position
+            BookEntry {
+                key: start.polyglot_key(),
+                mv: encode_move(&after_e4, c5),
+                weight: 100,
+                learn: 0,
+            },
+            entry(&castle_pos, castle, 1),
+        ];
+        //Castling is written as king takes rook
+        assert_eq!(entries[5].mv, (4 << 6) | 7);
+
+        let path = std::env::temp_dir().join("fabchess_polyglot_book_test.bin");
+        let bytes: Vec<u8> = entries.iter().flat_map(|e| e.to_bytes().to_vec()).collect();
+        std::fs::write(&path, &bytes).unwrap();
+        let book = PolyglotBook::open(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(book.entries.len(), entries.len());
+        assert!(book.entries.windows(2).all(|w| w[0].key <= w[1].key));
+
+        assert_eq!(book.book_moves(&start), vec![(e4, 10), (d4, 5), (nf3, 0)]);
+        assert_eq!(book.book_moves(&after_e4), vec![(c5, 7)]);
+        assert_eq!(book.book_moves(&castle_pos), vec![(castle, 1)]);
+        assert!(book.book_moves(&make_move(&start, d4)).is_empty());
+
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            book.get_move(&start, BookSelection::Best, &mut rng),
+            Some(e4)
+        );
+        let mut e4_count = 0;
+        for _ in 0..1000 {
+            let weighted = book.get_move(&start, BookSelection::Weighted, &mut rng);
+            assert!(weighted == Some(e4) || weighted == Some(d4));
+            if weighted == Some(e4) {
+                e4_count += 1;
+            }
+            let random = book.get_move(&start, BookSelection::Random, &mut rng);
+            assert!(random == Some(e4) || random == Some(d4));
+        }
+        assert!(e4_count > 550 && e4_count < 780);
+        assert_eq!(
+            book.get_move(&make_move(&start, d4), BookSelection::Weighted, &mut rng),
+            None
+        );
+
+        //A truncated last entry is ignored
+        let truncated = PolyglotBook::from_bytes(&bytes[..bytes.len() - 3]);
+        assert_eq!(truncated.entries.len(), entries.len() - 1);
+    }
+
+    #[test]
+    fn book_builder_test() {
+        let pgn = "[Event \"Test\"]\n[White \"A\"]\n[Black \"B\"]\n[Result \"1-0\"]\n[WhiteElo \"2500\"]\n[BlackElo \"2400\"]\n\n1. e4 c5 2. Nf3 1-0\n\n\
+                   [Event \"Test\"]\n[Result \"1/2-1/2\"]\n[WhiteElo \"2000\"]\n[BlackElo \"2000\"]\n\n1. e4 e5 1/2-1/2\n\n\
+                   [Event \"Test\"]\n[Result \"0-1\"]\n[WhiteElo \"2600\"]\n[BlackElo \"2600\"]\n\n1. d4 d5 0-1\n\n\
+                   [Event \"Test\"]\n[Result \"0-1\"]\n\n1. e4 c5 0-1\n";
+        let pgn_path = std::env::temp_dir().join("fabchess_book_builder_test.pgn");
+        let book_path = std::env::temp_dir().join("fabchess_book_builder_test.bin");
+        std::fs::write(&pgn_path, pgn).unwrap();
+        let start = GameState::standard();
+        let e4 = start.parse_san("e4").unwrap();
+        let after_e4 = make_move(&start, e4);
+        let c5 = after_e4.parse_san("c5").unwrap();
+        let e5 = after_e4.parse_san("e5").unwrap();
+
+        let mut builder = BookBuilder::new(BookFilter {
+            max_ply: 2,
+            ..BookFilter::default()
+        });
+        assert_eq!(
+            builder.add_pgn(pgn_path.to_str().unwrap()).unwrap().added,
+            4
+        );
+        //Nf3 lies behind the ply limit and d4 only lost
+        assert_eq!(builder.write(book_path.to_str().unwrap()).unwrap(), 4);
+        let book = PolyglotBook::open(book_path.to_str().unwrap()).unwrap();
+        assert_eq!(book.book_moves(&start), vec![(e4, 3)]);
+        assert_eq!(book.book_moves(&after_e4), vec![(c5, 2), (e5, 1)]);
+        assert!(book.book_moves(&make_move(&after_e4, c5)).is_empty());
+
+        let mut builder = BookBuilder::new(BookFilter {
+            results: vec![GameResult::WhiteWin, GameResult::BlackWin],
+            min_elo: Some(2300),
+            ..BookFilter::default()
+        });
+        //The draw fails both filters, the last game has no ratings
+        assert_eq!(
+            builder.add_pgn(pgn_path.to_str().unwrap()).unwrap().added,
+            2
+        );
+        let entries = builder.entries();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.windows(2).all(|w| w[0].key <= w[1].key));
+        let book = PolyglotBook { entries };
+        assert_eq!(book.book_moves(&start), vec![(e4, 2)]);
+        let after_e4_c5 = make_move(&after_e4, c5);
+        let nf3 = after_e4_c5.parse_san("Nf3").unwrap();
+        assert_eq!(book.book_moves(&after_e4_c5), vec![(nf3, 2)]);
+
+        std::fs::remove_file(&pgn_path).unwrap();
+        std::fs::remove_file(&book_path).unwrap();
+    }
+
+    #[test]
+    fn book_builder_bad_game_test() {
+        //The second game has an illegal move, the third starts from a Chess960 FEN tag
+        let pgn = "[Event \"Test\"]\n[Result \"1-0\"]\n\n1. e4 {best by test} e5 (1... c5) 2. Nf3 $1 1-0\n\n\
+                   [Event \"Test\"]\n[Result \"0-1\"]\n\n1. e4 e5 2. Ke3 Nc6 0-1\n\n\
+                   [Event \"Test\"]\n[FEN \"4k3/8/8/8/8/8/8/1R2K1R1 w GB - 0 1\"]\n[Result \"1-0\"]\n\n1. O-O Kd7 2. Rb7+ 1-0\n";
+        let pgn_path = std::env::temp_dir().join("fabchess_book_builder_bad_game_test.pgn");
+        std::fs::write(&pgn_path, pgn).unwrap();
+
+        let mut builder = BookBuilder::new(BookFilter::default());
+        let summary = builder.add_pgn(pgn_path.to_str().unwrap()).unwrap();
+        assert_eq!(summary.added, 2);
+        assert_eq!(summary.skipped, 1);
+        let book = PolyglotBook {
+            entries: builder.entries(),
+        };
+        let start = GameState::standard();
+        let e4 = start.parse_san("e4").unwrap();
+        assert_eq!(book.book_moves(&start), vec![(e4, 2)]);
+        //Neither the comment, the variation nor the annotation glyph are taken for moves
+        let after_e4 = make_move(&start, e4);
+        let after_e5 = make_move(&after_e4, after_e4.parse_san("e5").unwrap());
+        let nf3 = after_e5.parse_san("Nf3").unwrap();
+        assert_eq!(book.book_moves(&after_e5), vec![(nf3, 2)]);
+        let chess960 = GameState::try_from_fen("4k3/8/8/8/8/8/8/1R2K1R1 w GB - 0 1").unwrap();
+        let castle = chess960.parse_san("O-O").unwrap();
+        assert_eq!(book.book_moves(&chess960), vec![(castle, 2)]);
+
+        std::fs::remove_file(&pgn_path).unwrap();
+    }
+
+    #[test]
+    fn multi_pv_test() {
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            std::io::sink(),
+        ));
+        InterThreadCommunicationSystem::update_thread_count(&itcs, 1);
+        *itcs.cache() = Cache::with_size_threaded(1, 1);
+        itcs.uci_options().multi_pv = 3;
+        let lines_of = |state: &GameState| {
+            search_move(
+                Arc::clone(&itcs),
+                6,
+                state.clone(),
+                vec![],
+                TimeControl::Infinite,
+                SearchLimits::default(),
+                CancellationToken::default(),
+            );
+            itcs.multi_pv.lock().unwrap().clone()
+        };
+
+        let start = GameState::standard();
+        let lines = lines_of(&start);
+        assert_eq!(lines.len(), 3);
+        for (i, line) in lines.iter().enumerate() {
+            let mv = line.pv.pv[0].unwrap();
+            assert!(start.is_legal(mv));
+            assert!(lines[..i].iter().all(|other| other.pv.pv[0] != Some(mv)));
+            assert_eq!(line.depth, 6);
+        }
+
+        //Ra8 is the only mate
+        let back_rank = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        let lines = lines_of(&back_rank);
+        assert_eq!(
+            lines[0].pv.pv[0],
+            Some(back_rank.parse_uci_move("a1a8").unwrap())
+        );
+        assert_eq!(lines[0].score, MATE_SCORE - 1);
+        assert!(lines[1].score < MATE_SCORE - 200);
+        assert!(lines[2].score < MATE_SCORE - 200);
+
+        //Kg8 and Kg7 are the only legal moves
+        let lines = lines_of(&GameState::from_fen("7k/8/8/8/8/8/8/K6R b - - 0 1"));
+        assert_eq!(lines.len(), 2);
+        assert_ne!(lines[0].pv.pv[0], lines[1].pv.pv[0]);
+    }
+
+    #[test]
+    fn search_limits_test() {
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            std::io::sink(),
+        ));
+        InterThreadCommunicationSystem::update_thread_count(&itcs, 1);
+        *itcs.cache() = Cache::with_size_threaded(1, 1);
+        let search = |state: &GameState, limits: SearchLimits| {
+            itcs.cache().clear_threaded(1);
+            search_move(
+                Arc::clone(&itcs),
+                MAX_SEARCH_DEPTH as i16,
+                state.clone(),
+                vec![],
+                TimeControl::Infinite,
+                limits,
+                CancellationToken::default(),
+            );
+            itcs.multi_pv.lock().unwrap()[0].clone()
+        };
+
+        let start = GameState::standard();
+        let search_moves = vec![
+            start.parse_uci_move("a2a3").unwrap(),
+            start.parse_uci_move("h2h4").unwrap(),
+        ];
+        let best = search(
+            &start,
+            SearchLimits {
+                search_moves: search_moves.clone(),
+                nodes: Some(20_000),
+                mate: None,
+            },
+        );
+        assert!(search_moves.contains(&best.pv.pv[0].unwrap()));
+        let nodes = itcs.get_nodes_sum();
+        assert!(nodes >= 20_000 && nodes < 20_100);
+
+        //Qxf7 mates, which is found on the first iteration
+        let scholars_mate = GameState::from_fen(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+        );
+        let best = search(
+            &scholars_mate,
+            SearchLimits {
+                mate: Some(1),
+                ..SearchLimits::default()
+            },
+        );
+        assert_eq!(
+            best.pv.pv[0],
+            Some(scholars_mate.parse_uci_move("h5f7").unwrap())
+        );
+        assert_eq!(best.score, MATE_SCORE - 1);
+        assert_eq!(best.depth, 1);
+
+        //Any mate found is shorter than a mate in more moves than the search can look ahead
+        search_move(
+            Arc::clone(&itcs),
+            3,
+            scholars_mate.clone(),
+            vec![],
+            TimeControl::Infinite,
+            SearchLimits {
+                mate: Some(usize::MAX),
+                ..SearchLimits::default()
+            },
+            CancellationToken::default(),
+        );
+        let best = itcs.multi_pv.lock().unwrap()[0].clone();
+        assert_eq!(best.score, MATE_SCORE - 1);
+        assert_eq!(best.depth, 1);
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn ponder_test() {
+        let output = SharedOutput::default();
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            output.clone(),
+        ));
+        InterThreadCommunicationSystem::update_thread_count(&itcs, 1);
+        *itcs.cache() = Cache::with_size_threaded(1, 1);
+        itcs.ponder.store(true, Ordering::Relaxed);
+        let search_itcs = Arc::clone(&itcs);
+        let search = std::thread::spawn(move || {
+            search_move(
+                search_itcs,
+                4,
+                GameState::standard(),
+                vec![],
+                TimeControl::Incremental(1000, 0),
+                SearchLimits::default(),
+                CancellationToken::default(),
+            );
+        });
+        //Neither the depth limit nor the clock end a search on the opponent's time
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(!String::from_utf8_lossy(&output.0.lock().unwrap()).contains("bestmove"));
+        itcs.ponderhit();
+        search.join().unwrap();
+
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let bestmove_line: Vec<&str> = text
+            .lines()
+            .find(|line| line.starts_with("bestmove"))
+            .unwrap()
+            .split_whitespace()
+            .collect();
+        assert_eq!(bestmove_line.len(), 4);
+        assert_eq!(bestmove_line[2], "ponder");
+        let start = GameState::standard();
+        let best = start.parse_uci_move(bestmove_line[1]).unwrap();
+        assert!(make_move(&start, best)
+            .parse_uci_move(bestmove_line[3])
+            .is_ok());
+    }
+
+    #[test]
+    fn lazy_smp_test() {
+        let output = SharedOutput::default();
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            output.clone(),
+        ));
+        InterThreadCommunicationSystem::update_thread_count(&itcs, 4);
+        assert_eq!(itcs.tx.read().unwrap().len(), 3);
+        *itcs.cache() = Cache::with_size_threaded(1, 4);
+        let search_itcs = Arc::clone(&itcs);
+        let cancellation = CancellationToken::default();
+        let search_cancellation = cancellation.clone();
+        let search = std::thread::spawn(move || {
+            search_move(
+                search_itcs,
+                MAX_SEARCH_DEPTH as i16,
+                GameState::standard(),
+                vec![],
+                TimeControl::Infinite,
+                SearchLimits::default(),
+                search_cancellation,
+            );
+        });
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        cancellation.cancel();
+        search.join().unwrap();
+
+        //Every helper took part in the search
+        assert!(itcs
+            .nodes_searched()
+            .iter()
+            .all(|nodes| nodes.load(Ordering::Relaxed) > 0));
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let bestmove = text
+            .lines()
+            .find(|line| line.starts_with("bestmove"))
+            .unwrap()
+            .split_whitespace()
+            .nth(1)
+            .unwrap();
+        assert!(GameState::standard().parse_uci_move(bestmove).is_ok());
+
+        InterThreadCommunicationSystem::update_thread_count(&itcs, 1);
+        assert!(itcs.tx.read().unwrap().is_empty());
+        assert_eq!(itcs.nodes_searched().len(), 1);
+    }
+
+    #[derive(Default)]
+    struct CollectingObserver {
+        infos: Mutex<Vec<SearchInfo>>,
+        results: Mutex<Vec<SearchResult>>,
+    }
+    impl SearchObserver for CollectingObserver {
+        fn on_iteration(&self, info: &SearchInfo) {
+            self.infos.lock().unwrap().push(info.clone());
+        }
+        fn on_bestmove(&self, result: &SearchResult) {
+            self.results.lock().unwrap().push(result.clone());
+        }
+    }
+
+    #[test]
+    fn search_builder_test() {
+        let options = UCIOptions {
+            hash_size: 1,
+            ..UCIOptions::default()
+        };
+        let observer = Arc::new(CollectingObserver::default());
+        let start = GameState::standard();
+        let result = Search::new(start.clone())
+            .depth(5)
+            .options(options)
+            .observer(observer.clone())
+            .run();
+        assert!(start.is_legal(result.best_move));
+        assert_eq!(result.pv[0], result.best_move);
+        assert_eq!(result.ponder_move, result.pv.get(1).copied());
+        assert_eq!(result.depth, 5);
+        assert!(result.nodes > 0);
+        let infos = observer.infos.lock().unwrap();
+        assert!((1..=5).all(|depth| infos.iter().any(|info| info.depth == depth)));
+        assert_eq!(infos.last().unwrap().pv, result.pv);
+        assert_eq!(observer.results.lock().unwrap().len(), 1);
+
+        //An observer only replaces the UCI output for its own search
+        let output = SharedOutput::default();
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            output.clone(),
+        ));
+        *itcs.cache() = Cache::with_size_threaded(1, 1);
+        let scholars_mate = GameState::from_fen(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+        );
+        let observer = Arc::new(CollectingObserver::default());
+        let result = Search::new(scholars_mate.clone())
+            .depth(3)
+            .observer(observer.clone())
+            .run_on(&itcs);
+        assert_eq!(result.score, Score::Mate(1));
+        assert_eq!(observer.results.lock().unwrap().len(), 1);
+        assert!(output.0.lock().unwrap().is_empty());
+        Search::new(scholars_mate).depth(3).run_on(&itcs);
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert!(text.contains("score mate 1"));
+        assert!(text.contains("bestmove h5f7"));
+    }
+
+    #[test]
+    fn search_handle_test() {
+        let options = UCIOptions {
+            hash_size: 1,
+            ..UCIOptions::default()
+        };
+        let start = GameState::standard();
+        let first = Search::new(start.clone()).options(options).start();
+        let second = Search::new(start.clone()).options(options).start();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(first.is_running() && second.is_running());
+
+        //Searches in one process don't share their cancellation
+        first.stop();
+        let result = first.wait();
+        assert!(start.is_legal(result.best_move));
+        assert!(second.is_running());
+        second.stop();
+        assert!(start.is_legal(second.wait().best_move));
+
+        let finished = Search::new(start).depth(3).options(options).start();
+        while finished.is_running() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(finished.wait().depth, 3);
+    }
+
+    #[test]
+    fn skill_test() {
+        let mut options = UCIOptions::default();
+        assert_eq!(Skill::from_options(&options), None);
+        options.skill_level = 5;
+        assert_eq!(Skill::from_options(&options), Some(Skill { level: 5. }));
+        options.limit_strength = true;
+        options.elo = MIN_ELO;
+        assert_eq!(Skill::from_options(&options), Some(Skill { level: 0. }));
+        let weakest = Skill::from_elo(MIN_ELO);
+        let club = Skill::from_elo(1800);
+        let strongest = Skill::from_elo(MAX_ELO);
+        assert!(weakest.level < club.level && club.level < strongest.level);
+        assert!(strongest.level < MAX_SKILL_LEVEL as f64);
+        //Measured ratings map to their level, ratings in between to fractional levels
+        assert_eq!(Skill::from_elo(1910), Skill { level: 10. });
+        assert_eq!(Skill::from_elo(2090), Skill { level: 12.5 });
+        assert_eq!(Skill::from_elo(MIN_ELO - 1), weakest);
+        assert!(weakest.max_depth() < club.max_depth());
+        assert!(weakest.max_nodes() < club.max_nodes());
+
+        let start = GameState::standard();
+        let line = |mv: &str, score: i16| ScoredPrincipalVariation {
+            score,
+            pv: PrincipalVariation {
+                pv: vec![Some(start.parse_uci_move(mv).unwrap()), None],
+            },
+            depth: 1,
+        };
+        let lines = vec![
+            line("e2e4", 50),
+            line("d2d4", 40),
+            line("g1f3", 30),
+            line("f2f3", -500),
+        ];
+        let mut rng = rand::thread_rng();
+        let picks = |skill: Skill, rng: &mut rand::rngs::ThreadRng| {
+            (0..200)
+                .map(|_| skill.pick_line(&lines, rng))
+                .collect::<Vec<usize>>()
+        };
+        //Blunders are left to the weak levels
+        assert!(picks(strongest, &mut rng).iter().all(|pick| *pick != 3));
+        let weak_picks = picks(weakest, &mut rng);
+        assert!(weak_picks.iter().any(|pick| *pick != weak_picks[0]));
+
+        let result = Search::new(start.clone())
+            .options(UCIOptions {
+                hash_size: 1,
+                skill_level: 0,
+                ..UCIOptions::default()
+            })
+            .run();
+        assert!(start.is_legal(result.best_move));
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn contempt_test() {
+        //White has nothing better than a perpetual check
+        let perpetual = GameState::from_fen("6k1/6p1/8/6KQ/1r6/q2b4/8/8 w - - 0 1");
+        let score_with = |contempt: i16, analyse_mode: bool| {
+            Search::new(perpetual.clone())
+                .depth(8)
+                .options(UCIOptions {
+                    hash_size: 1,
+                    contempt,
+                    analyse_mode,
+                    ..UCIOptions::default()
+                })
+                .run()
+                .score
+        };
+        assert_eq!(score_with(0, false), Score::Centipawns(0));
+        assert_eq!(score_with(50, false), Score::Centipawns(-50));
+        assert_eq!(score_with(-50, false), Score::Centipawns(50));
+        assert_eq!(score_with(50, true), Score::Centipawns(0));
+    }
+
+    #[test]
+    fn singular_extension_test() {
+        let search_with = |fen: &str, singular_extensions: bool| {
+            Search::new(GameState::from_fen(fen))
+                .depth(10)
+                .options(UCIOptions {
+                    hash_size: 1,
+                    singular_extensions,
+                    singular_depth: MIN_SINGULAR_DEPTH,
+                    ..UCIOptions::default()
+                })
+                .run()
+        };
+        //Nf6+ gxf6 Bxf7# needs the quiet king to be mated after a sacrifice
+        let mate = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1";
+        for singular_extensions in [false, true].iter() {
+            let result = search_with(mate, *singular_extensions);
+            assert_eq!(result.score, Score::Mate(2));
+            assert_eq!(format!("{:?}", result.best_move), "d5f6");
+        }
+        //Taking the hanging queen is far better than anything else, so it is singular and extended
+        let recapture = "r3k2r/ppp2ppp/2n5/3q4/8/2N5/PPP2PPP/R3K2R w KQkq - 0 1";
+        let without = search_with(recapture, false);
+        let with = search_with(recapture, true);
+        assert_eq!(format!("{:?}", with.best_move), "c3d5");
+        assert_eq!(with.best_move, without.best_move);
+        assert_ne!(with.nodes, without.nodes);
+
+        //Verifies the TT move of a position searched to depth 8 before, against the given beta
+        let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(
+            std::io::sink(),
+        ));
+        InterThreadCommunicationSystem::update_thread_count(&itcs, 1);
+        *itcs.cache() = Cache::with_size_threaded(1, 1);
+        let mut thread = Thread::new(0, Arc::clone(&itcs), PawnHashTable::default());
+        thread.singular_extensions = true;
+        thread.singular_depth = MIN_SINGULAR_DEPTH;
+        let mut verify = |state: &GameState, beta_below_score: i16| {
+            let color = if state.color_to_move == WHITE { 1 } else { -1 };
+            principal_variation_search(
+                CombinedSearchParameters::from(-16000, 16000, 8, state, color, 1),
+                &mut thread,
+            );
+            let ce = itcs.cache().get(state.hash).probe(state.hash).unwrap();
+            let tt_move = CacheEntry::u16_to_mv(ce.mv, state);
+            let beta = ce.score - beta_below_score;
+            thread.history.push(state.hash, state.half_moves == 0);
+            let mut singular = false;
+            let instruction = singular_extension(
+                &CombinedSearchParameters::from(beta - 1, beta, 8, state, color, 1),
+                &mut thread,
+                tt_move,
+                None,
+                &mut singular,
+            );
+            let singular_beta = ce.score - thread.singular_margin * 8;
+            (tt_move, singular_beta, instruction, singular)
+        };
+        let recapture = GameState::from_fen(recapture);
+        let (tt_move, _, instruction, singular) = verify(&recapture, 0);
+        assert_eq!(format!("{:?}", tt_move), "c3d5");
+        assert!(matches!(instruction, SearchInstruction::ContinueSearching));
+        assert!(singular);
+        //In the starting position many moves are about as good as the TT move. With beta below
+        //the singular bound they fail high as well, which cuts the node (multi-cut)
+        let (_, singular_beta, instruction, singular) = verify(&GameState::standard(), 50);
+        assert!(matches!(
+            instruction,
+            SearchInstruction::StopSearching(score) if score == singular_beta
+        ));
+        assert!(!singular);
+        #[cfg(feature = "search-statistics")]
+        {
+            assert!(thread.search_statistics.singular_nodes >= 2);
+        }
+    }
+
+    #[cfg(feature = "nnue")]
+    #[test]
+    fn nnue_test() {
+        let mut rng = rand::thread_rng();
+        let mut random_weights =
+            |len: usize| -> Vec<i16> { (0..len).map(|_| rng.gen_range(-64, 64)).collect() };
+        let network = Network::new(
+            random_weights(FEATURES * HIDDEN_SIZE),
+            random_weights(HIDDEN_SIZE),
+            random_weights(2 * HIDDEN_SIZE),
+            0,
+        );
+        let bytes = network.to_bytes();
+        assert_eq!(bytes.len(), FILE_SIZE);
+        assert_eq!(Network::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+        assert!(Network::from_bytes(&bytes[..FILE_SIZE - 1]).is_err());
+        assert!(Network::from_bytes(&bytes[4..]).is_err());
+
+        //The accumulators are updated incrementally after both ways of making moves, castling,
+        //promotions and king bucket changes included
+        let positions = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "1r1k3r/8/8/8/8/8/8/qR1K3R w HBhb - 0 1",
+        ];
+        let mut movelist = movegen::MoveList::default();
+        let mut attack_container = GameStateAttackContainer::default();
+        for fen in positions.iter() {
+            for _i in 0..50 {
+                let mut g = GameState::from_fen(fen);
+                network.update(&mut g);
+                assert_eq!(g.nnue.values, network.accumulator(&g.pieces).values);
+                for _j in 0..100 {
+                    attack_container.write_state(&g);
+                    let agsi = movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+                    if !agsi.stm_haslegalmove {
+                        break;
+                    }
+                    let mv = movelist.move_list[rng.gen_range(0, movelist.move_list.len())].0;
+                    let before = g.nnue;
+                    let mut copied = make_move(&g, mv);
+                    network.update(&mut copied);
+                    let undo = make_move_mut(&mut g, mv);
+                    network.update(&mut g);
+                    let fresh = network.accumulator(&g.pieces);
+                    assert_eq!(copied.nnue.values, fresh.values);
+                    assert_eq!(g.nnue.values, fresh.values);
+                    if rng.gen_range(0, 4) == 0 {
+                        unmake_move(&mut g, mv, undo);
+                        assert_eq!(g.nnue.values, before.values);
+                        g = copied;
+                    }
+                }
+            }
+        }
+
+        //Accumulators missing more than the last move are computed from scratch
+        let mut g = GameState::from_fen(positions[0]);
+        network.update(&mut g);
+        attack_container.write_state(&g);
+        movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+        let g = make_move(&g, movelist.move_list[0].0);
+        let expected = network.evaluate(&network.accumulator(&g.pieces), g.color_to_move);
+        assert_eq!(network.evaluate_state(&g), expected);
+        attack_container.write_state(&g);
+        movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+        let g = make_move(&g, movelist.move_list[0].0);
+        let expected = network.evaluate(&network.accumulator(&g.pieces), g.color_to_move);
+        assert_eq!(network.evaluate_state(&g), expected);
+
+        //Both sides see the board the same way
+        let white = GameState::from_fen(positions[0]);
+        let black =
+            GameState::from_fen("r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq -");
+        assert_eq!(
+            network.evaluate_state(&white),
+            network.evaluate_state(&black)
+        );
+    }
+
+    #[test]
+    fn nnue_training_test() {
+        let labelled = [
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+                1.,
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                0.5,
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -", 0.5),
+            ("4k3/8/8/8/8/8/3PPP2/4K3 b - -", 1.),
+            ("rnbqkbnr/pppppppp/8/8/8/8/8/4K3 w kq -", 0.),
+        ];
+        let mut q_search = QSearch::default();
+        let mut positions: Vec<TrainingPosition> = labelled
+            .iter()
+            .map(|(fen, label)| {
+                let position = LabelledGameState {
+                    game_state: GameState::from_fen(fen),
+                    label: *label,
+                };
+                TrainingPosition::new(&position, 1.1155, 0.5, &mut q_search)
+            })
+            .collect();
+        //Results are from white's point of view, targets from the side to move's
+        assert!(positions[3].target < 0.5);
+        assert!(positions[4].target < 0.1);
+
+        let mut trainer = NetworkTrainer::new(0.01, 2);
+        let start_error = trainer.loss(&positions);
+        for _ in 0..50 {
+            trainer.train_epoch(&mut positions, 2);
+        }
+        assert!(trainer.loss(&positions) < start_error / 4.);
+
+        //The exported network evaluates like the trainer, up to quantization
+        let bytes = trainer.to_network().to_bytes();
+        let network = Network::from_bytes(&bytes).unwrap();
+        for (fen, _) in labelled.iter() {
+            let g = GameState::from_fen(fen);
+            let position = TrainingPosition::new(
+                &LabelledGameState {
+                    game_state: g.clone(),
+                    label: 0.5,
+                },
+                1.1155,
+                0.5,
+                &mut q_search,
+            );
+            let expected = trainer.evaluate(&position) * EVAL_SCALE as f32;
+            let eval = network.evaluate(&network.accumulator(&g.pieces), g.color_to_move);
+            assert!((f32::from(eval) - expected).abs() < 30.);
+        }
+    }
+
     #[test]
     fn zobrist_hash_test() {
         //Tests incremental update of hash
@@ -183,6 +1555,7 @@ mod tests {
                         g.en_passant,
                     )
                 );
+                assert_eq!(g.pawn_hash, GameState::calculate_pawn_hash(&g.pieces));
                 attack_container.write_state(&g);
                 let agsi = movegen::generate_moves(&g, false, &mut movelist, &attack_container);
                 if !agsi.stm_haslegalmove {
@@ -196,6 +1569,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pawn_hash_test() {
+        //Evaluations with the pawn hash table, missing and hitting it, equal the uncached ones
+        let mut rng = rand::thread_rng();
+        let mut movelist = movegen::MoveList::default();
+        let mut attack_container = GameStateAttackContainer::default();
+        let mut pawn_hash = PawnHashTable::default();
+        for _i in 0..1000 {
+            let mut g = GameState::standard();
+            for _j in 0..200 {
+                attack_container.write_state(&g);
+                let eval = eval_game_state(&g, &attack_container, -16000, 16000).final_eval;
+                for k in 0..2 {
+                    let (cached, hit) = eval_game_state_with_pawn_hash(
+                        &g,
+                        &attack_container,
+                        None,
+                        &mut pawn_hash,
+                        -16000,
+                        16000,
+                    );
+                    assert_eq!(cached.final_eval, eval);
+                    //The first evaluation stored the pawn structure
+                    assert!(k == 0 || hit);
+                }
+                let agsi = movegen::generate_moves(&g, false, &mut movelist, &attack_container);
+                if !agsi.stm_haslegalmove {
+                    break;
+                }
+                let mv = movelist.move_list[rng.gen_range(0, movelist.move_list.len())].0;
+                //Unmaking restores the pawn key as well
+                let pawn_key = g.pawn_hash;
+                let undo = make_move_mut(&mut g, mv);
+                assert_eq!(g.pawn_hash, GameState::calculate_pawn_hash(&g.pieces));
+                unmake_move(&mut g, mv, undo);
+                assert_eq!(g.pawn_hash, pawn_key);
+                g = make_move(&g, mv);
+            }
+        }
+    }
+
     #[test]
     fn phase_incremental() {
         let mut rng = rand::thread_rng();
@@ -222,6 +1636,101 @@ mod tests {
             }
         }
     }
+    #[test]
+    fn eval_params_test() {
+        //The compiled-in parameters survive the tuner's and the engine's formats unchanged
+        assert_eq!(Parameters::default().to_eval_params(), DEFAULT_EVAL_PARAMS);
+        let text = DEFAULT_EVAL_PARAMS.to_string();
+        let params = EvalParams::parse(&text).unwrap();
+        assert_eq!(params, DEFAULT_EVAL_PARAMS);
+        assert_eq!(params.to_string(), text);
+
+        let mut modified = DEFAULT_EVAL_PARAMS;
+        modified.psqt_knight[3][4] = EvaluationScore(-7, 12);
+        modified.knight_value_with_pawns[16] = 42;
+        assert_eq!(EvalParams::parse(&modified.to_string()).unwrap(), modified);
+
+        let tempo = text.lines().find(|l| l.starts_with("tempo_bonus")).unwrap();
+        assert!(EvalParams::parse(&text.replace(tempo, "")).is_err());
+        assert!(EvalParams::parse(&text.replace(tempo, "tempo_bonus 10")).is_err());
+        assert!(EvalParams::parse(&text.replace(tempo, "tempo_bonus 10 x")).is_err());
+        assert!(EvalParams::parse(&format!("{}{}\n", text, tempo)).is_err());
+        assert!(EvalParams::parse(&text.replacen("FabChess", "Other", 1)).is_err());
+
+        //Loaded parameters are what the evaluation uses. Both positions are in the midgame, so
+        //midgame scores count unscaled
+        let g = GameState::standard();
+        let attacks = GameStateAttackContainer::from_state(&g);
+        let eval = eval_game_state(&g, &attacks, -16000, 16000).final_eval;
+        assert_eq!(
+            eval_game_state_with_params(&g, &attacks, &params).final_eval,
+            eval
+        );
+        let mut modified = DEFAULT_EVAL_PARAMS;
+        modified.tempo_bonus.0 += 10;
+        assert_eq!(
+            eval_game_state_with_params(&g, &attacks, &modified).final_eval,
+            eval + 10
+        );
+
+        //The piece-square score kept up by the position is left for the loaded tables. Only
+        //white's knight on e5 stands on the changed square
+        let g =
+            GameState::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
+        assert!((g.phase.phase - 128.).abs() < std::f64::EPSILON);
+        let attacks = GameStateAttackContainer::from_state(&g);
+        let eval = eval_game_state(&g, &attacks, -16000, 16000).final_eval;
+        let mut modified = DEFAULT_EVAL_PARAMS;
+        let e5 = Square::E5;
+        modified.psqt_knight[e5.rank().index()][e5.file().index()].0 += 10;
+        assert_eq!(
+            eval_game_state_with_params(&g, &attacks, &modified).final_eval,
+            eval + 10
+        );
+        let mut pawn_hash = PawnHashTable::default();
+        assert_eq!(
+            eval_game_state_with_pawn_hash(
+                &g,
+                &attacks,
+                Some(&modified),
+                &mut pawn_hash,
+                -16000,
+                16000
+            )
+            .0
+            .final_eval,
+            eval + 10
+        );
+    }
+
+    #[test]
+    fn eval_trace_test() {
+        //The per-side breakdown printed by the eval command adds up to the engine's evaluation
+        let params = Parameters::default();
+        for fen in &[
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq -",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -",
+            "6k1/5ppp/8/8/8/8/1r3PPP/4R1K1 b - -",
+        ] {
+            let g = GameState::from_fen(fen);
+            let traced = trace_game_state(&g);
+            assert_eq!(traced.final_eval, eval_game_state_from_null(&g).final_eval);
+            let sides = trace_sides(&g);
+            let mut terms = (0., 0.);
+            for side in sides.iter() {
+                for term in side.terms(&params).iter() {
+                    terms = (terms.0 + term.0, terms.1 + term.1);
+                }
+            }
+            let phase = sides[0].phase;
+            let split = (terms.0 * phase + terms.1 / 1.5 * (128. - phase)) / 128.;
+            let whole = sides[0].evaluate(&params) + sides[1].evaluate(&params);
+            assert!((split - whole).abs() < 1e-6);
+            assert!((whole - f64::from(traced.final_eval)).abs() <= 2.);
+        }
+    }
+
     #[test]
     fn psqt_incremental_test() {
         let mut rng = rand::thread_rng();
@@ -229,13 +1738,13 @@ mod tests {
         let mut attack_container = GameStateAttackContainer::default();
         let mut _eval = core_sdk::evaluation::EvaluationResult {
             final_eval: 0,
-            trace: core_sdk::evaluation::trace::Trace::default(),
+            trace: None,
         };
 
         for _i in 0..100_000 {
             let mut g = GameState::standard();
-            let w_psqt = psqt(true, &g.pieces, &mut _eval);
-            let b_psqt = psqt(false, &g.pieces, &mut _eval);
+            let w_psqt = psqt(true, &g.pieces, &DEFAULT_EVAL_PARAMS, &mut _eval);
+            let b_psqt = psqt(false, &g.pieces, &DEFAULT_EVAL_PARAMS, &mut _eval);
             assert_eq!(g.psqt, w_psqt - b_psqt);
             for _j in 0..200 {
                 attack_container.write_state(&g);
@@ -247,8 +1756,8 @@ mod tests {
                     &g,
                     movelist.move_list[rng.gen_range(0, movelist.move_list.len())].0,
                 );
-                let w_psqt = psqt(true, &g.pieces, &mut _eval);
-                let b_psqt = psqt(false, &g.pieces, &mut _eval);
+                let w_psqt = psqt(true, &g.pieces, &DEFAULT_EVAL_PARAMS, &mut _eval);
+                let b_psqt = psqt(false, &g.pieces, &DEFAULT_EVAL_PARAMS, &mut _eval);
                 assert_eq!(g.psqt, w_psqt - b_psqt);
             }
         }
@@ -265,7 +1774,10 @@ mod tests {
             };
             let reader = BufReader::new(file);
             let parser = GameParser {
-                pgn_parser: PGNParser { reader },
+                pgn_parser: PGNParser {
+                    reader,
+                    tags: Vec::new(),
+                },
                 is_opening: false,
                 opening_load_untilply: 0usize,
                 move_list: movegen::MoveList::default(),
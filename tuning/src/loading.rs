@@ -88,6 +88,13 @@ pub fn load_positions(
                 } else {
                     panic!(format!("Invalid split {}", fen_split[1]));
                 };
+                let game_state = match GameState::try_from_fen(fen_split[0]) {
+                    Ok(state) => state,
+                    Err(e) => {
+                        eprintln!("Skipping invalid FEN {}: {}", fen_split[0], e);
+                        continue;
+                    }
+                };
                 if is_newgame {
                     is_newgame = false;
                     stats.games += 1;
@@ -101,7 +108,7 @@ pub fn load_positions(
                 }
 
                 buf.push(LabelledGameState {
-                    game_state: GameState::from_fen(fen_split[0]),
+                    game_state,
                     label: game_result,
                 });
             }
@@ -124,10 +131,13 @@ pub fn load_positions(
             } else {
                 0.0
             };
-            buf.push(LabelledGameState {
-                game_state: GameState::from_fen(fen),
-                label: game_result,
-            });
+            match GameState::try_from_fen(fen) {
+                Ok(game_state) => buf.push(LabelledGameState {
+                    game_state,
+                    label: game_result,
+                }),
+                Err(e) => eprintln!("Skipping invalid FEN {}: {}", fen, e),
+            }
         }
         return;
     }
@@ -2,12 +2,14 @@ extern crate core;
 extern crate rand;
 
 pub mod loading;
+pub mod nnue_training;
+pub mod q_search;
 
 use crate::loading::{load_positions, FileFormatSupported, LabelledGameState, Statistics};
 use core_sdk::board_representation::game_state::{BLACK, WHITE};
-use core_sdk::evaluation::eval_game_state_from_null;
 use core_sdk::evaluation::parameters::Parameters;
 use core_sdk::evaluation::trace::Trace;
+use core_sdk::evaluation::trace_game_state;
 use core_sdk::evaluation::{EG, MG};
 use rand::{seq::SliceRandom, thread_rng};
 
@@ -81,11 +83,11 @@ pub fn main() {
 pub fn init_texel_states(labelledstates: Vec<LabelledGameState>) -> Vec<TexelState> {
     let mut res: Vec<TexelState> = Vec::with_capacity(1);
     for state in labelledstates {
-        let eval = eval_game_state_from_null(&state.game_state);
+        let eval = trace_game_state(&state.game_state);
         res.push(TexelState {
             label: state.label,
             eval: eval.final_eval as f64,
-            trace: eval.trace,
+            trace: *eval.trace.expect("traced evaluation"),
         });
     }
     res
@@ -873,7 +875,10 @@ pub fn texel_tuning(tuner: &mut Tuner) {
             tuner
                 .params
                 .write_to_file(&format!("{}tunebest.txt", PARAM_FILE));
-            println!("Saved new best params in tunebest.txt");
+            tuner
+                .params
+                .write_eval_params(&format!("{}tunebest.params", PARAM_FILE));
+            println!("Saved new best params in tunebest.txt and tunebest.params");
         } else {
             lr /= 1.25;
         }
@@ -882,6 +887,9 @@ pub fn texel_tuning(tuner: &mut Tuner) {
             tuner
                 .params
                 .write_to_file(&format!("{}tune{}.txt", PARAM_FILE, epoch + 1));
+            tuner
+                .params
+                .write_eval_params(&format!("{}tune{}.params", PARAM_FILE, epoch + 1));
             println!("Saved general progress params in tune.txt");
         }
     }
@@ -0,0 +1,100 @@
+use std::fs;
+use tuning::loading::{load_positions, FileFormatSupported, LabelledGameState, Statistics};
+use tuning::nnue_training::{NetworkTrainer, TrainingPosition};
+use tuning::q_search::QSearch;
+
+//Positions in the own encoding (.txt) or EPD
+const POSITION_FILE: &str = "D:/FenCollection/Zuri/quiet-labeled.epd";
+//Checkpoints and networks are written here
+const NETWORK_DIR: &str = "D:/FenCollection/Networks/";
+//Continue training from a checkpoint instead of random weights
+const START_CHECKPOINT: Option<&str> = None;
+
+//Scale of the sigmoid turning the classical evaluation into a winning chance, see the texel tuner
+const K: f64 = 1.1155;
+//0 trains on the q-search score only, 1 on the game results only
+const RESULT_WEIGHT: f64 = 0.5;
+const START_LEARNING_RATE: f32 = 0.001;
+const BATCH_SIZE: usize = 16384;
+const THREADS: usize = 4;
+//Part of the positions left out of training to measure progress
+const VALIDATION_SHARE: f64 = 0.05;
+const EPOCHS: usize = 100;
+const CHECKPOINT_EVERY: usize = 5;
+
+fn main() {
+    //Step 1. Load the positions and compute the features and targets once
+    let mut stats = Statistics::default();
+    let mut labelled: Vec<LabelledGameState> = Vec::new();
+    load_positions(
+        POSITION_FILE,
+        if POSITION_FILE.ends_with(".txt") {
+            FileFormatSupported::OwnEncoding
+        } else if POSITION_FILE.ends_with("epd") {
+            FileFormatSupported::EPD
+        } else {
+            panic!("Invalid position file encoding!")
+        },
+        &mut labelled,
+        &mut stats,
+    );
+    println!(
+        "Loaded file {} with {} positions!",
+        POSITION_FILE,
+        labelled.len()
+    );
+    let mut q_search = QSearch::default();
+    let mut positions: Vec<TrainingPosition> = labelled
+        .iter()
+        .map(|position| TrainingPosition::new(position, K, RESULT_WEIGHT, &mut q_search))
+        .collect();
+    drop(labelled);
+    let validation_size = (positions.len() as f64 * VALIDATION_SHARE) as usize;
+    let validation = positions.split_off(positions.len() - validation_size);
+    println!(
+        "Training on {} positions, validating on {}",
+        positions.len(),
+        validation.len()
+    );
+
+    //Step 2. Train, keeping the network with the lowest validation error
+    fs::create_dir_all(NETWORK_DIR).expect("Unable to create network directory");
+    let mut trainer = match START_CHECKPOINT {
+        Some(path) => NetworkTrainer::load_checkpoint(path, START_LEARNING_RATE, THREADS)
+            .unwrap_or_else(|e| panic!("Unable to load checkpoint {}: {}", path, e)),
+        None => NetworkTrainer::new(START_LEARNING_RATE, THREADS),
+    };
+    let mut best_error = trainer.loss(&validation);
+    println!("Validation error in epoch 0: {}", best_error);
+    for epoch in 1..=EPOCHS {
+        let training_error = trainer.train_epoch(&mut positions, BATCH_SIZE);
+        let error = trainer.loss(&validation);
+        println!(
+            "Epoch {}: training error {}, validation error {}",
+            epoch, training_error, error
+        );
+        if error < best_error {
+            best_error = error;
+            let path = format!("{}best.nnue", NETWORK_DIR);
+            trainer
+                .to_network()
+                .write(&path)
+                .unwrap_or_else(|e| panic!("Unable to write {}: {}", path, e));
+            println!("Saved new best network in {}", path);
+        } else {
+            trainer.learning_rate /= 2.;
+        }
+        if epoch % CHECKPOINT_EVERY == 0 {
+            let path = format!("{}epoch{}.ckpt", NETWORK_DIR, epoch);
+            trainer
+                .save_checkpoint(&path)
+                .unwrap_or_else(|e| panic!("Unable to write {}: {}", path, e));
+            let path = format!("{}epoch{}.nnue", NETWORK_DIR, epoch);
+            trainer
+                .to_network()
+                .write(&path)
+                .unwrap_or_else(|e| panic!("Unable to write {}: {}", path, e));
+            println!("Saved checkpoint of epoch {}", epoch);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::loading::LabelledGameState;
+use crate::q_search::QSearch;
+use crate::sigmoid;
+use core_sdk::board_representation::game_state::{GameState, KING, WHITE};
+use core_sdk::evaluation::nnue::{feature_index, Network, FEATURES, HIDDEN_SIZE, QA, QB};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::fs;
+use std::io;
+use std::thread;
+
+//All parameters are kept in one vector: feature weights, feature biases, output weights and the
+//output bias. An accumulator value of 1.0 corresponds to QA in the engine, an output of 1.0 to
+//EVAL_SCALE centipawns
+const FEATURE_BIASES: usize = FEATURES * HIDDEN_SIZE;
+const OUTPUT_WEIGHTS: usize = FEATURE_BIASES + HIDDEN_SIZE;
+const OUTPUT_BIAS: usize = OUTPUT_WEIGHTS + 2 * HIDDEN_SIZE;
+pub const PARAMETERS: usize = OUTPUT_BIAS + 1;
+
+const ADAM_BETA1: f32 = 0.9;
+const ADAM_BETA2: f32 = 0.999;
+const ADAM_EPSILON: f32 = 1e-8;
+
+pub struct TrainingPosition {
+    //Active features, from the point of view of the side to move first
+    pub features: [Vec<u16>; 2],
+    //Expected score of the side to move, between 0 and 1
+    pub target: f32,
+}
+
+impl TrainingPosition {
+    //The target blends the game result with the q-search score of the classical evaluation, so
+    //hanging pieces and pending recaptures are resolved before the position is scored
+    pub fn new(
+        position: &LabelledGameState,
+        k: f64,
+        result_weight: f64,
+        q_search: &mut QSearch,
+    ) -> Self {
+        let state = &position.game_state;
+        let score = f64::from(q_search.search(state).0);
+        let result = if state.color_to_move == WHITE {
+            position.label
+        } else {
+            1. - position.label
+        };
+        TrainingPosition {
+            features: [
+                active_features(state, state.color_to_move),
+                active_features(state, 1 - state.color_to_move),
+            ],
+            target: (result_weight * result + (1. - result_weight) * sigmoid(k, score)) as f32,
+        }
+    }
+}
+
+pub fn active_features(state: &GameState, perspective: usize) -> Vec<u16> {
+    let king_square = state.pieces[KING][perspective].trailing_zeros() as usize;
+    let mut res = Vec::with_capacity(32);
+    for (piece_index, piece_boards) in state.pieces.iter().enumerate() {
+        for (color, board) in piece_boards.iter().enumerate() {
+            let mut board = *board;
+            while board != 0u64 {
+                let square = board.trailing_zeros() as usize;
+                board ^= 1u64 << square;
+                res.push(
+                    feature_index(perspective, king_square, piece_index, color, square) as u16,
+                );
+            }
+        }
+    }
+    res
+}
+
+//Floating point version of the engine's network, trained with Adam on the squared error between
+//the predicted winning chance and the target
+pub struct NetworkTrainer {
+    pub parameters: Vec<f32>,
+    pub learning_rate: f32,
+    pub threads: usize,
+    adam_m: Vec<f32>,
+    adam_v: Vec<f32>,
+    adam_steps: i32,
+    //One gradient buffer per thread
+    gradients: Vec<Vec<f32>>,
+    //Seeded, so a training run can be repeated
+    rng: StdRng,
+}
+
+impl NetworkTrainer {
+    pub fn new(learning_rate: f32, threads: usize) -> Self {
+        let mut trainer =
+            NetworkTrainer::from_parameters(vec![0.; PARAMETERS], learning_rate, threads);
+        //About 30 features are active at once
+        let rng = &mut trainer.rng;
+        for weight in trainer.parameters[..FEATURE_BIASES].iter_mut() {
+            *weight = rng.gen_range(-0.1, 0.1);
+        }
+        for weight in trainer.parameters[OUTPUT_WEIGHTS..OUTPUT_BIAS].iter_mut() {
+            *weight = rng.gen_range(-0.1, 0.1);
+        }
+        trainer
+    }
+
+    fn from_parameters(parameters: Vec<f32>, learning_rate: f32, threads: usize) -> Self {
+        assert_eq!(parameters.len(), PARAMETERS);
+        NetworkTrainer {
+            parameters,
+            learning_rate,
+            threads: threads.max(1),
+            adam_m: vec![0.; PARAMETERS],
+            adam_v: vec![0.; PARAMETERS],
+            adam_steps: 0,
+            gradients: Vec::new(),
+            rng: SeedableRng::from_seed([42; 32]),
+        }
+    }
+
+    //Checkpoints hold the unquantized parameters as little endian f32, so training can go on
+    //without losing precision. The optimizer starts over
+    pub fn load_checkpoint(path: &str, learning_rate: f32, threads: usize) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        if bytes.len() != 4 * PARAMETERS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "checkpoint has {} bytes, expected {}",
+                    bytes.len(),
+                    4 * PARAMETERS
+                ),
+            ));
+        }
+        let parameters = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        Ok(NetworkTrainer::from_parameters(
+            parameters,
+            learning_rate,
+            threads,
+        ))
+    }
+
+    pub fn save_checkpoint(&self, path: &str) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(4 * PARAMETERS);
+        for parameter in self.parameters.iter() {
+            bytes.extend_from_slice(&parameter.to_le_bytes());
+        }
+        fs::write(path, bytes)
+    }
+
+    //Quantized the way the engine evaluates
+    pub fn to_network(&self) -> Network {
+        let quantize = |params: &[f32], scale: i32| -> Vec<i16> {
+            params
+                .iter()
+                .map(|p| (p * scale as f32).round().clamp(-32768., 32767.) as i16)
+                .collect()
+        };
+        Network::new(
+            quantize(&self.parameters[..FEATURE_BIASES], QA),
+            quantize(&self.parameters[FEATURE_BIASES..OUTPUT_WEIGHTS], QA),
+            quantize(&self.parameters[OUTPUT_WEIGHTS..OUTPUT_BIAS], QB),
+            (self.parameters[OUTPUT_BIAS] * (QA * QB) as f32).round() as i32,
+        )
+    }
+
+    //In units of EVAL_SCALE centipawns, from the point of view of the side to move
+    pub fn evaluate(&self, position: &TrainingPosition) -> f32 {
+        let mut accumulators = [[0f32; HIDDEN_SIZE]; 2];
+        forward(&self.parameters, position, &mut accumulators)
+    }
+
+    //Average error of the predictions
+    pub fn loss(&self, positions: &[TrainingPosition]) -> f64 {
+        let mut accumulators = [[0f32; HIDDEN_SIZE]; 2];
+        let sum: f64 = positions
+            .iter()
+            .map(|position| {
+                let output = forward(&self.parameters, position, &mut accumulators);
+                f64::from((winning_chance(output) - position.target).powi(2))
+            })
+            .sum();
+        sum / positions.len().max(1) as f64
+    }
+
+    //Shuffles the positions and does one pass over them, returning the average training error
+    pub fn train_epoch(&mut self, positions: &mut [TrainingPosition], batch_size: usize) -> f64 {
+        positions.shuffle(&mut self.rng);
+        let mut loss = 0.;
+        for batch in positions.chunks(batch_size.max(1)) {
+            loss += self.train_batch(batch);
+        }
+        loss / positions.len().max(1) as f64
+    }
+
+    //Returns the summed error of the batch
+    fn train_batch(&mut self, batch: &[TrainingPosition]) -> f64 {
+        let threads = self.threads;
+        self.gradients.resize_with(threads, || vec![0.; PARAMETERS]);
+        let chunk_size = ((batch.len() + threads - 1) / threads).max(1);
+        let used = (batch.len() + chunk_size - 1) / chunk_size;
+        let parameters = &self.parameters;
+        let gradients = &mut self.gradients;
+        let loss: f64 = thread::scope(|scope| {
+            let handles: Vec<_> = gradients
+                .iter_mut()
+                .zip(batch.chunks(chunk_size))
+                .map(|(gradient, chunk)| {
+                    scope.spawn(move || {
+                        gradient.iter_mut().for_each(|g| *g = 0.);
+                        chunk
+                            .iter()
+                            .map(|position| backward(parameters, position, gradient))
+                            .sum::<f64>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("training thread panicked"))
+                .sum()
+        });
+        self.adam_steps += 1;
+        let bias_correction1 = 1. - ADAM_BETA1.powi(self.adam_steps);
+        let bias_correction2 = 1. - ADAM_BETA2.powi(self.adam_steps);
+        let scale = 1. / batch.len() as f32;
+        for i in 0..PARAMETERS {
+            let gradient: f32 = self.gradients[..used].iter().map(|g| g[i]).sum::<f32>() * scale;
+            self.adam_m[i] = ADAM_BETA1 * self.adam_m[i] + (1. - ADAM_BETA1) * gradient;
+            self.adam_v[i] = ADAM_BETA2 * self.adam_v[i] + (1. - ADAM_BETA2) * gradient * gradient;
+            let m = self.adam_m[i] / bias_correction1;
+            let v = self.adam_v[i] / bias_correction2;
+            self.parameters[i] -= self.learning_rate * m / (v.sqrt() + ADAM_EPSILON);
+        }
+        loss
+    }
+}
+
+//Same scale as the sigmoid of the texel tuner with k = 1
+fn winning_chance(output: f32) -> f32 {
+    1. / (1. + 10f32.powf(-output))
+}
+
+fn forward(
+    parameters: &[f32],
+    position: &TrainingPosition,
+    accumulators: &mut [[f32; HIDDEN_SIZE]; 2],
+) -> f32 {
+    let mut output = parameters[OUTPUT_BIAS];
+    for (perspective, accumulator) in accumulators.iter_mut().enumerate() {
+        accumulator.copy_from_slice(&parameters[FEATURE_BIASES..OUTPUT_WEIGHTS]);
+        for feature in position.features[perspective].iter() {
+            let start = *feature as usize * HIDDEN_SIZE;
+            for (value, weight) in accumulator
+                .iter_mut()
+                .zip(&parameters[start..start + HIDDEN_SIZE])
+            {
+                *value += weight;
+            }
+        }
+        let weights = &parameters[OUTPUT_WEIGHTS + perspective * HIDDEN_SIZE..];
+        for (value, weight) in accumulator.iter().zip(weights) {
+            output += value.clamp(0., 1.) * weight;
+        }
+    }
+    output
+}
+
+//Adds the gradient of the error of one position and returns the error
+fn backward(parameters: &[f32], position: &TrainingPosition, gradient: &mut [f32]) -> f64 {
+    let mut accumulators = [[0f32; HIDDEN_SIZE]; 2];
+    let output = forward(parameters, position, &mut accumulators);
+    let chance = winning_chance(output);
+    let error = chance - position.target;
+    let output_gradient = 2. * error * std::f32::consts::LN_10 * chance * (1. - chance);
+    gradient[OUTPUT_BIAS] += output_gradient;
+    for (perspective, accumulator) in accumulators.iter().enumerate() {
+        let offset = OUTPUT_WEIGHTS + perspective * HIDDEN_SIZE;
+        let mut accumulator_gradient = [0f32; HIDDEN_SIZE];
+        for (i, value) in accumulator.iter().enumerate() {
+            gradient[offset + i] += output_gradient * value.clamp(0., 1.);
+            //The clipped ReLU passes the gradient only inside its range
+            if *value > 0. && *value < 1. {
+                accumulator_gradient[i] = output_gradient * parameters[offset + i];
+                gradient[FEATURE_BIASES + i] += accumulator_gradient[i];
+            }
+        }
+        for feature in position.features[perspective].iter() {
+            let start = *feature as usize * HIDDEN_SIZE;
+            for (g, a) in gradient[start..start + HIDDEN_SIZE]
+                .iter_mut()
+                .zip(accumulator_gradient.iter())
+            {
+                *g += a;
+            }
+        }
+    }
+    f64::from(error * error)
+}
@@ -1,10 +1,25 @@
-use core_sdk::board_representation::game_state::GameState;
+use core_sdk::board_representation::game_state::{GameMove, GameState};
+use core_sdk::book::polyglot::{BookSelection, PolyglotBook};
+
+pub const DEFAULT_BOOK_DEPTH: usize = 20;
+pub const MIN_BOOK_DEPTH: usize = 1;
+pub const MAX_BOOK_DEPTH: usize = 200;
 
 pub struct UCIEngine<'a> {
     pub name: &'a str,
     pub author: &'a str,
     pub contributors: &'a [&'a str],
     pub internal_state: GameState,
+    pub own_book: bool,
+    pub book_file: String,
+    //Last full move number for which the book is consulted
+    pub book_depth: usize,
+    pub book: Option<PolyglotBook>,
+    //Parameters of the classical evaluation, compiled-in ones if empty
+    pub eval_params: String,
+    //Network used instead of the classical evaluation
+    #[cfg(feature = "nnue")]
+    pub eval_file: String,
 }
 
 impl<'a> UCIEngine<'a> {
@@ -14,6 +29,13 @@ impl<'a> UCIEngine<'a> {
             author: &"Fabian von der Warth",
             contributors: &["Erik Imgrund", "Marcin Mielniczuk"],
             internal_state: GameState::standard(),
+            own_book: false,
+            book_file: String::new(),
+            book_depth: DEFAULT_BOOK_DEPTH,
+            book: None,
+            eval_params: String::new(),
+            #[cfg(feature = "nnue")]
+            eval_file: String::new(),
         }
     }
 
@@ -22,4 +44,15 @@ impl<'a> UCIEngine<'a> {
         println!("id author {}", self.author);
         println!("id contributors {}", self.contributors.join(", "))
     }
+
+    pub fn book_move(&self) -> Option<GameMove> {
+        if !self.own_book || self.internal_state.full_moves > self.book_depth {
+            return None;
+        }
+        self.book.as_ref()?.get_move(
+            &self.internal_state,
+            BookSelection::Weighted,
+            &mut rand::thread_rng(),
+        )
+    }
 }
@@ -1,35 +1,47 @@
-use super::uci_engine::UCIEngine;
-use core_sdk::board_representation::game_state::{GameMove, GameMoveType, GameState, PieceType};
+use super::uci_engine::{UCIEngine, MAX_BOOK_DEPTH, MIN_BOOK_DEPTH};
+use core_sdk::board_representation::game_state::{FenError, GameState, MoveError, BLACK, WHITE};
 use core_sdk::board_representation::game_state_attack_container::GameStateAttackContainer;
+use core_sdk::book::polyglot::PolyglotBook;
+use core_sdk::evaluation::eval_params::{EvalParams, DEFAULT_EVAL_PARAMS};
+#[cfg(feature = "nnue")]
+use core_sdk::evaluation::nnue::Network;
+use core_sdk::evaluation::parameters::Parameters;
+use core_sdk::evaluation::trace::TERMS;
+use core_sdk::evaluation::{eval_game_state_with_params, trace_sides};
 use core_sdk::move_generation::makemove::make_move;
-use core_sdk::move_generation::movegen;
+use core_sdk::search::alphabeta::{
+    MAX_SINGULAR_DEPTH, MAX_SINGULAR_MARGIN, MIN_SINGULAR_DEPTH, MIN_SINGULAR_MARGIN,
+};
+use core_sdk::search::builder::{Search, SearchHandle};
 use core_sdk::search::cache::{Cache, MAX_HASH_SIZE, MIN_HASH_SIZE};
 use core_sdk::search::searcher::{
-    search_move, InterThreadCommunicationSystem, MAX_SKIP_RATIO, MAX_THREADS, MIN_SKIP_RATIO,
-    MIN_THREADS,
+    InterThreadCommunicationSystem, MAX_CONTEMPT, MAX_MULTI_PV, MAX_SKIP_RATIO, MAX_THREADS,
+    MIN_CONTEMPT, MIN_MULTI_PV, MIN_SKIP_RATIO, MIN_THREADS,
+};
+use core_sdk::search::skill::{MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO, MIN_SKILL_LEVEL};
+use core_sdk::search::timecontrol::{
+    SearchLimits, TimeControl, MAX_MOVE_OVERHEAD, MIN_MOVE_OVERHEAD,
 };
-use core_sdk::search::timecontrol::{TimeControl, MAX_MOVE_OVERHEAD, MIN_MOVE_OVERHEAD};
 use core_sdk::search::MAX_SEARCH_DEPTH;
+use std::fmt::{self, Display, Formatter};
 use std::io::{BufRead, Write};
 use std::sync::{atomic::Ordering, Arc};
-use std::thread;
-use std::time::Duration;
 use std::u64;
 
-pub fn parse_loop<I: BufRead, O: Write + 'static>(mut stdin: I, stdout: O) {
+pub fn parse_loop<I: BufRead, O: Write + Send + 'static>(mut stdin: I, stdout: O) {
     let mut history: Vec<GameState> = vec![];
+    let mut search: Option<SearchHandle> = None;
 
     let mut us = UCIEngine::standard();
 
     let itcs = Arc::new(InterThreadCommunicationSystem::default_with_output(stdout));
     *itcs.cache() =
         Cache::with_size_threaded(itcs.uci_options().hash_size, itcs.uci_options().threads);
-    let mut movelist = movegen::MoveList::default();
-    let mut attack_container = GameStateAttackContainer::default();
 
     //let mut stdin = io::stdin();
     let mut line = String::new();
     loop {
+        itcs.output().flush().expect("engine output write failed");
         line.clear();
         stdin.read_line(&mut line).unwrap();
         println!("Read line: {}", line.trim());
@@ -43,45 +55,68 @@ pub fn parse_loop<I: BufRead, O: Write + 'static>(mut stdin: I, stdout: O) {
             "uci" => {
                 uci(&us, &itcs);
             }
-            "setoption" => setoption(&arg[1..], &itcs),
+            "setoption" => {
+                stop_search(&mut search);
+                setoption(&arg[1..], &itcs, &mut us)
+            }
 
             "ucinewgame" | "newgame" => {
+                stop_search(&mut search);
                 newgame(&mut us);
                 itcs.cache().clear_threaded(itcs.uci_options().threads);
+                itcs.clear_pawn_hash();
                 itcs.saved_time.store(0, Ordering::Relaxed);
             }
             "isready" => isready(&itcs, true),
-            "position" => {
-                history = position(&mut us, &arg[1..], &mut movelist, &mut attack_container);
-            }
+            "position" => match position(&mut us, &arg[1..], itcs.uci_options().chess960) {
+                Ok(new_history) => history = new_history,
+                Err(e) => writeln!(itcs.output(), "info string {}", e)
+                    .expect("engine output write failed"),
+            },
             "go" => {
+                stop_search(&mut search);
                 isready(&itcs, false);
-                let (tc, depth) = go(&us, &arg[1..]);
+                let (tc, depth, limits, ponder) = go(&us, &arg[1..], &itcs);
+                //Analysis and pondering keep searching instead of playing from the book
+                let book_move = match tc {
+                    TimeControl::Infinite => None,
+                    _ if ponder => None,
+                    _ => us.book_move(),
+                };
+                if let Some(mv) = book_move {
+                    writeln!(itcs.output(), "bestmove {:?}", mv)
+                        .expect("engine output write failed");
+                    continue;
+                }
                 let mut new_history = vec![];
                 for gs in &history {
                     new_history.push(gs.clone());
                 }
                 let new_state = us.internal_state.clone();
-                let itcs = Arc::clone(&itcs);
-                search_move(itcs, depth as i16, new_state, new_history, tc); // ???
-            }
-            "stop" => {
-                *itcs.timeout_flag.write().unwrap() = true;
-                thread::sleep(Duration::from_millis(5));
+                itcs.ponder.store(ponder, Ordering::Relaxed);
+                //Info and bestmove lines are written by the engine state's UCI observer, so stop
+                //and ponderhit are read while it runs
+                search = Some(
+                    Search::new(new_state)
+                        .history(new_history)
+                        .time_control(tc)
+                        .depth(depth)
+                        .limits(limits)
+                        .start_on(&itcs),
+                );
             }
+            "ponderhit" => itcs.ponderhit(),
+            "stop" => stop_search(&mut search),
             "quit" => {
+                stop_search(&mut search);
                 break;
             }
             "d" => {
                 print_internal_state(&us);
             }
             "perft" => perft(&us.internal_state, &arg[1..]),
-            "static" => {
-                println!(
-                    "cp {}",
-                    core_sdk::evaluation::eval_game_state_from_null(&us.internal_state).final_eval
-                );
-            }
+            "eval" => eval(&us, &itcs),
+            "static" => println!("cp {}", static_eval(&us.internal_state, &itcs)),
             _ => {
                 println!("Unknown command {}", line);
             }
@@ -89,6 +124,25 @@ pub fn parse_loop<I: BufRead, O: Write + 'static>(mut stdin: I, stdout: O) {
     }
 }
 
+fn wait_for_search(search: &mut Option<SearchHandle>) {
+    if let Some(handle) = search.take() {
+        handle.wait();
+    }
+}
+
+pub fn stop(search: &Option<SearchHandle>) {
+    if let Some(handle) = search {
+        handle.stop();
+    }
+}
+
+//Commands which need the engine idle end a running search instead of waiting for it, an infinite
+//search or ponder would never finish on its own since stop can't be read meanwhile
+fn stop_search(search: &mut Option<SearchHandle>) {
+    stop(search);
+    wait_for_search(search);
+}
+
 pub fn perft(game_state: &GameState, cmd: &[&str]) {
     let depth = cmd[0].parse::<usize>().unwrap();
     core_sdk::perft_div(&game_state, depth);
@@ -98,71 +152,230 @@ pub fn print_internal_state(engine: &UCIEngine) {
     println!("{}", engine.internal_state);
 }
 
-pub fn go(engine: &UCIEngine, cmd: &[&str]) -> (TimeControl, usize) {
+//The classical evaluation term by term, in centipawns from white's point of view. The final score
+//interpolates between midgame and endgame by the phase, after dividing the endgame by 1.5
+pub fn eval(engine: &UCIEngine, itcs: &InterThreadCommunicationSystem) {
+    let g = &engine.internal_state;
+    let loaded = itcs.eval_params();
+    let params = Parameters::from_eval_params(loaded.as_deref().unwrap_or(&DEFAULT_EVAL_PARAMS));
+    let sides = trace_sides(g);
+    let white = sides[WHITE].terms(&params);
+    let black = sides[BLACK].terms(&params);
+    let cp = |value: f64| value.round() as i32;
+    println!("           Term |     White     |     Black     |     Total");
+    println!("                |    MG     EG  |    MG     EG  |    MG     EG");
+    println!("----------------+---------------+---------------+--------------");
+    let mut total = (0., 0.);
+    for ((name, w), b) in TERMS.iter().zip(white.iter()).zip(black.iter()) {
+        //Black's terms count negatively in its trace
+        let b = (-b.0, -b.1);
+        total = (total.0 + w.0 - b.0, total.1 + w.1 - b.1);
+        println!(
+            "{:>15} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}",
+            name,
+            cp(w.0),
+            cp(w.1),
+            cp(b.0),
+            cp(b.1),
+            cp(w.0 - b.0),
+            cp(w.1 - b.1)
+        );
+    }
+    println!("----------------+---------------+---------------+--------------");
+    println!(
+        "{:>15} | {:>13} | {:>13} | {:>6} {:>6}",
+        "Total",
+        "",
+        "",
+        cp(total.0),
+        cp(total.1)
+    );
+    println!("Phase: {:.2} (128 midgame, 0 endgame)", g.phase.phase);
+    println!(
+        "Classical evaluation: {} cp",
+        cp(sides[WHITE].evaluate(&params) + sides[BLACK].evaluate(&params))
+    );
+    println!("Final evaluation: {} cp", static_eval(g, itcs));
+}
+
+//Static evaluation from white's point of view, with the loaded network or parameters if there
+//are any
+pub fn static_eval(g: &GameState, itcs: &InterThreadCommunicationSystem) -> i16 {
+    #[cfg(feature = "nnue")]
+    {
+        if let Some(network) = itcs.network() {
+            let eval = network.evaluate_state(g);
+            return if g.color_to_move == WHITE {
+                eval
+            } else {
+                -eval
+            };
+        }
+    }
+    let attacks = GameStateAttackContainer::from_state(g);
+    let params = itcs.eval_params();
+    eval_game_state_with_params(
+        g,
+        &attacks,
+        params.as_deref().unwrap_or(&DEFAULT_EVAL_PARAMS),
+    )
+    .final_eval
+}
+
+//Returns the clock, the depth limit, the other limits and whether to ponder
+pub fn go(
+    engine: &UCIEngine,
+    cmd: &[&str],
+    itcs: &InterThreadCommunicationSystem,
+) -> (TimeControl, usize, SearchLimits, bool) {
     let mut wtime: u64 = 0;
     let mut btime: u64 = 0;
     let mut winc: u64 = 0;
     let mut binc: u64 = 0;
     let mut depth = MAX_SEARCH_DEPTH;
-    if cmd[0].to_lowercase() == "infinite" {
-        return (TimeControl::Infinite, depth);
-    } else if cmd[0].to_lowercase() == "depth" {
-        depth = cmd[1].parse::<usize>().unwrap();
-        return (TimeControl::Infinite, depth);
-    }
+    let mut limits = SearchLimits::default();
+    let mut infinite = false;
+    let mut ponder = false;
+    //Without any clock the search only ends on stop or one of the limits
+    let mut clock_given = false;
+    let mut movetime: Option<u64> = None;
     let mut index = 0;
     let mut movestogo: Option<usize> = None;
     while index < cmd.len() {
-        match cmd[index] {
+        match cmd[index].to_lowercase().as_str() {
+            "infinite" => {
+                infinite = true;
+                index += 1;
+                continue;
+            }
+            "ponder" => {
+                ponder = true;
+                index += 1;
+                continue;
+            }
+            "searchmoves" => {
+                index += 1;
+                while index < cmd.len() {
+                    match engine.internal_state.parse_uci_move(cmd[index]) {
+                        Ok(mv) => limits.search_moves.push(mv),
+                        Err(MoveError::IllegalMove(_)) => writeln!(
+                            itcs.output(),
+                            "info string Ignoring illegal searchmove {}",
+                            cmd[index]
+                        )
+                        .expect("engine output write failed"),
+                        Err(MoveError::InvalidSyntax(_)) => break,
+                    }
+                    index += 1;
+                }
+                continue;
+            }
+            "depth" => {
+                depth = cmd[index + 1].parse::<usize>().unwrap_or(MAX_SEARCH_DEPTH);
+            }
+            "nodes" => match cmd.get(index + 1) {
+                Some(nodes) => limits.nodes = nodes.parse::<u64>().ok(),
+                None => writeln!(itcs.output(), "info string go nodes is missing a value")
+                    .expect("engine output write failed"),
+            },
+            "mate" => match cmd.get(index + 1) {
+                Some(moves) => {
+                    limits.mate = moves
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .map(|n| n.min(MAX_SEARCH_DEPTH))
+                }
+                None => writeln!(itcs.output(), "info string go mate is missing a value")
+                    .expect("engine output write failed"),
+            },
             "wtime" => {
                 wtime = cmd[index + 1].parse::<u64>().unwrap_or(0);
+                clock_given = true;
             }
             "btime" => {
                 btime = cmd[index + 1].parse::<u64>().unwrap_or(0);
+                clock_given = true;
             }
             "winc" => {
                 winc = cmd[index + 1].parse::<u64>().unwrap_or(0);
+                clock_given = true;
             }
             "binc" => {
                 binc = cmd[index + 1].parse::<u64>().unwrap_or(0);
+                clock_given = true;
             }
             "movetime" => {
-                let mvtime = cmd[index + 1].parse::<u64>().unwrap_or(0);
-                return (TimeControl::MoveTime(mvtime), depth);
+                movetime = Some(cmd[index + 1].parse::<u64>().unwrap_or(0));
             }
             "movestogo" => movestogo = Some(cmd[index + 1].parse::<usize>().unwrap_or(1)),
             _ => println!("Some parts of the go command weren't recognized well."),
         };
         index += 2;
     }
+    if infinite || movetime.is_none() && !clock_given {
+        return (TimeControl::Infinite, depth, limits, ponder);
+    }
+    if let Some(mvtime) = movetime {
+        return (TimeControl::MoveTime(mvtime), depth, limits, ponder);
+    }
     if movestogo.is_none() {
         if engine.internal_state.color_to_move == 0 {
-            (TimeControl::Incremental(wtime, winc), depth)
+            (TimeControl::Incremental(wtime, winc), depth, limits, ponder)
         } else {
-            (TimeControl::Incremental(btime, binc), depth)
+            (TimeControl::Incremental(btime, binc), depth, limits, ponder)
         }
     } else if let Some(mvs) = movestogo {
         if mvs == 0 {
             panic!("movestogo = 0");
         }
         if engine.internal_state.color_to_move == 0 {
-            (TimeControl::Tournament(wtime, winc, mvs), depth)
+            (
+                TimeControl::Tournament(wtime, winc, mvs),
+                depth,
+                limits,
+                ponder,
+            )
         } else {
-            (TimeControl::Tournament(btime, binc, mvs), depth)
+            (
+                TimeControl::Tournament(btime, binc, mvs),
+                depth,
+                limits,
+                ponder,
+            )
         }
     } else {
         panic!("Something went wrong in go!");
     }
 }
 
+pub enum PositionCmdError {
+    //Neither startpos nor fen follows position, holds what was given instead
+    InvalidSyntax(String),
+    Fen(FenError),
+    Move(MoveError),
+}
+impl Display for PositionCmdError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            PositionCmdError::InvalidSyntax(s) => {
+                write!(formatter, "Expected startpos or fen, got '{}'", s)
+            }
+            PositionCmdError::Fen(e) => write!(formatter, "Invalid FEN: {}", e),
+            PositionCmdError::Move(e) => write!(formatter, "Invalid move: {}", e),
+        }
+    }
+}
+
+//Sets up the position and returns the history leading up to it. On error the engine keeps its
+//previous position
 pub fn position(
     engine: &mut UCIEngine,
     cmd: &[&str],
-    movelist: &mut movegen::MoveList,
-    attack_container: &mut GameStateAttackContainer,
-) -> Vec<GameState> {
+    chess960: bool,
+) -> Result<Vec<GameState>, PositionCmdError> {
     let mut move_index = 1;
-    match cmd[0] {
+    let mut state = match cmd.first().copied().unwrap_or_default() {
         "fen" => {
             let mut fen_string = String::new();
             while move_index < cmd.len() && cmd[move_index].to_lowercase() != "moves" {
@@ -170,72 +383,33 @@ pub fn position(
                 fen_string.push_str(" ");
                 move_index += 1;
             }
-            engine.internal_state = GameState::from_fen(fen_string.trim_end());
-        }
-        "startpos" => {
-            engine.internal_state = GameState::standard();
-        }
-        _ => {
-            panic!("Illegal position cmd");
+            GameState::try_from_fen(fen_string.trim_end()).map_err(PositionCmdError::Fen)?
         }
-    }
+        "startpos" => GameState::standard(),
+        other => return Err(PositionCmdError::InvalidSyntax(other.to_owned())),
+    };
+    state.chess960 |= chess960;
     let mut history: Vec<GameState> = vec![];
-    history.push(engine.internal_state.clone());
     if move_index < cmd.len() && cmd[move_index].to_lowercase() == "moves" {
         move_index += 1;
         while move_index < cmd.len() {
             //Parse the move and make it
-            let mv = cmd[move_index];
-            let (from, to, promo) = GameMove::string_to_move(mv);
-            engine.internal_state = scout_and_make_draftmove(
-                from,
-                to,
-                promo,
-                &engine.internal_state,
-                movelist,
-                attack_container,
-            );
-            history.push(engine.internal_state.clone());
+            let mv = state
+                .parse_uci_move(cmd[move_index])
+                .map_err(PositionCmdError::Move)?;
+            let next_state = make_move(&state, mv);
+            history.push(state);
+            state = next_state;
             move_index += 1;
         }
     }
-    history.pop();
-    history
-}
-
-pub fn scout_and_make_draftmove(
-    from: usize,
-    to: usize,
-    promo_pieces: Option<PieceType>,
-    game_state: &GameState,
-    movelist: &mut movegen::MoveList,
-    attack_container: &mut GameStateAttackContainer,
-) -> GameState {
-    attack_container.write_state(game_state);
-    movegen::generate_moves(&game_state, false, movelist, attack_container);
-    for gmv in movelist.move_list.iter() {
-        let mv = gmv.0;
-        if mv.from as usize == from && mv.to as usize == to {
-            if let GameMoveType::Promotion(ps, _) = mv.move_type {
-                match promo_pieces {
-                    Some(piece) => {
-                        if piece != ps {
-                            continue;
-                        }
-                    }
-                    None => {
-                        continue;
-                    }
-                }
-            }
-            return make_move(&game_state, mv);
-        }
-    }
-    panic!("Invalid move; not found in list!");
+    engine.internal_state = state;
+    Ok(history)
 }
 
 pub fn isready(itcs: &Arc<InterThreadCommunicationSystem>, print_rdy: bool) {
-    if itcs.tx.read().unwrap().len() == 0 {
+    //Nothing may be reallocated while a search is running
+    if itcs.nodes_searched().len() != itcs.uci_options().threads {
         let threads = itcs.uci_options().threads;
         InterThreadCommunicationSystem::update_thread_count(itcs, threads);
     }
@@ -275,10 +449,96 @@ pub fn uci(engine: &UCIEngine, itcs: &InterThreadCommunicationSystem) {
         MIN_SKIP_RATIO,
         MAX_SKIP_RATIO
     );
+    println!(
+        "option name UCI_Chess960 type check default {}",
+        itcs.uci_options().chess960
+    );
+    println!(
+        "option name Ponder type check default {}",
+        itcs.uci_options().ponder
+    );
+    println!(
+        "option name MultiPV type spin default {} min {} max {}",
+        itcs.uci_options().multi_pv,
+        MIN_MULTI_PV,
+        MAX_MULTI_PV
+    );
+    println!(
+        "option name Contempt type spin default {} min {} max {}",
+        itcs.uci_options().contempt,
+        MIN_CONTEMPT,
+        MAX_CONTEMPT
+    );
+    println!(
+        "option name UCI_AnalyseMode type check default {}",
+        itcs.uci_options().analyse_mode
+    );
+    println!(
+        "option name SingularExtensions type check default {}",
+        itcs.uci_options().singular_extensions
+    );
+    println!(
+        "option name SingularDepth type spin default {} min {} max {}",
+        itcs.uci_options().singular_depth,
+        MIN_SINGULAR_DEPTH,
+        MAX_SINGULAR_DEPTH
+    );
+    println!(
+        "option name SingularMargin type spin default {} min {} max {}",
+        itcs.uci_options().singular_margin,
+        MIN_SINGULAR_MARGIN,
+        MAX_SINGULAR_MARGIN
+    );
+    println!(
+        "option name UCI_LimitStrength type check default {}",
+        itcs.uci_options().limit_strength
+    );
+    println!(
+        "option name UCI_Elo type spin default {} min {} max {}",
+        itcs.uci_options().elo,
+        MIN_ELO,
+        MAX_ELO
+    );
+    println!(
+        "option name Skill Level type spin default {} min {} max {}",
+        itcs.uci_options().skill_level,
+        MIN_SKILL_LEVEL,
+        MAX_SKILL_LEVEL
+    );
+    println!("option name OwnBook type check default {}", engine.own_book);
+    println!(
+        "option name BookFile type string default {}",
+        if engine.book_file.is_empty() {
+            "<empty>"
+        } else {
+            &engine.book_file
+        }
+    );
+    println!(
+        "option name BookDepth type spin default {} min {} max {}",
+        engine.book_depth, MIN_BOOK_DEPTH, MAX_BOOK_DEPTH
+    );
+    println!(
+        "option name EvalParams type string default {}",
+        if engine.eval_params.is_empty() {
+            "<empty>"
+        } else {
+            &engine.eval_params
+        }
+    );
+    #[cfg(feature = "nnue")]
+    println!(
+        "option name EvalFile type string default {}",
+        if engine.eval_file.is_empty() {
+            "<empty>"
+        } else {
+            &engine.eval_file
+        }
+    );
     println!("uciok");
 }
 
-pub fn setoption(cmd: &[&str], itcs: &Arc<InterThreadCommunicationSystem>) {
+pub fn setoption(cmd: &[&str], itcs: &Arc<InterThreadCommunicationSystem>, engine: &mut UCIEngine) {
     let mut index = 0;
     while index < cmd.len() {
         let arg = cmd[index];
@@ -300,13 +560,24 @@ pub fn setoption(cmd: &[&str], itcs: &Arc<InterThreadCommunicationSystem>) {
                     .expect("engine output write failed");
                 return;
             }
+            #[cfg(target_arch = "wasm32")]
             "threads" => {
                 eprintln!("Error: threads unsupported in WASI build!");
-                // let num = cmd[index + 2]
-                //     .parse::<usize>()
-                //     .expect("Invalid Threads value!");
-                // InterThreadCommunicationSystem::update_thread_count(&itcs, num);
-                // println!("info String Succesfully set Threads to {}", num);
+                return;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            "threads" => {
+                let num = cmd[index + 2]
+                    .parse::<usize>()
+                    .expect("Invalid Threads value!");
+                let num = num.max(MIN_THREADS).min(MAX_THREADS);
+                InterThreadCommunicationSystem::update_thread_count(itcs, num);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set Threads to {}",
+                    num
+                )
+                .expect("engine output write failed");
                 return;
             }
             "moveoverhead" => {
@@ -348,6 +619,266 @@ pub fn setoption(cmd: &[&str], itcs: &Arc<InterThreadCommunicationSystem>) {
                 .expect("engine output write failed");
                 return;
             }
+            "uci_chess960" => {
+                let val = cmd[index + 2]
+                    .parse::<bool>()
+                    .expect("Invalid UCI_Chess960 value!");
+                itcs.uci_options().chess960 = val;
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set UCI_Chess960 to {}",
+                    val
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "ponder" => {
+                let val = cmd[index + 2]
+                    .parse::<bool>()
+                    .expect("Invalid Ponder value!");
+                itcs.uci_options().ponder = val;
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set Ponder to {}",
+                    val
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "multipv" => {
+                let num = cmd[index + 2]
+                    .parse::<usize>()
+                    .expect("Invalid MultiPV value!");
+                itcs.uci_options().multi_pv = num.max(MIN_MULTI_PV).min(MAX_MULTI_PV);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set MultiPV to {}",
+                    itcs.uci_options().multi_pv
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "contempt" => {
+                let num = cmd[index + 2]
+                    .parse::<i16>()
+                    .expect("Invalid Contempt value!");
+                itcs.uci_options().contempt = num.clamp(MIN_CONTEMPT, MAX_CONTEMPT);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set Contempt to {}",
+                    itcs.uci_options().contempt
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "uci_analysemode" => {
+                let val = cmd[index + 2]
+                    .parse::<bool>()
+                    .expect("Invalid UCI_AnalyseMode value!");
+                itcs.uci_options().analyse_mode = val;
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set UCI_AnalyseMode to {}",
+                    val
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "singularextensions" => {
+                let val = cmd[index + 2]
+                    .parse::<bool>()
+                    .expect("Invalid SingularExtensions value!");
+                itcs.uci_options().singular_extensions = val;
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set SingularExtensions to {}",
+                    val
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "singulardepth" => {
+                let num = cmd[index + 2]
+                    .parse::<i16>()
+                    .expect("Invalid SingularDepth value!");
+                itcs.uci_options().singular_depth =
+                    num.clamp(MIN_SINGULAR_DEPTH, MAX_SINGULAR_DEPTH);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set SingularDepth to {}",
+                    itcs.uci_options().singular_depth
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "singularmargin" => {
+                let num = cmd[index + 2]
+                    .parse::<i16>()
+                    .expect("Invalid SingularMargin value!");
+                itcs.uci_options().singular_margin =
+                    num.clamp(MIN_SINGULAR_MARGIN, MAX_SINGULAR_MARGIN);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set SingularMargin to {}",
+                    itcs.uci_options().singular_margin
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "uci_limitstrength" => {
+                let val = cmd[index + 2]
+                    .parse::<bool>()
+                    .expect("Invalid UCI_LimitStrength value!");
+                itcs.uci_options().limit_strength = val;
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set UCI_LimitStrength to {}",
+                    val
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "uci_elo" => {
+                let num = cmd[index + 2]
+                    .parse::<u32>()
+                    .expect("Invalid UCI_Elo value!");
+                itcs.uci_options().elo = num.max(MIN_ELO).min(MAX_ELO);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set UCI_Elo to {}",
+                    itcs.uci_options().elo
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            //Second word of Skill Level, the first one is skipped like name
+            "level" => {
+                let num = cmd[index + 2]
+                    .parse::<usize>()
+                    .expect("Invalid Skill Level value!");
+                itcs.uci_options().skill_level = num.max(MIN_SKILL_LEVEL).min(MAX_SKILL_LEVEL);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set Skill Level to {}",
+                    itcs.uci_options().skill_level
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "ownbook" => {
+                let val = cmd[index + 2]
+                    .parse::<bool>()
+                    .expect("Invalid OwnBook value!");
+                engine.own_book = val;
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set OwnBook to {}",
+                    val
+                )
+                .expect("engine output write failed");
+                return;
+            }
+            "bookfile" => {
+                //The path may contain spaces
+                let path = cmd[index + 2..].join(" ");
+                engine.book = None;
+                if path.is_empty() || path == "<empty>" {
+                    engine.book_file = String::new();
+                } else {
+                    match PolyglotBook::open(&path) {
+                        Ok(book) => {
+                            writeln!(
+                                itcs.output(),
+                                "info String Succesfully loaded book {} with {} entries",
+                                path,
+                                book.entries.len()
+                            )
+                            .expect("engine output write failed");
+                            engine.book = Some(book);
+                        }
+                        Err(e) => writeln!(
+                            itcs.output(),
+                            "info String Could not load book {}: {}",
+                            path,
+                            e
+                        )
+                        .expect("engine output write failed"),
+                    }
+                    engine.book_file = path;
+                }
+                return;
+            }
+            "evalparams" => {
+                let path = cmd[index + 2..].join(" ");
+                itcs.set_eval_params(None);
+                if path.is_empty() || path == "<empty>" {
+                    engine.eval_params = String::new();
+                } else {
+                    match EvalParams::open(&path) {
+                        Ok(params) => {
+                            itcs.set_eval_params(Some(params));
+                            writeln!(
+                                itcs.output(),
+                                "info String Succesfully loaded evaluation parameters {}",
+                                path
+                            )
+                            .expect("engine output write failed");
+                        }
+                        Err(e) => writeln!(
+                            itcs.output(),
+                            "info String Could not load evaluation parameters {}: {}",
+                            path,
+                            e
+                        )
+                        .expect("engine output write failed"),
+                    }
+                    engine.eval_params = path;
+                }
+                return;
+            }
+            #[cfg(feature = "nnue")]
+            "evalfile" => {
+                //Without a network the classical evaluation is used
+                let path = cmd[index + 2..].join(" ");
+                itcs.set_network(None);
+                if path.is_empty() || path == "<empty>" {
+                    engine.eval_file = String::new();
+                } else {
+                    match Network::open(&path) {
+                        Ok(network) => {
+                            itcs.set_network(Some(network));
+                            writeln!(
+                                itcs.output(),
+                                "info String Succesfully loaded network {}",
+                                path
+                            )
+                            .expect("engine output write failed");
+                        }
+                        Err(e) => writeln!(
+                            itcs.output(),
+                            "info String Could not load network {}: {}",
+                            path,
+                            e
+                        )
+                        .expect("engine output write failed"),
+                    }
+                    engine.eval_file = path;
+                }
+                return;
+            }
+            "bookdepth" => {
+                let num = cmd[index + 2]
+                    .parse::<usize>()
+                    .expect("Invalid BookDepth value!");
+                engine.book_depth = num.max(MIN_BOOK_DEPTH).min(MAX_BOOK_DEPTH);
+                writeln!(
+                    itcs.output(),
+                    "info String Succesfully set BookDepth to {}",
+                    engine.book_depth
+                )
+                .expect("engine output write failed");
+                return;
+            }
             _ => {
                 index += 1;
             }